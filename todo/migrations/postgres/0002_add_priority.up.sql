@@ -0,0 +1,2 @@
+ALTER TABLE "todos" ADD COLUMN "priority" TEXT NOT NULL DEFAULT 'normal'
+    CHECK ("priority" IN ('low', 'normal', 'high', 'urgent'));
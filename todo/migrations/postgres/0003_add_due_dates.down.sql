@@ -0,0 +1 @@
+ALTER TABLE "todos" DROP COLUMN "due_date", DROP COLUMN "due_time";
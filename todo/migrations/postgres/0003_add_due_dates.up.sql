@@ -0,0 +1 @@
+ALTER TABLE "todos" ADD COLUMN "due_date" DATE, ADD COLUMN "due_time" TIME;
@@ -0,0 +1,4 @@
+ALTER TABLE "todos"
+    DROP COLUMN "created_at",
+    DROP COLUMN "updated_at",
+    DROP COLUMN "completed_at";
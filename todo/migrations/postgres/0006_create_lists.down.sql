@@ -0,0 +1,2 @@
+ALTER TABLE "todos" DROP COLUMN IF EXISTS "list";
+DROP TABLE IF EXISTS "lists";
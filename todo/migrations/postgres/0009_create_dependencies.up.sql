@@ -0,0 +1,9 @@
+-- "todo_id" cannot start before "blocker_id" is done.
+CREATE TABLE IF NOT EXISTS "todo_dependencies" (
+    "todo_id" uuid NOT NULL REFERENCES "todos" ("id") ON DELETE CASCADE,
+    "blocker_id" uuid NOT NULL REFERENCES "todos" ("id") ON DELETE CASCADE,
+    PRIMARY KEY ("todo_id", "blocker_id"),
+    CHECK ("todo_id" <> "blocker_id")
+);
+
+CREATE INDEX IF NOT EXISTS "todo_dependencies_blocker_id" ON "todo_dependencies" ("blocker_id");
@@ -0,0 +1,10 @@
+-- Todos are listed by "position". Positions are spaced apart so a moved todo only needs a
+-- value between its new neighbours. Existing todos keep the order they were created in.
+ALTER TABLE "todos" ADD COLUMN "position" DOUBLE PRECISION NOT NULL DEFAULT 0;
+
+UPDATE "todos" SET "position" = 1024 * (
+    SELECT count(*) FROM "todos" AS "earlier"
+    WHERE ("earlier"."created_at", "earlier"."id") <= ("todos"."created_at", "todos"."id")
+);
+
+CREATE INDEX IF NOT EXISTS "todos_list_position" ON "todos" ("list", "position");
@@ -0,0 +1,3 @@
+-- Without the column trashed todos would come back, so they are purged instead.
+DELETE FROM "todos" WHERE "deleted_at" IS NOT NULL;
+ALTER TABLE "todos" DROP COLUMN IF EXISTS "deleted_at";
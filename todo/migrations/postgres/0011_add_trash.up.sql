@@ -0,0 +1,2 @@
+-- Removed todos stay in the trash, with the time they were removed, until they are purged.
+ALTER TABLE "todos" ADD COLUMN "deleted_at" TIMESTAMPTZ;
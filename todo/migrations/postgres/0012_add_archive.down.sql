@@ -0,0 +1,2 @@
+-- Archived todos go back to their lists.
+ALTER TABLE "todos" DROP COLUMN IF EXISTS "archived_at";
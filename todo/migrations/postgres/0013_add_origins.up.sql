@@ -0,0 +1,5 @@
+-- The recurring todo a todo was spawned from as its next occurrence. No foreign key: the
+-- copy outlives its origin once that is purged.
+ALTER TABLE "todos" ADD COLUMN "origin_id" uuid;
+
+CREATE INDEX IF NOT EXISTS "todos_origin_id" ON "todos" ("origin_id");
@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS "todos" (
+    "id" uuid PRIMARY KEY NOT NULL,
+    "message" TEXT NOT NULL,
+    "done" BOOL NOT NULL DEFAULT FALSE
+);
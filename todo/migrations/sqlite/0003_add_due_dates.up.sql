@@ -0,0 +1,2 @@
+ALTER TABLE "todos" ADD COLUMN "due_date" DATE;
+ALTER TABLE "todos" ADD COLUMN "due_time" TIME;
@@ -0,0 +1,3 @@
+ALTER TABLE "todos" DROP COLUMN "created_at";
+ALTER TABLE "todos" DROP COLUMN "updated_at";
+ALTER TABLE "todos" DROP COLUMN "completed_at";
@@ -0,0 +1,6 @@
+-- SQLite only accepts constant defaults when adding columns, so existing rows are
+-- stamped afterwards.
+ALTER TABLE "todos" ADD COLUMN "created_at" DATETIME NOT NULL DEFAULT '1970-01-01 00:00:00';
+ALTER TABLE "todos" ADD COLUMN "updated_at" DATETIME NOT NULL DEFAULT '1970-01-01 00:00:00';
+ALTER TABLE "todos" ADD COLUMN "completed_at" DATETIME;
+UPDATE "todos" SET "created_at" = datetime('now'), "updated_at" = datetime('now');
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS "todos_list";
+ALTER TABLE "todos" DROP COLUMN "list";
+DROP TABLE IF EXISTS "lists";
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS "lists" (
+    "name" TEXT PRIMARY KEY NOT NULL
+);
+
+INSERT OR IGNORE INTO "lists" ("name") VALUES ('inbox');
+
+-- Existing todos end up in the inbox. Renaming a list moves its todos along.
+ALTER TABLE "todos" ADD COLUMN "list" TEXT NOT NULL DEFAULT 'inbox'
+    REFERENCES "lists" ("name") ON UPDATE CASCADE ON DELETE CASCADE;
+
+CREATE INDEX IF NOT EXISTS "todos_list" ON "todos" ("list");
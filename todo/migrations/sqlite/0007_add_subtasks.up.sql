@@ -0,0 +1,4 @@
+-- Removing a todo removes its subtasks too, unless they were handed to another parent first.
+ALTER TABLE "todos" ADD COLUMN "parent_id" uuid REFERENCES "todos" ("id") ON DELETE CASCADE;
+
+CREATE INDEX IF NOT EXISTS "todos_parent_id" ON "todos" ("parent_id");
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS "todos_list_position";
+ALTER TABLE "todos" DROP COLUMN "position";
@@ -0,0 +1,8 @@
+-- Without the column trashed todos would come back, so they are purged instead. Migrations
+-- run without foreign keys, so their tag links and dependencies go first.
+DELETE FROM "todo_tags" WHERE "todo_id" IN (SELECT "id" FROM "todos" WHERE "deleted_at" IS NOT NULL);
+DELETE FROM "todo_dependencies"
+WHERE "todo_id" IN (SELECT "id" FROM "todos" WHERE "deleted_at" IS NOT NULL)
+   OR "blocker_id" IN (SELECT "id" FROM "todos" WHERE "deleted_at" IS NOT NULL);
+DELETE FROM "todos" WHERE "deleted_at" IS NOT NULL;
+ALTER TABLE "todos" DROP COLUMN "deleted_at";
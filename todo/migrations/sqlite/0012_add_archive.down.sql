@@ -0,0 +1,2 @@
+-- Archived todos go back to their lists.
+ALTER TABLE "todos" DROP COLUMN "archived_at";
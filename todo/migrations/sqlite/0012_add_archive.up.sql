@@ -0,0 +1,2 @@
+-- Archived todos are done and kept out of the list, with the time they were archived.
+ALTER TABLE "todos" ADD COLUMN "archived_at" DATETIME;
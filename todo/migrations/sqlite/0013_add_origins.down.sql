@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS "todos_origin_id";
+ALTER TABLE "todos" DROP COLUMN "origin_id";
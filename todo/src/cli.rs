@@ -0,0 +1,351 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+use uuid::Uuid;
+
+use crate::domain::age::Age;
+use crate::domain::due::DueFilter;
+use crate::domain::list::ListName;
+use crate::domain::subtask::{CompletePolicy, RemovePolicy, SubtaskPolicy};
+use crate::domain::tag::Tag;
+use crate::domain::{priority::Priority, todo::Todo, todo_ref::TodoRef};
+use crate::terminal::error::TerminalError;
+use crate::terminal::{parse_due, parse_recurrence, parse_tag_changes, UserOptions};
+
+pub mod migrate;
+
+#[derive(Parser)]
+#[command(
+    about = "A simple todo list",
+    long_about = "A simple todo list. Runs interactively when no command is given.",
+    after_help = "Exit status: 0 on success, 1 when the todo was not found, 2 on errors."
+)]
+pub struct Cli {
+    /// Postgres connection string, used when neither DATABASE_URL nor the `database_url` key
+    /// of the config file is set.
+    #[arg(long, value_name = "URL", global = true)]
+    pub database_url: Option<String>,
+
+    /// Store todos in a local SQLite database file instead of Postgres.
+    #[arg(long, value_name = "PATH", global = true)]
+    pub sqlite: Option<PathBuf>,
+
+    /// Keep todos in memory only; they are lost when the program exits.
+    #[arg(long, conflicts_with = "sqlite", global = true)]
+    pub memory: bool,
+
+    /// How to print todos and results. `json` and `ndjson` print one JSON event per
+    /// message, for scripts.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text, global = true)]
+    pub format: OutputFormat,
+
+    /// Work on this list instead of the default `inbox` list.
+    #[arg(long, value_name = "NAME", global = true)]
+    pub list: Option<ListName>,
+
+    /// What completing a todo with open subtasks does: `block` refuses, `cascade` completes
+    /// the subtasks too.
+    #[arg(long, value_name = "POLICY", default_value_t = CompletePolicy::Block, global = true)]
+    pub complete_parent: CompletePolicy,
+
+    /// What removing a todo with subtasks does: `cascade` removes them too, `reparent` moves
+    /// them up a level.
+    #[arg(long, value_name = "POLICY", default_value_t = RemovePolicy::Cascade, global = true)]
+    pub remove_parent: RemovePolicy,
+
+    /// Archive todos done and left alone for this long, like "30d", once at startup. A todo
+    /// goes along with its subtasks, once all of them are done.
+    #[arg(long, value_name = "AGE", global = true)]
+    pub archive_after: Option<Age>,
+
+    /// Run the commands in FILE, one per line, instead of asking for them. Blank lines and
+    /// lines starting with `#` are skipped. Piped stdin is read the same way.
+    #[arg(long, value_name = "FILE")]
+    pub script: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub pool: PoolArgs,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+impl Cli {
+    pub fn subtask_policy(&self) -> SubtaskPolicy {
+        SubtaskPolicy {
+            complete: self.complete_parent,
+            remove: self.remove_parent,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Ndjson,
+}
+
+#[derive(Args)]
+pub struct PoolArgs {
+    /// Borrow Postgres connections from a pool of at most N connections instead of
+    /// sharing a single one.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub pool_size: Option<u32>,
+
+    /// Close pooled connections that have been idle for this many seconds.
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value_t = 600,
+        requires = "pool_size"
+    )]
+    pub pool_idle_timeout: u64,
+
+    /// Skip checking that a pooled connection is alive before lending it.
+    #[arg(long, requires = "pool_size")]
+    pub no_pool_health_check: bool,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Add a new todo.
+    Add {
+        /// One of low, normal, high or urgent.
+        #[arg(short, long, default_value_t = Priority::Normal)]
+        priority: Priority,
+        /// Add it as a subtask of this todo, given by position or id prefix.
+        #[arg(long, value_name = "TODO")]
+        parent: Option<TodoRef>,
+        #[arg(required = true, value_parser = non_empty)]
+        message: Vec<String>,
+    },
+    /// Show every todo.
+    List {
+        /// Only show todos with this tag, like "#backend" or "@home".
+        #[arg(conflicts_with_all = ["today", "overdue"])]
+        tag: Option<Tag>,
+        /// Only show open todos due today.
+        #[arg(long, conflicts_with = "overdue")]
+        today: bool,
+        /// Only show open todos past their due date.
+        #[arg(long, conflicts_with = "archived")]
+        overdue: bool,
+        /// Show the archived todos instead, most recently archived first.
+        #[arg(long, conflicts_with_all = ["tag", "today"])]
+        archived: bool,
+    },
+    /// Mark a todo as done.
+    Done {
+        /// Position in `todo list` or a unique prefix of the todo id.
+        todo: TodoRef,
+    },
+    /// Mark a done todo as not done again.
+    Undone {
+        /// Position in `todo list` or a unique prefix of the todo id.
+        todo: TodoRef,
+    },
+    /// Change the priority of a todo.
+    Priority {
+        /// Position in `todo list` or a unique prefix of the todo id.
+        todo: TodoRef,
+        /// One of low, normal, high or urgent.
+        priority: Priority,
+    },
+    /// Set when a todo is due.
+    Due {
+        /// Position in `todo list` or a unique prefix of the todo id.
+        todo: TodoRef,
+        /// Like "tomorrow", "2026-11-01" or "fri 17:00"; "none" removes the due date.
+        #[arg(required = true)]
+        when: Vec<String>,
+    },
+    /// Make a todo come back once it is done.
+    Repeat {
+        /// Position in `todo list` or a unique prefix of the todo id.
+        todo: TodoRef,
+        /// Like "daily", "weekdays", "every 3 days" or "monthly on 15"; "none" stops it.
+        #[arg(required = true)]
+        rule: Vec<String>,
+    },
+    /// Show the open todos that are not waiting for another one.
+    Next,
+    /// Make a todo wait until another one is done.
+    Block {
+        /// Position in `todo list` or a unique prefix of the todo id.
+        todo: TodoRef,
+        /// The todo to do first, given the same way.
+        blocker: TodoRef,
+    },
+    /// Stop a todo from waiting for another one.
+    Unblock {
+        /// Position in `todo list` or a unique prefix of the todo id.
+        todo: TodoRef,
+        /// The todo it waits for, given the same way.
+        blocker: TodoRef,
+    },
+    /// Add or remove tags.
+    Tag {
+        /// Position in `todo list` or a unique prefix of the todo id.
+        todo: TodoRef,
+        /// Tags to add as "+foo" and to remove as "-foo".
+        #[arg(required = true, allow_hyphen_values = true)]
+        changes: Vec<String>,
+    },
+    /// Move a todo to another list.
+    Move {
+        /// Position in `todo list` or a unique prefix of the todo id.
+        todo: TodoRef,
+        /// Name of the list to move it to.
+        list: ListName,
+    },
+    /// Move a todo to another position in the list. Todos stay grouped by priority.
+    Mv {
+        /// Position in `todo list` or a unique prefix of the todo id.
+        todo: TodoRef,
+        /// Where it goes, starting at 1.
+        #[arg(value_parser = clap::value_parser!(u64).range(1..))]
+        position: u64,
+    },
+    /// Move a todo to the top of the list.
+    Top {
+        /// Position in `todo list` or a unique prefix of the todo id.
+        todo: TodoRef,
+    },
+    /// Move a todo to the bottom of the list.
+    Bottom {
+        /// Position in `todo list` or a unique prefix of the todo id.
+        todo: TodoRef,
+    },
+    /// Show every list. Pass an action to create, rename or delete one.
+    Lists {
+        #[command(subcommand)]
+        action: Option<ListsAction>,
+    },
+    /// Move a todo and its subtasks to the trash.
+    Rm {
+        /// Position in `todo list` or a unique prefix of the todo id.
+        todo: TodoRef,
+    },
+    /// Move every todo to the trash.
+    Clear,
+    /// Show the removed todos, most recently removed first.
+    Trash,
+    /// Bring a todo back from the trash, with the subtasks removed along with it.
+    Restore {
+        /// Position in `todo trash` or a unique prefix of the todo id.
+        todo: TodoRef,
+    },
+    /// Delete the todos in the trash for good.
+    Purge {
+        /// Only those removed longer ago than this, like "30d", "2w" or "12h".
+        #[arg(long)]
+        older_than: Option<Age>,
+    },
+    /// Archive the done todos, each along with its subtasks once all of them are done.
+    Archive,
+    /// Bring a todo back from the archive, with the subtasks archived along with it.
+    Unarchive {
+        /// Position in `todo list --archived` or a unique prefix of the todo id.
+        todo: TodoRef,
+    },
+    /// Manage the database schema. Applies pending migrations when no action is given.
+    Migrate {
+        #[command(subcommand)]
+        action: Option<MigrateAction>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ListsAction {
+    /// Create an empty list.
+    New { name: ListName },
+    /// Rename a list; its todos move along.
+    Rename { from: ListName, to: ListName },
+    /// Delete a list together with its todos.
+    Rm { name: ListName },
+}
+
+#[derive(Subcommand, Clone, Copy)]
+pub enum MigrateAction {
+    /// Apply every pending migration.
+    Up,
+    /// Show which migrations have been applied.
+    Status,
+    /// Revert the most recently applied migrations.
+    Down {
+        #[arg(long, default_value_t = 1)]
+        steps: usize,
+    },
+}
+
+impl Command {
+    // The interactive action a one-shot command maps to, if any.
+    pub fn user_option(self) -> Result<Option<UserOptions>, TerminalError> {
+        Ok(match self {
+            Command::Add {
+                priority,
+                parent,
+                message,
+            } => {
+                let todo = Todo::new(message.join(" "), Uuid::new_v4()).with_priority(priority);
+                Some(match parent {
+                    Some(parent) => UserOptions::NewSubtask(parent, todo),
+                    None => UserOptions::NewTodo(todo),
+                })
+            }
+            Command::List { archived: true, .. } => Some(UserOptions::ShowArchive),
+            Command::List { tag: Some(tag), .. } => Some(UserOptions::ShowTagged(tag)),
+            Command::List { today: true, .. } => Some(UserOptions::ShowDue(DueFilter::Today)),
+            Command::List { overdue: true, .. } => Some(UserOptions::ShowDue(DueFilter::Overdue)),
+            Command::List { .. } => Some(UserOptions::ShowList),
+            Command::Done { todo } => Some(UserOptions::DoTodo(todo)),
+            Command::Undone { todo } => Some(UserOptions::UndoneTodo(todo)),
+            Command::Priority { todo, priority } => Some(UserOptions::SetPriority(todo, priority)),
+            Command::Due { todo, when } => {
+                Some(UserOptions::SetDue(todo, parse_due(&when.join(" "))?))
+            }
+            Command::Repeat { todo, rule } => Some(UserOptions::SetRecurrence(
+                todo,
+                parse_recurrence(&rule.join(" "))?,
+            )),
+            Command::Next => Some(UserOptions::ShowNext),
+            Command::Block { todo, blocker } => Some(UserOptions::Block(todo, blocker)),
+            Command::Unblock { todo, blocker } => Some(UserOptions::Unblock(todo, blocker)),
+            Command::Tag { todo, changes } => {
+                let (add, remove) = parse_tag_changes(changes.iter().map(String::as_str))?;
+                Some(UserOptions::TagTodo(todo, add, remove))
+            }
+            Command::Move { todo, list } => Some(UserOptions::MoveTodo(todo, list)),
+            Command::Mv { todo, position } => {
+                Some(UserOptions::MoveToPosition(todo, position as usize))
+            }
+            Command::Top { todo } => Some(UserOptions::MoveToTop(todo)),
+            Command::Bottom { todo } => Some(UserOptions::MoveToBottom(todo)),
+            Command::Lists { action: None } => Some(UserOptions::ShowLists),
+            Command::Lists {
+                action: Some(ListsAction::New { name }),
+            } => Some(UserOptions::CreateList(name)),
+            Command::Lists {
+                action: Some(ListsAction::Rename { from, to }),
+            } => Some(UserOptions::RenameList(from, to)),
+            Command::Lists {
+                action: Some(ListsAction::Rm { name }),
+            } => Some(UserOptions::DeleteList(name)),
+            Command::Rm { todo } => Some(UserOptions::RemoveTodo(todo)),
+            Command::Clear => Some(UserOptions::ClearList),
+            Command::Trash => Some(UserOptions::ShowTrash),
+            Command::Restore { todo } => Some(UserOptions::RestoreTodo(todo)),
+            Command::Purge { older_than } => Some(UserOptions::PurgeTrash(older_than)),
+            Command::Archive => Some(UserOptions::ArchiveDone),
+            Command::Unarchive { todo } => Some(UserOptions::UnarchiveTodo(todo)),
+            Command::Migrate { .. } => None,
+        })
+    }
+}
+
+fn non_empty(input: &str) -> Result<String, String> {
+    match input.trim() {
+        "" => Err("the todo message cannot be empty".to_string()),
+        trimmed => Ok(trimmed.to_string()),
+    }
+}
@@ -0,0 +1,82 @@
+use console::style;
+use rusqlite::Connection;
+
+use crate::cli::{Cli, MigrateAction};
+use crate::db::connect::open_unmigrated;
+use crate::db::migrations::{self, Migration, MigrationStatus};
+use crate::repository::todo::error::StorageError;
+use crate::terminal::error::TerminalError;
+use crate::terminal::UserInterface;
+
+pub enum MigrateOutcome {
+    Applied(Vec<&'static Migration>),
+    Reverted(Vec<&'static Migration>),
+    Status(Vec<MigrationStatus>),
+}
+
+// `database_url` is only asked for when migrating Postgres.
+pub async fn run(
+    cli: &Cli,
+    database_url: impl FnOnce() -> String,
+    action: MigrateAction,
+) -> Result<MigrateOutcome, StorageError> {
+    if let Some(path) = &cli.sqlite {
+        let mut connection = Connection::open(path)?;
+        return Ok(match action {
+            MigrateAction::Up => {
+                MigrateOutcome::Applied(migrations::sqlite::migrate_up(&mut connection)?)
+            }
+            MigrateAction::Down { steps } => {
+                MigrateOutcome::Reverted(migrations::sqlite::migrate_down(&mut connection, steps)?)
+            }
+            MigrateAction::Status => {
+                MigrateOutcome::Status(migrations::sqlite::status(&connection)?)
+            }
+        });
+    }
+
+    let client = open_unmigrated(&database_url()).await?;
+    Ok(match action {
+        MigrateAction::Up => {
+            MigrateOutcome::Applied(migrations::postgres::migrate_up(&client).await?)
+        }
+        MigrateAction::Down { steps } => {
+            MigrateOutcome::Reverted(migrations::postgres::migrate_down(&client, steps).await?)
+        }
+        MigrateAction::Status => {
+            MigrateOutcome::Status(migrations::postgres::status(&client).await?)
+        }
+    })
+}
+
+pub fn report(
+    user_interface: &mut dyn UserInterface,
+    outcome: MigrateOutcome,
+) -> Result<(), TerminalError> {
+    match outcome {
+        MigrateOutcome::Applied(applied) if applied.is_empty() => {
+            user_interface.write_interface(&style("Database is up to date.").green())
+        }
+        MigrateOutcome::Applied(applied) => applied.iter().try_for_each(|migration| {
+            user_interface.write_interface(&style(format!("Applied {}", migration.name)).green())
+        }),
+        MigrateOutcome::Reverted(reverted) if reverted.is_empty() => {
+            user_interface.write_interface(&style("No migration to revert.").yellow())
+        }
+        MigrateOutcome::Reverted(reverted) => reverted.iter().try_for_each(|migration| {
+            user_interface.write_interface(&style(format!("Reverted {}", migration.name)).yellow())
+        }),
+        MigrateOutcome::Status(statuses) => statuses.iter().try_for_each(|status| {
+            let line = match status.applied_at {
+                Some(applied_at) => style(format!(
+                    "[applied {}] {}",
+                    applied_at.format("%Y-%m-%d %H:%M:%S"),
+                    status.migration.name
+                ))
+                .green(),
+                None => style(format!("[pending] {}", status.migration.name)).yellow(),
+            };
+            user_interface.write_interface(&line)
+        }),
+    }
+}
@@ -1,264 +1,2176 @@
 use async_trait::async_trait;
+use chrono::{Local, Utc};
 use uuid::Uuid;
 
-use crate::domain::todo::Todo;
+use crate::domain::age::Age;
+use crate::domain::due::{Due, DueFilter};
+use crate::domain::list::ListName;
+use crate::domain::priority::Priority;
+use crate::domain::recurrence::Recurrence;
+use crate::domain::subtask::{CompletePolicy, RemovePolicy, SubtaskPolicy};
+use crate::domain::tag::Tag;
+use crate::domain::todo::{timestamp, Todo};
+use crate::domain::todo_ref::{Resolution, TodoRef};
+use crate::domain::todos::Todos;
 use crate::repository::todo::Storage;
 use crate::terminal::error::TerminalError;
 use crate::terminal::{UserInterface, UserOptions};
+use history::{Change, History};
+pub(crate) mod history;
 
 pub struct TodoControllerImpl {
     pub todo_repository: Box<dyn Storage + Send + Sync>,
     pub user_interface: Box<dyn UserInterface + Send + Sync>,
+    last_shown: Option<Vec<Uuid>>,
+    subtasks: SubtaskPolicy,
+    history: History,
+    archive_after: Option<Age>,
+}
+
+impl TodoControllerImpl {
+    pub fn new(
+        todo_repository: Box<dyn Storage + Send + Sync>,
+        user_interface: Box<dyn UserInterface + Send + Sync>,
+    ) -> Self {
+        TodoControllerImpl {
+            todo_repository,
+            user_interface,
+            last_shown: None,
+            subtasks: SubtaskPolicy::default(),
+            history: History::default(),
+            archive_after: None,
+        }
+    }
+
+    pub fn with_subtask_policy(self, subtasks: SubtaskPolicy) -> Self {
+        TodoControllerImpl { subtasks, ..self }
+    }
+
+    // Done todos left alone for `archive_after` are archived once, before the first command
+    // that is not quit, help, undo or redo.
+    pub fn with_archive_after(self, archive_after: Option<Age>) -> Self {
+        TodoControllerImpl {
+            archive_after,
+            ..self
+        }
+    }
+
+    // Takes back `change`, returning how many todos it touched.
+    async fn revert(&mut self, change: &Change) -> Result<u64, TerminalError> {
+        let storage = &mut self.todo_repository;
+        let todos_modified = match change {
+            Change::Added { id } => storage.remove_todo(*id).await?,
+            Change::Removed { id, reparented } => {
+                let restored = storage.restore_todo(*id).await?;
+                for child in reparented {
+                    storage.set_parent(*child, Some(*id)).await?;
+                }
+                restored
+            }
+            Change::Done { ids, next } => {
+                let mut reopened = 0;
+                for id in ids {
+                    reopened += storage.mark_todo_undone(*id).await?;
+                }
+                if let Some(next) = next {
+                    storage.remove_todo(*next).await?;
+                }
+                reopened
+            }
+            Change::Cleared { ids } => {
+                let mut restored = 0;
+                for id in ids {
+                    restored += storage.restore_todo(*id).await?;
+                }
+                restored
+            }
+            Change::Edited { id, before, .. } => storage.update_todo(*id, before.clone()).await?,
+        };
+        Ok(todos_modified)
+    }
+
+    // Makes `change` again after it was undone, returning how many todos it touched.
+    async fn reapply(&mut self, change: &Change) -> Result<u64, TerminalError> {
+        let storage = &mut self.todo_repository;
+        let todos_modified = match change {
+            Change::Added { id } => storage.restore_todo(*id).await?,
+            Change::Removed { id, reparented } => {
+                let parent = storage.get_todo(*id).await?.and_then(|todo| todo.parent);
+                for child in reparented {
+                    storage.set_parent(*child, parent).await?;
+                }
+                storage.remove_todo(*id).await?
+            }
+            Change::Done { ids, next } => {
+                let mut done = 0;
+                for id in ids {
+                    done += storage.mark_todo_done(*id).await?;
+                }
+                if let Some(next) = next {
+                    storage.restore_todo(*next).await?;
+                }
+                done
+            }
+            // Only the todos that were cleared, not whatever has been added since.
+            Change::Cleared { ids } => {
+                let mut cleared = 0;
+                for id in ids {
+                    cleared += storage.remove_todo(*id).await?;
+                }
+                cleared
+            }
+            Change::Edited { id, after, .. } => storage.update_todo(*id, after.clone()).await?,
+        };
+        Ok(todos_modified)
+    }
+
+    // Moves `uuid` to `index` among the other todos last shown, or last when it is None.
+    // The list is arranged by priority with subtasks under their parent, so a move it would
+    // not show, such as one past todos of another priority, is refused.
+    async fn move_to(
+        &mut self,
+        uuid: Uuid,
+        index: Option<usize>,
+    ) -> Result<Outcome, TerminalError> {
+        let todo_list = self.todo_repository.get_todo_list().await?;
+        let shown: Vec<Uuid> = match &self.last_shown {
+            Some(shown) => shown.clone(),
+            None => arranged(todo_list.clone())
+                .iter()
+                .map(|todo| todo.id)
+                .collect(),
+        };
+        let others: Vec<Uuid> = shown.iter().copied().filter(|id| *id != uuid).collect();
+        let index = index.unwrap_or(others.len()).min(others.len());
+        let before = others.get(index).copied();
+
+        let Some(todo) = todo_list.iter().find(|todo| todo.id == uuid).cloned() else {
+            return self.reorder_todo(uuid, before).await;
+        };
+        let mut moved: Vec<Todo> = todo_list
+            .iter()
+            .filter(|todo| todo.id != uuid)
+            .cloned()
+            .collect();
+        let at = match before {
+            Some(before) => moved.iter().position(|todo| todo.id == before),
+            None => Some(moved.len()),
+        };
+        if let Some(at) = at {
+            moved.insert(at, todo);
+            let shows_at = arranged(Todos::new(moved))
+                .iter()
+                .filter(|todo| todo.id == uuid || others.contains(&todo.id))
+                .position(|todo| todo.id == uuid);
+            if shows_at != Some(index) {
+                return Err(TerminalError::UnreachablePosition(index + 1));
+            }
+        }
+        self.reorder_todo(uuid, before).await
+    }
+
+    // Turns what the user typed into an id, reporting it when nothing matches.
+    async fn resolve(&mut self, todo_ref: TodoRef) -> Result<Option<Uuid>, TerminalError> {
+        if let TodoRef::Id(uuid) = todo_ref {
+            return Ok(Some(uuid));
+        }
+
+        let resolution = match (&todo_ref, &self.last_shown) {
+            (TodoRef::Position(_), Some(shown)) => todo_ref.resolve(shown, &Todos::new(vec![])),
+            _ => {
+                let todo_list = arranged(self.todo_repository.get_todo_list().await?);
+                let shown = match &self.last_shown {
+                    Some(shown) => shown.clone(),
+                    None => todo_list.iter().map(|todo| todo.id).collect(),
+                };
+                todo_ref.resolve(&shown, &todo_list)
+            }
+        };
+        self.found(todo_ref, resolution)
+    }
+
+    // Positions and id prefixes given to `restore` refer to the trash, as `trash` shows it.
+    async fn resolve_trashed(&mut self, todo_ref: TodoRef) -> Result<Option<Uuid>, TerminalError> {
+        let trash = self.todo_repository.get_trash().await?;
+        self.resolve_among(todo_ref, &trash)
+    }
+
+    // Positions and id prefixes given to `unarchive` refer to the archive, as
+    // `list --archived` shows it.
+    async fn resolve_archived(&mut self, todo_ref: TodoRef) -> Result<Option<Uuid>, TerminalError> {
+        let archive = self.todo_repository.get_archive().await?;
+        self.resolve_among(todo_ref, &archive)
+    }
+
+    fn resolve_among(
+        &mut self,
+        todo_ref: TodoRef,
+        todos: &Todos,
+    ) -> Result<Option<Uuid>, TerminalError> {
+        let shown: Vec<Uuid> = todos.iter().map(|todo| todo.id).collect();
+        let resolution = todo_ref.resolve(&shown, todos);
+        self.found(todo_ref, resolution)
+    }
+
+    // Whether the next occurrence of `uuid` was already brought in, wherever it is now.
+    async fn spawned(&mut self, uuid: Uuid, todo_list: &Todos) -> Result<bool, TerminalError> {
+        let spawned = |todos: &Todos| todos.iter().any(|todo| todo.origin == Some(uuid));
+        Ok(spawned(todo_list)
+            || spawned(&self.todo_repository.get_trash().await?)
+            || spawned(&self.todo_repository.get_archive().await?))
+    }
+
+    fn found(
+        &mut self,
+        todo_ref: TodoRef,
+        resolution: Resolution,
+    ) -> Result<Option<Uuid>, TerminalError> {
+        match resolution {
+            Resolution::Found(uuid) => Ok(Some(uuid)),
+            Resolution::NotFound => {
+                self.user_interface.report_not_found()?;
+                Ok(None)
+            }
+            Resolution::Ambiguous(matches) => Err(TerminalError::AmbiguousTodoRef(
+                todo_ref.to_string(),
+                matches,
+            )),
+        }
+    }
+}
+
+// Todos are shown by priority, with subtasks right below their parent.
+fn arranged(mut todo_list: Todos) -> Todos {
+    todo_list.sort_by_priority();
+    todo_list.nest_subtasks();
+    todo_list
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    Completed,
+    NotFound,
+    Unrecognized,
+    Quit,
+}
+
+impl Outcome {
+    fn from_modified(todos_modified: u64) -> Self {
+        match todos_modified {
+            0 => Outcome::NotFound,
+            _ => Outcome::Completed,
+        }
+    }
 }
 
 #[cfg_attr(test, mockall::automock)]
 #[async_trait]
 pub trait TodoController {
     async fn show_list(&mut self) -> Result<(), TerminalError>;
+    async fn show_due(&mut self, filter: DueFilter) -> Result<(), TerminalError>;
+    async fn show_tagged(&mut self, tag: Tag) -> Result<(), TerminalError>;
+    async fn show_next(&mut self) -> Result<(), TerminalError>;
+    async fn show_lists(&mut self) -> Result<(), TerminalError>;
+    async fn create_list(&mut self, name: ListName) -> Result<(), TerminalError>;
+    async fn rename_list(&mut self, from: ListName, to: ListName)
+        -> Result<Outcome, TerminalError>;
+    async fn delete_list(&mut self, name: ListName) -> Result<Outcome, TerminalError>;
+    async fn switch_list(&mut self, name: ListName) -> Result<Outcome, TerminalError>;
+    async fn move_todo(&mut self, uuid: Uuid, list: ListName) -> Result<Outcome, TerminalError>;
+    async fn reorder_todo(
+        &mut self,
+        uuid: Uuid,
+        before: Option<Uuid>,
+    ) -> Result<Outcome, TerminalError>;
     async fn add_todo(&mut self, todo: Todo) -> Result<(), TerminalError>;
     async fn clear_todo_list(&mut self) -> Result<(), TerminalError>;
-    async fn remove_todo(&mut self, uuid: Uuid) -> Result<(), TerminalError>;
-    async fn mark_todo_done(&mut self, uuid: Uuid) -> Result<(), TerminalError>;
+    async fn remove_todo(&mut self, uuid: Uuid) -> Result<Outcome, TerminalError>;
+    async fn show_trash(&mut self) -> Result<(), TerminalError>;
+    async fn restore_todo(&mut self, uuid: Uuid) -> Result<Outcome, TerminalError>;
+    async fn purge_trash(&mut self, older_than: Option<Age>) -> Result<(), TerminalError>;
+    async fn show_archive(&mut self) -> Result<(), TerminalError>;
+    async fn archive_done(&mut self) -> Result<(), TerminalError>;
+    async fn unarchive_todo(&mut self, uuid: Uuid) -> Result<Outcome, TerminalError>;
+    async fn undo(&mut self) -> Result<Outcome, TerminalError>;
+    async fn redo(&mut self) -> Result<Outcome, TerminalError>;
+    async fn mark_todo_done(&mut self, uuid: Uuid) -> Result<Outcome, TerminalError>;
+    async fn mark_todo_undone(&mut self, uuid: Uuid) -> Result<Outcome, TerminalError>;
+    async fn edit_todo(&mut self, uuid: Uuid) -> Result<Outcome, TerminalError>;
+    async fn set_priority(
+        &mut self,
+        uuid: Uuid,
+        priority: Priority,
+    ) -> Result<Outcome, TerminalError>;
+    async fn set_due(&mut self, uuid: Uuid, due: Option<Due>) -> Result<Outcome, TerminalError>;
+    async fn set_recurrence(
+        &mut self,
+        uuid: Uuid,
+        recurrence: Option<Recurrence>,
+    ) -> Result<Outcome, TerminalError>;
+    async fn update_tags(
+        &mut self,
+        uuid: Uuid,
+        add: Vec<Tag>,
+        remove: Vec<Tag>,
+    ) -> Result<Outcome, TerminalError>;
+    async fn add_blocker(&mut self, uuid: Uuid, blocker: Uuid) -> Result<Outcome, TerminalError>;
+    async fn remove_blocker(&mut self, uuid: Uuid, blocker: Uuid)
+        -> Result<Outcome, TerminalError>;
+    async fn execute(&mut self, option: UserOptions) -> Result<Outcome, TerminalError>;
     async fn get_user_intention(&mut self) -> Result<(), TerminalError>;
+    async fn run_script(&mut self) -> Result<Outcome, TerminalError>;
 }
 
 #[async_trait]
 impl TodoController for TodoControllerImpl {
     async fn get_user_intention(&mut self) -> Result<(), TerminalError> {
         loop {
-            match self.user_interface.user_intention()? {
-                UserOptions::Quit => break,
-                UserOptions::NewTodo(todo) => self.add_todo(todo).await?,
-                UserOptions::Help => self.user_interface.show_help()?,
-                UserOptions::ClearList => self.clear_todo_list().await?,
-                UserOptions::RemoveTodo(index) => self.remove_todo(index).await?,
-                UserOptions::Unrecognized => self.user_interface.alert_unrecognized()?,
-                UserOptions::ShowList => self.show_list().await?,
-                UserOptions::DoTodo(index) => self.mark_todo_done(index).await?,
+            let option = self.user_interface.user_intention()?;
+            if self.execute(option).await? == Outcome::Quit {
+                break;
             }
         }
         self.user_interface.write_interface(&"Ok, quitting now.")?;
         Ok(())
     }
 
-    async fn show_list(&mut self) -> Result<(), TerminalError> {
-        let todo_list = self.todo_repository.get_todo_list().await?;
-        self.user_interface.show_todo_list(todo_list)?;
-        Ok(())
-    }
+    // Runs commands until the input ends, stopping at the first one that fails.
+    async fn run_script(&mut self) -> Result<Outcome, TerminalError> {
+        loop {
+            let option = self.user_interface.user_intention()?;
+            match self.execute(option).await? {
+                Outcome::Completed => {}
+                outcome => return Ok(outcome),
+            }
+        }
+    }
+
+    async fn execute(&mut self, option: UserOptions) -> Result<Outcome, TerminalError> {
+        let archives = !matches!(
+            option,
+            UserOptions::Quit | UserOptions::Help | UserOptions::Undo | UserOptions::Redo
+        );
+        if let Some(age) = self.archive_after.filter(|_| archives) {
+            self.archive_after = None;
+            self.todo_repository
+                .archive_done(age.before(Utc::now()))
+                .await?;
+        }
+        match option {
+            UserOptions::Quit => return Ok(Outcome::Quit),
+            UserOptions::NewTodo(todo) => self.add_todo(todo).await?,
+            UserOptions::NewSubtask(parent_ref, todo) => {
+                let parent = match self.resolve(parent_ref).await? {
+                    Some(uuid) => self.todo_repository.get_todo(uuid).await?,
+                    None => return Ok(Outcome::NotFound),
+                };
+                match parent {
+                    Some(parent) => self.add_todo(todo.with_parent(parent.id)).await?,
+                    None => {
+                        self.user_interface.report_not_found()?;
+                        return Ok(Outcome::NotFound);
+                    }
+                }
+            }
+            UserOptions::Help => self.user_interface.show_help()?,
+            UserOptions::ClearList => self.clear_todo_list().await?,
+            UserOptions::Unrecognized => {
+                self.user_interface.alert_unrecognized()?;
+                return Ok(Outcome::Unrecognized);
+            }
+            UserOptions::ShowList => self.show_list().await?,
+            UserOptions::ShowDue(filter) => self.show_due(filter).await?,
+            UserOptions::ShowTagged(tag) => self.show_tagged(tag).await?,
+            UserOptions::ShowNext => self.show_next().await?,
+            UserOptions::Block(todo_ref, blocker_ref) => {
+                let Some(uuid) = self.resolve(todo_ref).await? else {
+                    return Ok(Outcome::NotFound);
+                };
+                return match self.resolve(blocker_ref).await? {
+                    Some(blocker) => self.add_blocker(uuid, blocker).await,
+                    None => Ok(Outcome::NotFound),
+                };
+            }
+            UserOptions::Unblock(todo_ref, blocker_ref) => {
+                let Some(uuid) = self.resolve(todo_ref).await? else {
+                    return Ok(Outcome::NotFound);
+                };
+                return match self.resolve(blocker_ref).await? {
+                    Some(blocker) => self.remove_blocker(uuid, blocker).await,
+                    None => Ok(Outcome::NotFound),
+                };
+            }
+            UserOptions::RemoveTodo(todo_ref) => {
+                return match self.resolve(todo_ref).await? {
+                    Some(uuid) => self.remove_todo(uuid).await,
+                    None => Ok(Outcome::NotFound),
+                }
+            }
+            UserOptions::DoTodo(todo_ref) => {
+                return match self.resolve(todo_ref).await? {
+                    Some(uuid) => self.mark_todo_done(uuid).await,
+                    None => Ok(Outcome::NotFound),
+                }
+            }
+            UserOptions::UndoneTodo(todo_ref) => {
+                return match self.resolve(todo_ref).await? {
+                    Some(uuid) => self.mark_todo_undone(uuid).await,
+                    None => Ok(Outcome::NotFound),
+                }
+            }
+            UserOptions::EditTodo(todo_ref) => {
+                return match self.resolve(todo_ref).await? {
+                    Some(uuid) => self.edit_todo(uuid).await,
+                    None => Ok(Outcome::NotFound),
+                }
+            }
+            UserOptions::SetPriority(todo_ref, priority) => {
+                return match self.resolve(todo_ref).await? {
+                    Some(uuid) => self.set_priority(uuid, priority).await,
+                    None => Ok(Outcome::NotFound),
+                }
+            }
+            UserOptions::SetDue(todo_ref, due) => {
+                return match self.resolve(todo_ref).await? {
+                    Some(uuid) => self.set_due(uuid, due).await,
+                    None => Ok(Outcome::NotFound),
+                }
+            }
+            UserOptions::SetRecurrence(todo_ref, recurrence) => {
+                return match self.resolve(todo_ref).await? {
+                    Some(uuid) => self.set_recurrence(uuid, recurrence).await,
+                    None => Ok(Outcome::NotFound),
+                }
+            }
+            UserOptions::TagTodo(todo_ref, add, remove) => {
+                return match self.resolve(todo_ref).await? {
+                    Some(uuid) => self.update_tags(uuid, add, remove).await,
+                    None => Ok(Outcome::NotFound),
+                }
+            }
+            UserOptions::ShowLists => self.show_lists().await?,
+            UserOptions::CreateList(name) => self.create_list(name).await?,
+            UserOptions::RenameList(from, to) => return self.rename_list(from, to).await,
+            UserOptions::DeleteList(name) => return self.delete_list(name).await,
+            UserOptions::SwitchList(name) => return self.switch_list(name).await,
+            UserOptions::MoveTodo(todo_ref, list) => {
+                return match self.resolve(todo_ref).await? {
+                    Some(uuid) => self.move_todo(uuid, list).await,
+                    None => Ok(Outcome::NotFound),
+                }
+            }
+            UserOptions::MoveToPosition(todo_ref, position) => {
+                return match self.resolve(todo_ref).await? {
+                    Some(uuid) => self.move_to(uuid, Some(position - 1)).await,
+                    None => Ok(Outcome::NotFound),
+                }
+            }
+            UserOptions::MoveToTop(todo_ref) => {
+                return match self.resolve(todo_ref).await? {
+                    Some(uuid) => self.move_to(uuid, Some(0)).await,
+                    None => Ok(Outcome::NotFound),
+                }
+            }
+            UserOptions::MoveToBottom(todo_ref) => {
+                return match self.resolve(todo_ref).await? {
+                    Some(uuid) => self.move_to(uuid, None).await,
+                    None => Ok(Outcome::NotFound),
+                }
+            }
+            UserOptions::ShowTrash => self.show_trash().await?,
+            UserOptions::RestoreTodo(todo_ref) => {
+                return match self.resolve_trashed(todo_ref).await? {
+                    Some(uuid) => self.restore_todo(uuid).await,
+                    None => Ok(Outcome::NotFound),
+                }
+            }
+            UserOptions::PurgeTrash(older_than) => self.purge_trash(older_than).await?,
+            UserOptions::ShowArchive => self.show_archive().await?,
+            UserOptions::ArchiveDone => self.archive_done().await?,
+            UserOptions::UnarchiveTodo(todo_ref) => {
+                return match self.resolve_archived(todo_ref).await? {
+                    Some(uuid) => self.unarchive_todo(uuid).await,
+                    None => Ok(Outcome::NotFound),
+                }
+            }
+            UserOptions::Undo => return self.undo().await,
+            UserOptions::Redo => return self.redo().await,
+        }
+        Ok(Outcome::Completed)
+    }
+
+    async fn show_list(&mut self) -> Result<(), TerminalError> {
+        let todo_list = arranged(self.todo_repository.get_todo_list().await?);
+        self.last_shown = Some(todo_list.iter().map(|todo| todo.id).collect());
+        self.user_interface.show_todo_list(todo_list)?;
+        Ok(())
+    }
+
+    // Positions typed afterwards refer to this filtered list.
+    async fn show_due(&mut self, filter: DueFilter) -> Result<(), TerminalError> {
+        let todo_list = self.todo_repository.get_todo_list().await?;
+        let now = Local::now().naive_local();
+        let todo_list = arranged(Todos::new(
+            todo_list
+                .iter()
+                .filter(|todo| !todo.done && filter.matches(todo.due.as_ref(), now))
+                .cloned()
+                .collect(),
+        ));
+        self.last_shown = Some(todo_list.iter().map(|todo| todo.id).collect());
+        self.user_interface.show_todo_list(todo_list)?;
+        Ok(())
+    }
+
+    // Positions typed afterwards refer to this filtered list.
+    async fn show_tagged(&mut self, tag: Tag) -> Result<(), TerminalError> {
+        let todo_list = arranged(self.todo_repository.get_todos_tagged(tag).await?);
+        self.last_shown = Some(todo_list.iter().map(|todo| todo.id).collect());
+        self.user_interface.show_todo_list(todo_list)?;
+        Ok(())
+    }
+
+    // Open todos that wait for nothing. Positions typed afterwards refer to this list.
+    async fn show_next(&mut self) -> Result<(), TerminalError> {
+        let todo_list = self.todo_repository.get_todo_list().await?;
+        let todo_list = arranged(Todos::new(
+            todo_list
+                .iter()
+                .filter(|todo| !todo.done && !todo_list.is_blocked(todo))
+                .cloned()
+                .collect(),
+        ));
+        self.last_shown = Some(todo_list.iter().map(|todo| todo.id).collect());
+        self.user_interface.show_todo_list(todo_list)?;
+        Ok(())
+    }
+
+    async fn show_lists(&mut self) -> Result<(), TerminalError> {
+        let lists = self.todo_repository.get_lists().await?;
+        let current = self.todo_repository.current_list();
+        self.user_interface.show_lists(lists, &current)?;
+        Ok(())
+    }
+
+    async fn create_list(&mut self, name: ListName) -> Result<(), TerminalError> {
+        if !self.todo_repository.create_list(name.clone()).await? {
+            return Err(TerminalError::ListExists(name));
+        }
+        self.user_interface.list_created_message(&name)?;
+        Ok(())
+    }
+
+    async fn rename_list(
+        &mut self,
+        from: ListName,
+        to: ListName,
+    ) -> Result<Outcome, TerminalError> {
+        if from.is_default() {
+            return Err(TerminalError::ProtectedList(from));
+        }
+        let lists = self.todo_repository.get_lists().await?;
+        if lists.iter().any(|todo_list| todo_list.name == to) {
+            return Err(TerminalError::ListExists(to));
+        }
+        let lists_modified = self
+            .todo_repository
+            .rename_list(from.clone(), to.clone())
+            .await?;
+        match lists_modified {
+            0 => self.user_interface.report_list_not_found(&from)?,
+            _ => self.user_interface.list_renamed_message(&from, &to)?,
+        }
+        Ok(Outcome::from_modified(lists_modified))
+    }
+
+    // Deleting the current list moves back to the default one.
+    async fn delete_list(&mut self, name: ListName) -> Result<Outcome, TerminalError> {
+        if name.is_default() {
+            return Err(TerminalError::ProtectedList(name));
+        }
+        let current = self.todo_repository.current_list();
+        let lists_modified = self.todo_repository.delete_list(name.clone()).await?;
+        match lists_modified {
+            0 => self.user_interface.report_list_not_found(&name)?,
+            _ => {
+                self.user_interface.list_deleted_message(&name)?;
+                if name == current {
+                    self.last_shown = None;
+                    self.history.clear();
+                    let current = self.todo_repository.current_list();
+                    self.user_interface.switch_list_message(&current)?;
+                }
+            }
+        }
+        Ok(Outcome::from_modified(lists_modified))
+    }
+
+    async fn switch_list(&mut self, name: ListName) -> Result<Outcome, TerminalError> {
+        if !self.todo_repository.switch_list(name.clone()).await? {
+            self.user_interface.report_list_not_found(&name)?;
+            return Ok(Outcome::NotFound);
+        }
+        self.history.clear();
+        self.user_interface.switch_list_message(&name)?;
+        self.show_list().await?;
+        Ok(Outcome::Completed)
+    }
+
+    async fn move_todo(&mut self, uuid: Uuid, list: ListName) -> Result<Outcome, TerminalError> {
+        let lists = self.todo_repository.get_lists().await?;
+        if !lists.iter().any(|todo_list| todo_list.name == list) {
+            self.user_interface.report_list_not_found(&list)?;
+            return Ok(Outcome::NotFound);
+        }
+        let todos_modified = self.todo_repository.move_todo(uuid, list.clone()).await?;
+        match todos_modified {
+            0 => self.user_interface.report_not_found()?,
+            _ => {
+                self.user_interface
+                    .move_todo_message(uuid, &list, todos_modified)?;
+                self.show_list().await?;
+            }
+        }
+        Ok(Outcome::from_modified(todos_modified))
+    }
+
+    async fn reorder_todo(
+        &mut self,
+        uuid: Uuid,
+        before: Option<Uuid>,
+    ) -> Result<Outcome, TerminalError> {
+        let todos_modified = self.todo_repository.reorder_todo(uuid, before).await?;
+        match todos_modified {
+            0 => self.user_interface.report_not_found()?,
+            _ => {
+                self.user_interface
+                    .reorder_todo_message(uuid, todos_modified)?;
+                self.show_list().await?;
+            }
+        }
+        Ok(Outcome::from_modified(todos_modified))
+    }
+
+    async fn add_todo(&mut self, todo: Todo) -> Result<(), TerminalError> {
+        self.todo_repository.add_todo(todo.clone()).await?;
+        self.history.record(Change::Added { id: todo.id });
+        self.user_interface.add_todo_message(&todo)?;
+        self.show_list().await?;
+        Ok(())
+    }
+
+    async fn clear_todo_list(&mut self) -> Result<(), TerminalError> {
+        let todo_list = self.todo_repository.get_todo_list().await?;
+        let top: Vec<Uuid> = todo_list
+            .iter()
+            .filter(|todo| {
+                todo.parent
+                    .is_none_or(|parent| !todo_list.iter().any(|other| other.id == parent))
+            })
+            .map(|todo| todo.id)
+            .collect();
+        self.todo_repository.clear_todo_list().await?;
+        self.history.record(Change::Cleared { ids: top });
+        self.user_interface.clear_todo_message()?;
+        Ok(())
+    }
+
+    async fn remove_todo(&mut self, uuid: Uuid) -> Result<Outcome, TerminalError> {
+        let mut reparented = Vec::new();
+        if self.subtasks.remove == RemovePolicy::Reparent {
+            // The children take the removed todo's place under its own parent.
+            if let Some(todo) = self.todo_repository.get_todo(uuid).await? {
+                let todo_list = self.todo_repository.get_todo_list().await?;
+                for child in todo_list.children(uuid) {
+                    self.todo_repository
+                        .set_parent(child.id, todo.parent)
+                        .await?;
+                    reparented.push(child.id);
+                }
+            }
+        }
+        let todos_modified = self.todo_repository.remove_todo(uuid).await?;
+        match todos_modified {
+            0 => self.user_interface.report_not_found()?,
+            _ => {
+                self.history.record(Change::Removed {
+                    id: uuid,
+                    reparented,
+                });
+                self.user_interface
+                    .remove_todo_message(uuid, todos_modified)?;
+            }
+        }
+        Ok(Outcome::from_modified(todos_modified))
+    }
+
+    async fn show_trash(&mut self) -> Result<(), TerminalError> {
+        let trash = self.todo_repository.get_trash().await?;
+        self.user_interface.show_trash(trash)?;
+        Ok(())
+    }
+
+    async fn restore_todo(&mut self, uuid: Uuid) -> Result<Outcome, TerminalError> {
+        let todos_modified = self.todo_repository.restore_todo(uuid).await?;
+        match todos_modified {
+            0 => self.user_interface.report_not_found()?,
+            _ => {
+                self.user_interface
+                    .restore_todo_message(uuid, todos_modified)?;
+                self.show_list().await?;
+            }
+        }
+        Ok(Outcome::from_modified(todos_modified))
+    }
+
+    async fn purge_trash(&mut self, older_than: Option<Age>) -> Result<(), TerminalError> {
+        let deleted_before = match older_than {
+            Some(age) => age.before(Utc::now()),
+            None => timestamp(),
+        };
+        let purged = self.todo_repository.purge_trash(deleted_before).await?;
+        self.user_interface.purge_message(purged)?;
+        Ok(())
+    }
+
+    async fn show_archive(&mut self) -> Result<(), TerminalError> {
+        let archive = self.todo_repository.get_archive().await?;
+        self.user_interface.show_archive(archive)?;
+        Ok(())
+    }
+
+    async fn archive_done(&mut self) -> Result<(), TerminalError> {
+        let archived = self.todo_repository.archive_done(timestamp()).await?;
+        self.user_interface.archive_message(archived)?;
+        if archived > 0 {
+            self.show_list().await?;
+        }
+        Ok(())
+    }
+
+    async fn unarchive_todo(&mut self, uuid: Uuid) -> Result<Outcome, TerminalError> {
+        let todos_modified = self.todo_repository.unarchive_todo(uuid).await?;
+        match todos_modified {
+            0 => self.user_interface.report_not_found()?,
+            _ => {
+                self.user_interface
+                    .unarchive_todo_message(uuid, todos_modified)?;
+                self.show_list().await?;
+            }
+        }
+        Ok(Outcome::from_modified(todos_modified))
+    }
+
+    // Undo and redo only cover the current list; switching lists starts over.
+    async fn undo(&mut self) -> Result<Outcome, TerminalError> {
+        let Some(change) = self.history.undo() else {
+            self.user_interface.history_empty_message("undo")?;
+            return Ok(Outcome::NotFound);
+        };
+        let todos_modified = self.revert(&change).await?;
+        match todos_modified {
+            0 => self.user_interface.report_not_found()?,
+            _ => {
+                self.user_interface.undo_message(change.command())?;
+                self.show_list().await?;
+            }
+        }
+        Ok(Outcome::from_modified(todos_modified))
+    }
+
+    async fn redo(&mut self) -> Result<Outcome, TerminalError> {
+        let Some(change) = self.history.redo() else {
+            self.user_interface.history_empty_message("redo")?;
+            return Ok(Outcome::NotFound);
+        };
+        let todos_modified = self.reapply(&change).await?;
+        match todos_modified {
+            0 => self.user_interface.report_not_found()?,
+            _ => {
+                self.user_interface.redo_message(change.command())?;
+                self.show_list().await?;
+            }
+        }
+        Ok(Outcome::from_modified(todos_modified))
+    }
+
+    async fn mark_todo_done(&mut self, uuid: Uuid) -> Result<Outcome, TerminalError> {
+        let todo_list = self.todo_repository.get_todo_list().await?;
+        let open: Vec<Uuid> = todo_list
+            .descendants(uuid)
+            .iter()
+            .filter(|todo| !todo.done)
+            .map(|todo| todo.id)
+            .collect();
+        let mut cascaded = 0;
+        if !open.is_empty() {
+            match self.subtasks.complete {
+                CompletePolicy::Block => return Err(TerminalError::OpenSubtasks(open.len())),
+                CompletePolicy::Cascade => {
+                    for subtask in &open {
+                        cascaded += self.todo_repository.mark_todo_done(*subtask).await?;
+                    }
+                }
+            }
+        }
+        let todo = todo_list.iter().find(|todo| todo.id == uuid);
+        let blockers: Vec<Uuid> = todo
+            .map(|todo| todo_list.open_blockers(todo))
+            .unwrap_or_default()
+            .iter()
+            .map(|blocker| blocker.id)
+            .collect();
+        // Marking a todo done again must not bring it back twice, and neither must reopening
+        // it and marking it done once its next occurrence exists, even trashed or archived.
+        let next = match todo.filter(|todo| !todo.done && todo.recurrence.is_some()) {
+            Some(todo) if !self.spawned(uuid, &todo_list).await? => {
+                todo.next_occurrence(Local::now().date_naive())
+            }
+            _ => None,
+        };
+        let was_open = todo.is_some_and(|todo| !todo.done);
+        let todos_modified = self.todo_repository.mark_todo_done(uuid).await?;
+        match todos_modified {
+            0 => self.user_interface.report_not_found()?,
+            _ => {
+                self.user_interface
+                    .mark_done_message(uuid, todos_modified + cascaded)?;
+                if !blockers.is_empty() {
+                    self.user_interface.open_blockers_warning(uuid, &blockers)?;
+                }
+                if let Some(next) = &next {
+                    self.todo_repository.add_todo(next.clone()).await?;
+                    self.user_interface.next_occurrence_message(next)?;
+                }
+                let mut ids = open;
+                if was_open {
+                    ids.push(uuid);
+                }
+                self.history.record(Change::Done {
+                    ids,
+                    next: next.map(|next| next.id),
+                });
+                self.show_list().await?;
+            }
+        }
+        Ok(Outcome::from_modified(todos_modified))
+    }
+
+    async fn mark_todo_undone(&mut self, uuid: Uuid) -> Result<Outcome, TerminalError> {
+        let todos_modified = self.todo_repository.mark_todo_undone(uuid).await?;
+        match todos_modified {
+            0 => self.user_interface.report_not_found()?,
+            _ => {
+                self.user_interface
+                    .mark_undone_message(uuid, todos_modified)?;
+                self.show_list().await?;
+            }
+        }
+        Ok(Outcome::from_modified(todos_modified))
+    }
+
+    async fn edit_todo(&mut self, uuid: Uuid) -> Result<Outcome, TerminalError> {
+        let todo = match self.todo_repository.get_todo(uuid).await? {
+            Some(todo) => todo,
+            None => {
+                self.user_interface.report_not_found()?;
+                return Ok(Outcome::NotFound);
+            }
+        };
+        let message = self.user_interface.prompt_edit_todo(&todo)?;
+        let todos_modified = self
+            .todo_repository
+            .update_todo(uuid, message.clone())
+            .await?;
+        match todos_modified {
+            0 => self.user_interface.report_not_found()?,
+            _ => {
+                self.history.record(Change::Edited {
+                    id: uuid,
+                    before: todo.message,
+                    after: message,
+                });
+                self.user_interface
+                    .edit_todo_message(uuid, todos_modified)?;
+                self.show_list().await?;
+            }
+        }
+        Ok(Outcome::from_modified(todos_modified))
+    }
+
+    async fn set_priority(
+        &mut self,
+        uuid: Uuid,
+        priority: Priority,
+    ) -> Result<Outcome, TerminalError> {
+        let todos_modified = self.todo_repository.set_priority(uuid, priority).await?;
+        match todos_modified {
+            0 => self.user_interface.report_not_found()?,
+            _ => {
+                self.user_interface
+                    .priority_message(uuid, priority, todos_modified)?;
+                self.show_list().await?;
+            }
+        }
+        Ok(Outcome::from_modified(todos_modified))
+    }
+
+    async fn set_due(&mut self, uuid: Uuid, due: Option<Due>) -> Result<Outcome, TerminalError> {
+        let todos_modified = self.todo_repository.set_due(uuid, due).await?;
+        match todos_modified {
+            0 => self.user_interface.report_not_found()?,
+            _ => {
+                self.user_interface.due_message(uuid, due, todos_modified)?;
+                self.show_list().await?;
+            }
+        }
+        Ok(Outcome::from_modified(todos_modified))
+    }
+
+    async fn set_recurrence(
+        &mut self,
+        uuid: Uuid,
+        recurrence: Option<Recurrence>,
+    ) -> Result<Outcome, TerminalError> {
+        let todos_modified = self
+            .todo_repository
+            .set_recurrence(uuid, recurrence)
+            .await?;
+        match todos_modified {
+            0 => self.user_interface.report_not_found()?,
+            _ => {
+                self.user_interface
+                    .recurrence_message(uuid, recurrence, todos_modified)?;
+                self.show_list().await?;
+            }
+        }
+        Ok(Outcome::from_modified(todos_modified))
+    }
+
+    async fn add_blocker(&mut self, uuid: Uuid, blocker: Uuid) -> Result<Outcome, TerminalError> {
+        for id in [uuid, blocker] {
+            if self.todo_repository.get_todo(id).await?.is_none() {
+                self.user_interface.report_not_found()?;
+                return Ok(Outcome::NotFound);
+            }
+        }
+        let added = self.todo_repository.add_blocker(uuid, blocker).await?;
+        if !added {
+            return Err(TerminalError::DependencyCycle);
+        }
+        self.user_interface
+            .block_message(uuid, blocker, u64::from(added))?;
+        self.show_list().await?;
+        Ok(Outcome::Completed)
+    }
+
+    async fn remove_blocker(
+        &mut self,
+        uuid: Uuid,
+        blocker: Uuid,
+    ) -> Result<Outcome, TerminalError> {
+        let todos_modified = self.todo_repository.remove_blocker(uuid, blocker).await?;
+        match todos_modified {
+            0 => self.user_interface.report_not_found()?,
+            _ => {
+                self.user_interface
+                    .unblock_message(uuid, blocker, todos_modified)?;
+                self.show_list().await?;
+            }
+        }
+        Ok(Outcome::from_modified(todos_modified))
+    }
+
+    async fn update_tags(
+        &mut self,
+        uuid: Uuid,
+        add: Vec<Tag>,
+        remove: Vec<Tag>,
+    ) -> Result<Outcome, TerminalError> {
+        let todos_modified = self
+            .todo_repository
+            .update_tags(uuid, add.clone(), remove.clone())
+            .await?;
+        match todos_modified {
+            0 => self.user_interface.report_not_found()?,
+            _ => {
+                self.user_interface
+                    .tags_message(uuid, &add, &remove, todos_modified)?;
+                self.show_list().await?;
+            }
+        }
+        Ok(Outcome::from_modified(todos_modified))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::todo::{mocks::*, Todo};
+    use factori::create;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::repository::todo::memory::InMemoryStorage;
+    use crate::{repository::todo::MockStorage, terminal::MockUserInterface};
+
+    #[tokio::test]
+    async fn should_add_todo() {
+        let mut mock_storage = MockStorage::new();
+        let mut mock_user_interface = MockUserInterface::new();
+        let id = Uuid::new_v4();
+        let todo_added = Todo::new("todo 3".to_string(), id);
+        let original_todo_list = create!(Todos, number_todos: 3);
+        let mut updated_todo_list = original_todo_list.clone();
+        updated_todo_list.push(todo_added.clone());
+
+        mock_storage
+            .expect_add_todo()
+            .times(1)
+            .returning(|_| Ok(()));
+
+        mock_storage.expect_get_todo_list().times(1).returning({
+            let updated_list = updated_todo_list.clone();
+            move || Ok(updated_list.clone())
+        });
+
+        mock_user_interface
+            .expect_add_todo_message()
+            .withf(move |todo| todo.id == id)
+            .times(1)
+            .returning(|_| Ok(()));
+
+        mock_user_interface
+            .expect_show_todo_list()
+            .withf(move |returned_list| *returned_list == updated_todo_list)
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let mut todo_cli_mock =
+            TodoControllerImpl::new(Box::new(mock_storage), Box::new(mock_user_interface));
+
+        todo_cli_mock
+            .add_todo(todo_added)
+            .await
+            .expect("Should add a Todo successfully");
+    }
+
+    #[tokio::test]
+    async fn should_show_todo_list() {
+        let mut mock_storage = MockStorage::new();
+        let mut mock_user_interface = MockUserInterface::new();
+        let todo_list = create!(Todos, number_todos: 3);
+
+        mock_user_interface
+            .expect_show_todo_list()
+            .withf({
+                let list = todo_list.clone();
+                move |returned_list| *returned_list == list
+            })
+            .times(1)
+            .returning(|_| Ok(()));
+
+        mock_storage
+            .expect_get_todo_list()
+            .times(1)
+            .returning(move || Ok(todo_list.clone()));
+
+        let mut todo_cli_mock =
+            TodoControllerImpl::new(Box::new(mock_storage), Box::new(mock_user_interface));
+
+        todo_cli_mock
+            .show_list()
+            .await
+            .expect("Should list all todos")
+    }
+
+    #[tokio::test]
+    async fn should_clear_list() {
+        let mut mock_storage = MockStorage::new();
+        let mut mock_user_interface = MockUserInterface::new();
+        let todo_list = create!(Todos, number_todos: 3);
+
+        mock_storage
+            .expect_get_todo_list()
+            .times(1)
+            .returning(move || Ok(todo_list.clone()));
+
+        mock_storage
+            .expect_clear_todo_list()
+            .times(1)
+            .returning(|| Ok(()));
+
+        mock_user_interface
+            .expect_clear_todo_message()
+            .times(1)
+            .returning(|| Ok(()));
+
+        let mut todo_cli_mock =
+            TodoControllerImpl::new(Box::new(mock_storage), Box::new(mock_user_interface));
+
+        todo_cli_mock
+            .clear_todo_list()
+            .await
+            .expect("Should clear the list")
+    }
+
+    #[tokio::test]
+    async fn should_remove_todo() {
+        let mut mock_storage = MockStorage::new();
+        let mut mock_user_interface = MockUserInterface::new();
+        let mut original_todo_list = create!(Todos, number_todos: 4);
+        let todo = original_todo_list.get_mut(3).unwrap();
+        let todo_id = todo.id;
+
+        mock_storage
+            .expect_get_todo_list()
+            .return_once(|| Ok(original_todo_list));
+
+        mock_storage
+            .expect_remove_todo()
+            .times(1)
+            .returning(|_| Ok(1));
+
+        mock_user_interface
+            .expect_remove_todo_message()
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let mut todo_cli_mock =
+            TodoControllerImpl::new(Box::new(mock_storage), Box::new(mock_user_interface));
+
+        let outcome = todo_cli_mock
+            .remove_todo(todo_id)
+            .await
+            .expect("Should remove the fourth todo");
+
+        assert_eq!(outcome, Outcome::Completed);
+    }
+
+    #[tokio::test]
+    async fn should_mark_todo_as_done() {
+        let mut mock_storage = MockStorage::new();
+        let mut mock_user_interface = MockUserInterface::new();
+        let mut todo_list = create!(Todos, number_todos: 4);
+        let todo = todo_list.get_mut(3).unwrap();
+        let todo_id = todo.id;
+        todo.done = true;
+
+        mock_storage
+            .expect_mark_todo_done()
+            .times(1)
+            .returning(|_| Ok(1));
+
+        mock_user_interface
+            .expect_mark_done_message()
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        mock_user_interface
+            .expect_show_todo_list()
+            .times(1)
+            .returning(|_| Ok(()));
+
+        mock_storage
+            .expect_get_todo_list()
+            .times(2)
+            .returning(move || Ok(todo_list.clone()));
+
+        let mut todo_cli_mock =
+            TodoControllerImpl::new(Box::new(mock_storage), Box::new(mock_user_interface));
+
+        let outcome = todo_cli_mock
+            .mark_todo_done(todo_id)
+            .await
+            .expect("Should mark the last todo as done");
+
+        assert_eq!(outcome, Outcome::Completed);
+    }
+
+    #[tokio::test]
+    async fn should_add_and_remove_todo_in_storage() {
+        let mut mock_user_interface = MockUserInterface::new();
+        let todo = Todo::new("todo 1".to_string(), Uuid::new_v4());
+
+        mock_user_interface
+            .expect_add_todo_message()
+            .times(1)
+            .returning(|_| Ok(()));
+
+        mock_user_interface
+            .expect_show_todo_list()
+            .times(1)
+            .returning(|_| Ok(()));
+
+        mock_user_interface
+            .expect_remove_todo_message()
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let mut todo_cli = TodoControllerImpl::new(
+            Box::new(InMemoryStorage::new()),
+            Box::new(mock_user_interface),
+        );
+
+        todo_cli
+            .add_todo(todo.clone())
+            .await
+            .expect("Should add a Todo successfully");
+        todo_cli
+            .remove_todo(todo.id)
+            .await
+            .expect("Should remove the Todo");
+
+        let todo_list = todo_cli.todo_repository.get_todo_list().await.unwrap();
+        assert!(todo_list.is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_report_not_found_when_marking_missing_todo() {
+        let mut mock_user_interface = MockUserInterface::new();
+
+        mock_user_interface
+            .expect_report_not_found()
+            .times(1)
+            .returning(|| Ok(()));
+
+        let mut todo_cli = TodoControllerImpl::new(
+            Box::new(InMemoryStorage::new()),
+            Box::new(mock_user_interface),
+        );
+
+        let outcome = todo_cli
+            .mark_todo_done(Uuid::new_v4())
+            .await
+            .expect("Should report the missing todo");
+
+        assert_eq!(outcome, Outcome::NotFound);
+    }
+
+    #[tokio::test]
+    async fn should_edit_todo() {
+        let mut mock_user_interface = MockUserInterface::new();
+        let mut storage = InMemoryStorage::new();
+        let todo = Todo::new("todo with typo".to_string(), Uuid::new_v4());
+        storage.add_todo(todo.clone()).await.unwrap();
+
+        mock_user_interface
+            .expect_prompt_edit_todo()
+            .withf(|todo| todo.message == "todo with typo")
+            .times(1)
+            .returning(|_| Ok("todo without typo".to_string()));
+
+        mock_user_interface
+            .expect_edit_todo_message()
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        mock_user_interface
+            .expect_show_todo_list()
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let mut todo_cli =
+            TodoControllerImpl::new(Box::new(storage), Box::new(mock_user_interface));
+
+        todo_cli
+            .edit_todo(todo.id)
+            .await
+            .expect("Should edit the todo");
+
+        let edited = todo_cli.todo_repository.get_todo(todo.id).await.unwrap();
+        assert_eq!(edited.unwrap().message, "todo without typo");
+    }
+
+    #[tokio::test]
+    async fn should_report_not_found_when_editing_missing_todo() {
+        let mut mock_storage = MockStorage::new();
+        let mut mock_user_interface = MockUserInterface::new();
+
+        mock_storage
+            .expect_get_todo()
+            .times(1)
+            .returning(|_| Ok(None));
+
+        mock_user_interface.expect_prompt_edit_todo().never();
+
+        mock_user_interface
+            .expect_report_not_found()
+            .times(1)
+            .returning(|| Ok(()));
+
+        let mut todo_cli_mock =
+            TodoControllerImpl::new(Box::new(mock_storage), Box::new(mock_user_interface));
+
+        let outcome = todo_cli_mock
+            .edit_todo(Uuid::new_v4())
+            .await
+            .expect("Should report the missing todo");
+
+        assert_eq!(outcome, Outcome::NotFound);
+    }
+
+    #[tokio::test]
+    async fn should_mark_todo_as_undone() {
+        let mut mock_storage = MockStorage::new();
+        let mut mock_user_interface = MockUserInterface::new();
+        let todo_list = create!(Todos, number_todos: 2);
+        let todo_id = todo_list.get(1).unwrap().id;
+
+        mock_storage
+            .expect_mark_todo_undone()
+            .withf(move |uuid| *uuid == todo_id)
+            .times(1)
+            .returning(|_| Ok(1));
+
+        mock_storage
+            .expect_get_todo_list()
+            .times(1)
+            .returning(move || Ok(todo_list.clone()));
+
+        mock_user_interface
+            .expect_mark_undone_message()
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        mock_user_interface
+            .expect_show_todo_list()
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let mut todo_cli_mock =
+            TodoControllerImpl::new(Box::new(mock_storage), Box::new(mock_user_interface));
+
+        let outcome = todo_cli_mock
+            .mark_todo_undone(todo_id)
+            .await
+            .expect("Should mark the todo as not done");
+
+        assert_eq!(outcome, Outcome::Completed);
+    }
+
+    #[tokio::test]
+    async fn should_resolve_positions_from_the_last_shown_list() {
+        let mut mock_user_interface = MockUserInterface::new();
+        let mut storage = InMemoryStorage::new();
+        let todo_list = create!(Todos, number_todos: 3);
+        for todo in todo_list.iter() {
+            storage.add_todo(todo.clone()).await.unwrap();
+        }
+
+        mock_user_interface
+            .expect_show_todo_list()
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let mut todo_cli =
+            TodoControllerImpl::new(Box::new(storage), Box::new(mock_user_interface));
+        todo_cli.show_list().await.unwrap();
+
+        let resolved = todo_cli.resolve(TodoRef::Position(2)).await.unwrap();
+        let prefix = todo_list.get(2).unwrap().id.to_string()[..8].to_string();
+        let resolved_prefix = todo_cli.resolve(TodoRef::Prefix(prefix)).await.unwrap();
+
+        assert_eq!(resolved, Some(todo_list.get(1).unwrap().id));
+        assert_eq!(resolved_prefix, Some(todo_list.get(2).unwrap().id));
+    }
+
+    #[tokio::test]
+    async fn should_report_positions_outside_the_list() {
+        let mut mock_user_interface = MockUserInterface::new();
+
+        mock_user_interface
+            .expect_report_not_found()
+            .times(1)
+            .returning(|| Ok(()));
+
+        let mut todo_cli = TodoControllerImpl::new(
+            Box::new(InMemoryStorage::new()),
+            Box::new(mock_user_interface),
+        );
+
+        let resolved = todo_cli.resolve(TodoRef::Position(1)).await.unwrap();
+
+        assert_eq!(resolved, None);
+    }
+
+    #[tokio::test]
+    async fn should_reject_ambiguous_prefixes() {
+        let mut storage = InMemoryStorage::new();
+        for id in [
+            "c13d9b75-380c-4a5c-b1a6-ad4f02f83d7e",
+            "c13e0000-380c-4a5c-b1a6-ad4f02f83d7e",
+        ] {
+            let todo = Todo::new("todo".to_string(), Uuid::parse_str(id).unwrap());
+            storage.add_todo(todo).await.unwrap();
+        }
+
+        let mut todo_cli =
+            TodoControllerImpl::new(Box::new(storage), Box::new(MockUserInterface::new()));
+
+        let error = todo_cli
+            .resolve(TodoRef::Prefix("c13".to_string()))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, TerminalError::AmbiguousTodoRef(_, 2)));
+    }
+
+    #[tokio::test]
+    async fn should_execute_one_shot_options() {
+        let mut mock_user_interface = MockUserInterface::new();
+        let todo = Todo::new("buy milk".to_string(), Uuid::new_v4());
+
+        mock_user_interface
+            .expect_add_todo_message()
+            .times(1)
+            .returning(|_| Ok(()));
+
+        mock_user_interface
+            .expect_show_todo_list()
+            .times(2)
+            .returning(|_| Ok(()));
+
+        mock_user_interface
+            .expect_mark_done_message()
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        mock_user_interface
+            .expect_report_not_found()
+            .times(1)
+            .returning(|| Ok(()));
+
+        let mut todo_cli = TodoControllerImpl::new(
+            Box::new(InMemoryStorage::new()),
+            Box::new(mock_user_interface),
+        );
+
+        let added = todo_cli.execute(UserOptions::NewTodo(todo)).await.unwrap();
+        let done = todo_cli
+            .execute(UserOptions::DoTodo(TodoRef::Position(1)))
+            .await
+            .unwrap();
+        let missing = todo_cli
+            .execute(UserOptions::RemoveTodo(TodoRef::Position(2)))
+            .await
+            .unwrap();
+        let quit = todo_cli.execute(UserOptions::Quit).await.unwrap();
+
+        assert_eq!(added, Outcome::Completed);
+        assert_eq!(done, Outcome::Completed);
+        assert_eq!(missing, Outcome::NotFound);
+        assert_eq!(quit, Outcome::Quit);
+    }
+
+    #[tokio::test]
+    async fn should_stop_script_at_first_failing_command() {
+        let mut mock_user_interface = MockUserInterface::new();
+        let mut options = vec![
+            UserOptions::NewTodo(Todo::new("buy milk".to_string(), Uuid::new_v4())),
+            UserOptions::DoTodo(TodoRef::Position(3)),
+            UserOptions::ShowList,
+        ]
+        .into_iter();
+
+        mock_user_interface
+            .expect_user_intention()
+            .times(2)
+            .returning(move || Ok(options.next().unwrap()));
+
+        mock_user_interface
+            .expect_add_todo_message()
+            .times(1)
+            .returning(|_| Ok(()));
+
+        mock_user_interface
+            .expect_show_todo_list()
+            .times(1)
+            .returning(|_| Ok(()));
+
+        mock_user_interface
+            .expect_report_not_found()
+            .times(1)
+            .returning(|| Ok(()));
+
+        let mut todo_cli = TodoControllerImpl::new(
+            Box::new(InMemoryStorage::new()),
+            Box::new(mock_user_interface),
+        );
+
+        let outcome = todo_cli.run_script().await.unwrap();
+
+        assert_eq!(outcome, Outcome::NotFound);
+    }
+
+    #[tokio::test]
+    async fn should_show_only_overdue_todos() {
+        let mut mock_user_interface = MockUserInterface::new();
+        let yesterday = Local::now().date_naive().pred_opt().unwrap();
+        let mut overdue = Todo::new("overdue".to_string(), Uuid::new_v4());
+        overdue.due = Some(Due {
+            date: yesterday,
+            time: None,
+        });
+        let mut done = overdue.clone();
+        done.id = Uuid::new_v4();
+        done.done = true;
+        let undated = Todo::new("undated".to_string(), Uuid::new_v4());
+
+        let mut storage = InMemoryStorage::new();
+        for todo in [overdue.clone(), done, undated] {
+            storage.add_todo(todo).await.unwrap();
+        }
+
+        mock_user_interface
+            .expect_show_todo_list()
+            .withf(move |todo_list| *todo_list == Todos::new(vec![overdue.clone()]))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let mut todo_cli =
+            TodoControllerImpl::new(Box::new(storage), Box::new(mock_user_interface));
+
+        todo_cli.show_due(DueFilter::Overdue).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn should_only_clear_the_current_list() {
+        let mut mock_user_interface = MockUserInterface::new();
+        let work: ListName = "work".parse().unwrap();
+        let inbox_todo = Todo::new("call mom".to_string(), Uuid::new_v4());
+        let work_todo = Todo::new("fix login".to_string(), Uuid::new_v4());
+
+        let mut storage = InMemoryStorage::new();
+        storage.add_todo(inbox_todo.clone()).await.unwrap();
+        storage.create_list(work.clone()).await.unwrap();
+        storage.switch_list(work.clone()).await.unwrap();
+        storage.add_todo(work_todo.clone()).await.unwrap();
+        storage.switch_list(ListName::default()).await.unwrap();
+
+        mock_user_interface
+            .expect_clear_todo_message()
+            .times(1)
+            .returning(|| Ok(()));
+        mock_user_interface
+            .expect_switch_list_message()
+            .withf(|name| name.as_str() == "work")
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_user_interface
+            .expect_show_todo_list()
+            .withf(move |todo_list| *todo_list == Todos::new(vec![work_todo.clone()]))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let mut todo_cli =
+            TodoControllerImpl::new(Box::new(storage), Box::new(mock_user_interface));
+
+        todo_cli.execute(UserOptions::ClearList).await.unwrap();
+        let outcome = todo_cli
+            .execute(UserOptions::SwitchList(work))
+            .await
+            .unwrap();
+        assert_eq!(outcome, Outcome::Completed);
+    }
+
+    #[tokio::test]
+    async fn should_refuse_to_delete_the_default_list() {
+        let mut todo_cli = TodoControllerImpl::new(
+            Box::new(InMemoryStorage::new()),
+            Box::new(MockUserInterface::new()),
+        );
+
+        let result = todo_cli
+            .execute(UserOptions::DeleteList(ListName::default()))
+            .await;
+
+        assert!(matches!(result, Err(TerminalError::ProtectedList(_))));
+    }
+
+    #[tokio::test]
+    async fn should_resolve_positions_against_the_tagged_list() {
+        let mut mock_user_interface = MockUserInterface::new();
+        let untagged = Todo::new("call mom".to_string(), Uuid::new_v4());
+        let tagged = Todo::new("fix login #backend".to_string(), Uuid::new_v4());
+        let tagged_id = tagged.id;
+
+        let mut storage = InMemoryStorage::new();
+        for todo in [untagged, tagged] {
+            storage.add_todo(todo).await.unwrap();
+        }
+
+        mock_user_interface
+            .expect_show_todo_list()
+            .times(2)
+            .returning(|_| Ok(()));
+        mock_user_interface
+            .expect_mark_done_message()
+            .withf(move |uuid, _| *uuid == tagged_id)
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let mut todo_cli =
+            TodoControllerImpl::new(Box::new(storage), Box::new(mock_user_interface));
+
+        todo_cli
+            .show_tagged("backend".parse().unwrap())
+            .await
+            .unwrap();
+        let outcome = todo_cli
+            .execute(UserOptions::DoTodo(TodoRef::Position(1)))
+            .await
+            .unwrap();
+        assert!(matches!(outcome, Outcome::Completed));
+    }
+
+    #[tokio::test]
+    async fn should_block_completing_a_todo_with_open_subtasks() {
+        let parent = Todo::new("plan trip".to_string(), Uuid::new_v4());
+        let subtask = Todo::new("book hotel".to_string(), Uuid::new_v4()).with_parent(parent.id);
+        let mut storage = InMemoryStorage::new();
+        storage.add_todo(parent.clone()).await.unwrap();
+        storage.add_todo(subtask).await.unwrap();
 
-    async fn add_todo(&mut self, todo: Todo) -> Result<(), TerminalError> {
-        self.todo_repository.add_todo(todo).await?;
-        self.show_list().await?;
-        Ok(())
+        let mut todo_cli =
+            TodoControllerImpl::new(Box::new(storage), Box::new(MockUserInterface::new()));
+
+        let result = todo_cli
+            .execute(UserOptions::DoTodo(TodoRef::Id(parent.id)))
+            .await;
+        assert!(matches!(result, Err(TerminalError::OpenSubtasks(1))));
     }
 
-    async fn clear_todo_list(&mut self) -> Result<(), TerminalError> {
-        self.todo_repository.clear_todo_list().await?;
-        self.user_interface.clear_todo_message()?;
-        Ok(())
+    #[tokio::test]
+    async fn should_cascade_completion_to_subtasks() {
+        let mut mock_user_interface = MockUserInterface::new();
+        let parent = Todo::new("plan trip".to_string(), Uuid::new_v4());
+        let subtask = Todo::new("book hotel".to_string(), Uuid::new_v4()).with_parent(parent.id);
+        let mut storage = InMemoryStorage::new();
+        storage.add_todo(parent.clone()).await.unwrap();
+        storage.add_todo(subtask).await.unwrap();
+        let parent_id = parent.id;
+
+        // The subtask counts as modified too.
+        mock_user_interface
+            .expect_mark_done_message()
+            .withf(move |uuid, todos_modified| *uuid == parent_id && *todos_modified == 2)
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_user_interface
+            .expect_show_todo_list()
+            .withf(|todo_list| todo_list.iter().all(|todo| todo.done))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let mut todo_cli =
+            TodoControllerImpl::new(Box::new(storage), Box::new(mock_user_interface))
+                .with_subtask_policy(SubtaskPolicy {
+                    complete: CompletePolicy::Cascade,
+                    ..SubtaskPolicy::default()
+                });
+
+        let outcome = todo_cli
+            .execute(UserOptions::DoTodo(TodoRef::Id(parent.id)))
+            .await
+            .unwrap();
+        assert_eq!(outcome, Outcome::Completed);
     }
 
-    async fn remove_todo(&mut self, uuid: Uuid) -> Result<(), TerminalError> {
-        let todos_modified = self.todo_repository.remove_todo(uuid).await?;
-        match todos_modified {
-            0 => self.user_interface.report_not_found()?,
-            _ => self.user_interface.remove_todo_message()?,
-        }
-        Ok(())
+    #[tokio::test]
+    async fn should_bring_back_a_recurring_todo_once_done() {
+        let mut mock_user_interface = MockUserInterface::new();
+        let mut standup = Todo::new("standup notes".to_string(), Uuid::new_v4());
+        standup.recurrence = Some(Recurrence::Weekdays);
+        // A Friday far enough ahead to still be upcoming.
+        standup.due = Some(Due {
+            date: chrono::NaiveDate::from_ymd_opt(2099, 10, 16).unwrap(),
+            time: None,
+        });
+        let mut storage = InMemoryStorage::new();
+        storage.add_todo(standup.clone()).await.unwrap();
+
+        mock_user_interface
+            .expect_mark_done_message()
+            .times(2)
+            .returning(|_, _| Ok(()));
+        mock_user_interface
+            .expect_next_occurrence_message()
+            .withf(|next| {
+                next.due.map(|due| due.date) == chrono::NaiveDate::from_ymd_opt(2099, 10, 19)
+                    && next.recurrence == Some(Recurrence::Weekdays)
+                    && !next.done
+            })
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_user_interface
+            .expect_show_todo_list()
+            .withf(|todo_list| todo_list.iter().count() == 2)
+            .times(2)
+            .returning(|_| Ok(()));
+        let mut todo_cli =
+            TodoControllerImpl::new(Box::new(storage), Box::new(mock_user_interface));
+
+        let outcome = todo_cli
+            .execute(UserOptions::DoTodo(TodoRef::Id(standup.id)))
+            .await
+            .unwrap();
+        assert_eq!(outcome, Outcome::Completed);
+        // Already done, so nothing new comes back.
+        todo_cli
+            .execute(UserOptions::DoTodo(TodoRef::Id(standup.id)))
+            .await
+            .unwrap();
     }
 
-    async fn mark_todo_done(&mut self, uuid: Uuid) -> Result<(), TerminalError> {
-        let todos_modified = self.todo_repository.mark_todo_done(uuid).await?;
-        match todos_modified {
-            0 => self.user_interface.report_not_found()?,
-            _ => {
-                self.user_interface.mark_done_message()?;
-                self.show_list().await?;
-            }
+    #[tokio::test]
+    async fn should_not_bring_back_a_reopened_recurring_todo_twice() {
+        let mut mock_user_interface = MockUserInterface::new();
+        let mut standup = Todo::new("standup notes".to_string(), Uuid::new_v4());
+        standup.recurrence = Some(Recurrence::Daily);
+        let mut storage = InMemoryStorage::new();
+        storage.add_todo(standup.clone()).await.unwrap();
+
+        mock_user_interface
+            .expect_mark_done_message()
+            .times(2)
+            .returning(|_, _| Ok(()));
+        mock_user_interface
+            .expect_mark_undone_message()
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_user_interface
+            .expect_next_occurrence_message()
+            .withf(move |next| next.origin == Some(standup.id))
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_user_interface
+            .expect_show_todo_list()
+            .withf(|todo_list| todo_list.iter().count() == 2)
+            .times(3)
+            .returning(|_| Ok(()));
+        let mut todo_cli =
+            TodoControllerImpl::new(Box::new(storage), Box::new(mock_user_interface));
+
+        for option in [
+            UserOptions::DoTodo(TodoRef::Id(standup.id)),
+            UserOptions::UndoneTodo(TodoRef::Id(standup.id)),
+            UserOptions::DoTodo(TodoRef::Id(standup.id)),
+        ] {
+            let outcome = todo_cli.execute(option).await.unwrap();
+            assert_eq!(outcome, Outcome::Completed);
         }
-        Ok(())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::domain::todo::{mocks::*, Todo};
-    use factori::create;
-    use uuid::Uuid;
+    #[tokio::test]
+    async fn should_not_bring_back_a_recurring_todo_whose_next_occurrence_was_trashed() {
+        let mut mock_user_interface = MockUserInterface::new();
+        let mut standup = Todo::new("standup notes".to_string(), Uuid::new_v4());
+        standup.recurrence = Some(Recurrence::Daily);
+        let next = Todo {
+            origin: Some(standup.id),
+            ..Todo::new("standup notes".to_string(), Uuid::new_v4())
+        };
+        let mut storage = InMemoryStorage::new();
+        storage.add_todo(standup.clone()).await.unwrap();
+        storage.add_todo(next.clone()).await.unwrap();
+        storage.remove_todo(next.id).await.unwrap();
 
-    use super::*;
-    use crate::{repository::todo::MockStorage, terminal::MockUserInterface};
+        mock_user_interface
+            .expect_mark_done_message()
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_user_interface
+            .expect_next_occurrence_message()
+            .times(0);
+        mock_user_interface
+            .expect_show_todo_list()
+            .withf(|todo_list| todo_list.iter().count() == 1)
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut todo_cli =
+            TodoControllerImpl::new(Box::new(storage), Box::new(mock_user_interface));
+
+        let outcome = todo_cli
+            .execute(UserOptions::DoTodo(TodoRef::Id(standup.id)))
+            .await
+            .unwrap();
+        assert_eq!(outcome, Outcome::Completed);
+    }
 
     #[tokio::test]
-    async fn should_add_todo() {
-        let mut mock_storage = MockStorage::new();
+    async fn should_warn_when_completing_a_todo_with_open_blockers() {
         let mut mock_user_interface = MockUserInterface::new();
-        let id = Uuid::new_v4();
-        let todo_added = Todo::new("todo 3".to_string(), id);
-        let original_todo_list = create!(Todos, number_todos: 3);
-        let mut updated_todo_list = original_todo_list.clone();
-        updated_todo_list.push(todo_added.clone());
+        let tests = Todo::new("run tests".to_string(), Uuid::new_v4());
+        let mut publish = Todo::new("publish".to_string(), Uuid::new_v4());
+        publish.blocked_by = vec![tests.id];
+        let mut storage = InMemoryStorage::new();
+        storage.add_todo(tests.clone()).await.unwrap();
+        storage.add_todo(publish.clone()).await.unwrap();
 
-        mock_storage
-            .expect_add_todo()
+        mock_user_interface
+            .expect_mark_done_message()
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_user_interface
+            .expect_open_blockers_warning()
+            .withf(move |uuid, blockers| *uuid == publish.id && blockers == [tests.id])
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_user_interface
+            .expect_show_todo_list()
             .times(1)
             .returning(|_| Ok(()));
 
-        mock_storage.expect_get_todo_list().times(1).returning({
-            let updated_list = updated_todo_list.clone();
-            move || Ok(updated_list.clone())
-        });
+        let mut todo_cli =
+            TodoControllerImpl::new(Box::new(storage), Box::new(mock_user_interface));
 
+        let outcome = todo_cli
+            .execute(UserOptions::DoTodo(TodoRef::Id(publish.id)))
+            .await
+            .unwrap();
+        assert_eq!(outcome, Outcome::Completed);
+    }
+
+    #[tokio::test]
+    async fn should_refuse_dependency_cycles() {
+        let first = Todo::new("first".to_string(), Uuid::new_v4());
+        let mut second = Todo::new("second".to_string(), Uuid::new_v4());
+        second.blocked_by = vec![first.id];
+        let mut storage = InMemoryStorage::new();
+        storage.add_todo(first.clone()).await.unwrap();
+        storage.add_todo(second.clone()).await.unwrap();
+
+        let mut todo_cli =
+            TodoControllerImpl::new(Box::new(storage), Box::new(MockUserInterface::new()));
+
+        let result = todo_cli
+            .execute(UserOptions::Block(
+                TodoRef::Id(first.id),
+                TodoRef::Id(second.id),
+            ))
+            .await;
+        assert!(matches!(result, Err(TerminalError::DependencyCycle)));
+    }
+
+    #[tokio::test]
+    async fn should_move_a_todo_to_the_position_it_was_given() {
+        let todos: Vec<Todo> = ["first", "second", "third"]
+            .into_iter()
+            .map(|message| Todo::new(message.to_string(), Uuid::new_v4()))
+            .collect();
+        let expected = vec![todos[2].id, todos[0].id, todos[1].id];
+        let mut storage = InMemoryStorage::new();
+        for todo in todos {
+            storage.add_todo(todo).await.unwrap();
+        }
+
+        let mut mock_user_interface = MockUserInterface::new();
+        mock_user_interface
+            .expect_reorder_todo_message()
+            .times(1)
+            .returning(|_, _| Ok(()));
         mock_user_interface
             .expect_show_todo_list()
-            .withf(move |returned_list| *returned_list == updated_todo_list)
+            .withf(move |todos| todos.iter().map(|todo| todo.id).eq(expected.clone()))
             .times(1)
             .returning(|_| Ok(()));
 
-        let mut todo_cli_mock = TodoControllerImpl {
-            user_interface: Box::new(mock_user_interface),
-            todo_repository: Box::new(mock_storage),
-        };
+        let mut todo_cli =
+            TodoControllerImpl::new(Box::new(storage), Box::new(mock_user_interface));
 
-        todo_cli_mock
-            .add_todo(todo_added)
+        let outcome = todo_cli
+            .execute(UserOptions::MoveToPosition(TodoRef::Position(3), 1))
             .await
-            .expect("Should add a Todo successfully");
+            .unwrap();
+        assert_eq!(outcome, Outcome::Completed);
     }
 
     #[tokio::test]
-    async fn should_show_todo_list() {
-        let mut mock_storage = MockStorage::new();
-        let mut mock_user_interface = MockUserInterface::new();
-        let todo_list = create!(Todos, number_todos: 3);
+    async fn should_refuse_to_move_a_todo_past_other_priorities() {
+        let urgent =
+            Todo::new("urgent".to_string(), Uuid::new_v4()).with_priority(Priority::Urgent);
+        let first = Todo::new("first".to_string(), Uuid::new_v4());
+        let second = Todo::new("second".to_string(), Uuid::new_v4());
+        let expected = vec![urgent.id, second.id, first.id];
+        let mut storage = InMemoryStorage::new();
+        for todo in [&first, &second, &urgent] {
+            storage.add_todo(todo.clone()).await.unwrap();
+        }
 
+        let mut mock_user_interface = MockUserInterface::new();
+        mock_user_interface
+            .expect_reorder_todo_message()
+            .times(1)
+            .returning(|_, _| Ok(()));
         mock_user_interface
             .expect_show_todo_list()
-            .withf({
-                let list = todo_list.clone();
-                move |returned_list| *returned_list == list
-            })
+            .withf(move |todos| todos.iter().map(|todo| todo.id).eq(expected.clone()))
             .times(1)
             .returning(|_| Ok(()));
 
-        mock_storage
-            .expect_get_todo_list()
-            .times(1)
-            .returning(move || Ok(todo_list.clone()));
+        let mut todo_cli =
+            TodoControllerImpl::new(Box::new(storage), Box::new(mock_user_interface));
 
-        let mut todo_cli_mock = TodoControllerImpl {
-            user_interface: Box::new(mock_user_interface),
-            todo_repository: Box::new(mock_storage),
-        };
+        // The urgent todo is shown first whatever its place in the list.
+        let result = todo_cli
+            .execute(UserOptions::MoveToPosition(TodoRef::Id(second.id), 1))
+            .await;
+        assert!(matches!(result, Err(TerminalError::UnreachablePosition(1))));
+        let result = todo_cli
+            .execute(UserOptions::MoveToTop(TodoRef::Id(second.id)))
+            .await;
+        assert!(matches!(result, Err(TerminalError::UnreachablePosition(1))));
+        let result = todo_cli
+            .execute(UserOptions::MoveToBottom(TodoRef::Id(urgent.id)))
+            .await;
+        assert!(matches!(result, Err(TerminalError::UnreachablePosition(3))));
 
-        todo_cli_mock
-            .show_list()
+        let outcome = todo_cli
+            .execute(UserOptions::MoveToPosition(TodoRef::Id(second.id), 2))
             .await
-            .expect("Should list all todos")
+            .unwrap();
+        assert_eq!(outcome, Outcome::Completed);
     }
 
     #[tokio::test]
-    async fn should_clear_list() {
-        let mut mock_storage = MockStorage::new();
-        let mut mock_user_interface = MockUserInterface::new();
+    async fn should_restore_todos_by_their_position_in_the_trash() {
+        let kept = Todo::new("kept".to_string(), Uuid::new_v4());
+        let removed = Todo::new("removed".to_string(), Uuid::new_v4());
+        let removed_id = removed.id;
+        let mut storage = InMemoryStorage::new();
+        storage.add_todo(kept).await.unwrap();
+        storage.add_todo(removed).await.unwrap();
 
-        mock_storage
-            .expect_clear_todo_list()
+        let mut mock_user_interface = MockUserInterface::new();
+        mock_user_interface
+            .expect_remove_todo_message()
+            .withf(move |uuid, _| *uuid == removed_id)
             .times(1)
-            .returning(|| Ok(()));
-
+            .returning(|_, _| Ok(()));
         mock_user_interface
-            .expect_clear_todo_message()
+            .expect_restore_todo_message()
+            .withf(move |uuid, _| *uuid == removed_id)
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_user_interface
+            .expect_show_todo_list()
+            .withf(|todos| todos.iter().count() == 2)
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_user_interface
+            .expect_report_not_found()
             .times(1)
             .returning(|| Ok(()));
 
-        let mut todo_cli_mock = TodoControllerImpl {
-            user_interface: Box::new(mock_user_interface),
-            todo_repository: Box::new(mock_storage),
-        };
+        let mut todo_cli =
+            TodoControllerImpl::new(Box::new(storage), Box::new(mock_user_interface));
 
-        todo_cli_mock
-            .clear_todo_list()
+        todo_cli
+            .execute(UserOptions::RemoveTodo(TodoRef::Position(2)))
             .await
-            .expect("Should clear the list")
+            .unwrap();
+        // The trash holds a single todo, whatever the list showed at that position.
+        let outcome = todo_cli
+            .execute(UserOptions::RestoreTodo(TodoRef::Position(1)))
+            .await
+            .unwrap();
+        assert_eq!(outcome, Outcome::Completed);
+        let outcome = todo_cli
+            .execute(UserOptions::RestoreTodo(TodoRef::Position(1)))
+            .await;
+        assert!(matches!(outcome, Ok(Outcome::NotFound)));
     }
 
     #[tokio::test]
-    async fn should_remove_todo() {
-        let mut mock_storage = MockStorage::new();
+    async fn should_undo_and_redo_a_removal() {
+        let kept = Todo::new("kept".to_string(), Uuid::new_v4());
+        let removed = Todo::new("removed".to_string(), Uuid::new_v4());
+        let removed_id = removed.id;
+        let mut storage = InMemoryStorage::new();
+        storage.add_todo(kept).await.unwrap();
+        storage.add_todo(removed).await.unwrap();
+
         let mut mock_user_interface = MockUserInterface::new();
-        let mut original_todo_list = create!(Todos, number_todos: 4);
-        let todo = original_todo_list.get_mut(3).unwrap();
-        let todo_id = todo.id;
+        mock_user_interface
+            .expect_remove_todo_message()
+            .withf(move |uuid, _| *uuid == removed_id)
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_user_interface
+            .expect_undo_message()
+            .withf(|command| command == "rm")
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_user_interface
+            .expect_redo_message()
+            .withf(|command| command == "rm")
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut sizes = mockall::Sequence::new();
+        for size in [2, 1] {
+            mock_user_interface
+                .expect_show_todo_list()
+                .withf(move |todos| todos.iter().count() == size)
+                .times(1)
+                .in_sequence(&mut sizes)
+                .returning(|_| Ok(()));
+        }
+        mock_user_interface
+            .expect_history_empty_message()
+            .withf(|command| command == "redo")
+            .times(1)
+            .returning(|_| Ok(()));
 
-        mock_storage
-            .expect_get_todo_list()
-            .return_once(|| Ok(original_todo_list));
+        let mut todo_cli =
+            TodoControllerImpl::new(Box::new(storage), Box::new(mock_user_interface));
 
-        mock_storage
-            .expect_remove_todo()
-            .times(1)
-            .returning(|_| Ok(1));
+        todo_cli
+            .execute(UserOptions::RemoveTodo(TodoRef::Position(2)))
+            .await
+            .unwrap();
+        let outcome = todo_cli.execute(UserOptions::Undo).await.unwrap();
+        assert_eq!(outcome, Outcome::Completed);
+        let outcome = todo_cli.execute(UserOptions::Redo).await.unwrap();
+        assert_eq!(outcome, Outcome::Completed);
+        let outcome = todo_cli.execute(UserOptions::Redo).await.unwrap();
+        assert_eq!(outcome, Outcome::NotFound);
+    }
+
+    #[tokio::test]
+    async fn should_only_clear_the_recorded_todos_again_on_redo() {
+        let cleared = Todo::new("cleared".to_string(), Uuid::new_v4());
+        let added_later = Todo::new("added later".to_string(), Uuid::new_v4());
+        let added_later_id = added_later.id;
+        let mut storage = InMemoryStorage::new();
+        storage.add_todo(cleared).await.unwrap();
 
+        let mut mock_user_interface = MockUserInterface::new();
         mock_user_interface
-            .expect_remove_todo_message()
+            .expect_clear_todo_message()
             .times(1)
             .returning(|| Ok(()));
+        mock_user_interface
+            .expect_undo_message()
+            .withf(|command| command == "clear")
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_user_interface
+            .expect_redo_message()
+            .withf(|command| command == "clear")
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut sizes = mockall::Sequence::new();
+        for size in [1, 1] {
+            mock_user_interface
+                .expect_show_todo_list()
+                .withf(move |todos| todos.iter().count() == size)
+                .times(1)
+                .in_sequence(&mut sizes)
+                .returning(|_| Ok(()));
+        }
+
+        let mut todo_cli =
+            TodoControllerImpl::new(Box::new(storage), Box::new(mock_user_interface));
+
+        todo_cli.execute(UserOptions::ClearList).await.unwrap();
+        todo_cli.execute(UserOptions::Undo).await.unwrap();
+        // Added by another session, so it is not in this one's history.
+        todo_cli
+            .todo_repository
+            .add_todo(added_later)
+            .await
+            .unwrap();
+        let outcome = todo_cli.execute(UserOptions::Redo).await.unwrap();
 
-        let mut todo_cli_mock = TodoControllerImpl {
-            user_interface: Box::new(mock_user_interface),
-            todo_repository: Box::new(mock_storage),
+        assert_eq!(outcome, Outcome::Completed);
+        let todo_list = todo_cli.todo_repository.get_todo_list().await.unwrap();
+        assert_eq!(
+            todo_list.iter().map(|todo| todo.id).collect::<Vec<_>>(),
+            vec![added_later_id]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_archive_old_done_todos_and_unarchive_them_by_position() {
+        let open = Todo::new("open".to_string(), Uuid::new_v4());
+        let two_days_ago = timestamp() - chrono::Duration::days(2);
+        let old = Todo {
+            done: true,
+            completed_at: Some(two_days_ago),
+            updated_at: two_days_ago,
+            ..Todo::new("old".to_string(), Uuid::new_v4())
+        };
+        let old_id = old.id;
+        let recent = Todo {
+            done: true,
+            completed_at: Some(timestamp()),
+            ..Todo::new("recent".to_string(), Uuid::new_v4())
         };
+        let mut storage = InMemoryStorage::new();
+        for todo in [open, old, recent] {
+            storage.add_todo(todo).await.unwrap();
+        }
 
-        todo_cli_mock
-            .remove_todo(todo_id)
+        let mut mock_user_interface = MockUserInterface::new();
+        let mut sizes = mockall::Sequence::new();
+        for size in [2, 3, 3] {
+            mock_user_interface
+                .expect_show_todo_list()
+                .withf(move |todos| todos.iter().count() == size)
+                .times(1)
+                .in_sequence(&mut sizes)
+                .returning(|_| Ok(()));
+        }
+        mock_user_interface
+            .expect_unarchive_todo_message()
+            .withf(move |uuid, _| *uuid == old_id)
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let mut todo_cli =
+            TodoControllerImpl::new(Box::new(storage), Box::new(mock_user_interface))
+                .with_archive_after(Some("1d".parse().unwrap()));
+
+        todo_cli.execute(UserOptions::ShowList).await.unwrap();
+        let outcome = todo_cli
+            .execute(UserOptions::UnarchiveTodo(TodoRef::Position(1)))
             .await
-            .expect("Should remove the fourth todo")
+            .unwrap();
+        assert_eq!(outcome, Outcome::Completed);
+        // Archiving already ran for this session, so it stays.
+        todo_cli.execute(UserOptions::ShowList).await.unwrap();
     }
 
     #[tokio::test]
-    async fn should_mark_todo_as_done() {
+    async fn should_auto_archive_once_and_not_for_help_or_quit() {
         let mut mock_storage = MockStorage::new();
         let mut mock_user_interface = MockUserInterface::new();
-        let mut todo_list = create!(Todos, number_todos: 4);
-        let todo = todo_list.get_mut(3).unwrap();
-        let todo_id = todo.id;
-        todo.done = true;
 
         mock_storage
-            .expect_mark_todo_done()
+            .expect_archive_done()
             .times(1)
-            .returning(|_| Ok(1));
-
+            .returning(|_| Ok(0));
+        mock_storage
+            .expect_get_todo_list()
+            .times(2)
+            .returning(|| Ok(Todos::new(vec![])));
         mock_user_interface
-            .expect_mark_done_message()
+            .expect_show_help()
             .times(1)
             .returning(|| Ok(()));
-
         mock_user_interface
             .expect_show_todo_list()
-            .times(1)
+            .times(2)
             .returning(|_| Ok(()));
 
-        mock_storage
-            .expect_get_todo_list()
-            .times(1)
-            .returning(move || Ok(todo_list.clone()));
-
-        mock_user_interface
-            .expect_mark_done_message()
-            .times(1)
-            .returning(|| Ok(()));
-
-        let mut todo_cli_mock = TodoControllerImpl {
-            user_interface: Box::new(mock_user_interface),
-            todo_repository: Box::new(mock_storage),
-        };
+        let mut todo_cli =
+            TodoControllerImpl::new(Box::new(mock_storage), Box::new(mock_user_interface))
+                .with_archive_after(Some("1d".parse().unwrap()));
 
-        todo_cli_mock
-            .mark_todo_done(todo_id)
-            .await
-            .expect("Should mark the last todo as done")
+        todo_cli.execute(UserOptions::Help).await.unwrap();
+        todo_cli.execute(UserOptions::ShowList).await.unwrap();
+        todo_cli.execute(UserOptions::ShowList).await.unwrap();
+        let outcome = todo_cli.execute(UserOptions::Quit).await.unwrap();
+        assert_eq!(outcome, Outcome::Quit);
     }
 }
@@ -0,0 +1,127 @@
+use std::collections::VecDeque;
+use uuid::Uuid;
+
+// How many changes `undo` can go back.
+pub const HISTORY_DEPTH: usize = 50;
+
+// A change made to the current list, with what it takes to take it back or make it again.
+// Removed todos sit in the trash, so most changes are undone by restoring or removing.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Added {
+        id: Uuid,
+    },
+    Removed {
+        id: Uuid,
+        // Subtasks handed to the removed todo's parent instead of going with it.
+        reparented: Vec<Uuid>,
+    },
+    Done {
+        // Only the todos this marked done, subtasks included.
+        ids: Vec<Uuid>,
+        // The next occurrence added for a recurring todo.
+        next: Option<Uuid>,
+    },
+    Cleared {
+        // The todos at the top of the list; their subtasks come back with them.
+        ids: Vec<Uuid>,
+    },
+    Edited {
+        id: Uuid,
+        before: String,
+        after: String,
+    },
+}
+
+impl Change {
+    // The command that made the change.
+    pub fn command(&self) -> &'static str {
+        match self {
+            Change::Added { .. } => "add",
+            Change::Removed { .. } => "rm",
+            Change::Done { .. } => "done",
+            Change::Cleared { .. } => "clear",
+            Change::Edited { .. } => "edit",
+        }
+    }
+}
+
+// Changes that can be undone, newest last, and the undone ones that can be made again.
+pub struct History {
+    undo: VecDeque<Change>,
+    redo: Vec<Change>,
+    depth: usize,
+}
+
+impl History {
+    pub fn new(depth: usize) -> Self {
+        History {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            depth,
+        }
+    }
+
+    // A new change can no longer be followed by the ones undone before it.
+    pub fn record(&mut self, change: Change) {
+        self.redo.clear();
+        self.push_undo(change);
+    }
+
+    pub fn undo(&mut self) -> Option<Change> {
+        let change = self.undo.pop_back()?;
+        self.redo.push(change.clone());
+        Some(change)
+    }
+
+    pub fn redo(&mut self) -> Option<Change> {
+        let change = self.redo.pop()?;
+        self.push_undo(change.clone());
+        Some(change)
+    }
+
+    // Changes made on another list cannot be undone from this one.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    fn push_undo(&mut self, change: Change) {
+        self.undo.push_back(change);
+        if self.undo.len() > self.depth {
+            self.undo.pop_front();
+        }
+    }
+}
+
+impl Default for History {
+    fn default() -> Self {
+        History::new(HISTORY_DEPTH)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn added() -> Change {
+        Change::Added { id: Uuid::new_v4() }
+    }
+
+    #[test]
+    fn should_forget_the_oldest_changes_and_redo_only_what_was_undone() {
+        let mut history = History::new(2);
+        let changes = [added(), added(), added()];
+        for change in &changes {
+            history.record(change.clone());
+        }
+
+        assert_eq!(history.undo(), Some(changes[2].clone()));
+        assert_eq!(history.undo(), Some(changes[1].clone()));
+        assert_eq!(history.undo(), None);
+
+        assert_eq!(history.redo(), Some(changes[1].clone()));
+        history.record(added());
+        assert_eq!(history.redo(), None);
+    }
+}
@@ -1 +1,5 @@
+pub mod config;
 pub mod connect;
+pub mod migrations;
+pub mod pool;
+pub mod reconnect;
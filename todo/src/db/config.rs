@@ -0,0 +1,118 @@
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use error::ConfigError;
+pub(crate) mod error;
+
+pub const DATABASE_URL_VAR: &str = "DATABASE_URL";
+
+#[derive(Deserialize, Default)]
+pub struct ConfigFile {
+    pub database_url: Option<String>,
+}
+
+pub fn config_file_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("todo").join("config.toml"))
+}
+
+pub fn read_config_file(path: &Path) -> Result<ConfigFile, ConfigError> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => {
+            toml::from_str(&contents).map_err(|error| ConfigError::Parse(path.to_path_buf(), error))
+        }
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(ConfigFile::default()),
+        Err(error) => Err(ConfigError::Read(path.to_path_buf(), error)),
+    }
+}
+
+pub fn database_url(flag: Option<&str>) -> Result<String, ConfigError> {
+    let path = config_file_path();
+    let env = std::env::var(DATABASE_URL_VAR).ok();
+    let file = || match &path {
+        Some(path) => read_config_file(path),
+        None => Ok(ConfigFile::default()),
+    };
+
+    resolve_database_url(env, flag.map(str::to_string), file)?
+        .ok_or(ConfigError::MissingDatabaseUrl(path))
+}
+
+// The environment wins over the config file, which wins over the command line flag. The file
+// is not read when the environment variable is set, so a broken one cannot get in its way.
+pub fn resolve_database_url(
+    env: Option<String>,
+    flag: Option<String>,
+    file: impl FnOnce() -> Result<ConfigFile, ConfigError>,
+) -> Result<Option<String>, ConfigError> {
+    let given = |url: &String| !url.trim().is_empty();
+    if let Some(url) = env.filter(given) {
+        return Ok(Some(url));
+    }
+    Ok(file()?.database_url.filter(given).or(flag.filter(given)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(database_url: Option<&str>) -> ConfigFile {
+        ConfigFile {
+            database_url: database_url.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn should_prefer_environment_variable() {
+        let url = resolve_database_url(
+            Some("postgres://env".to_string()),
+            Some("postgres://flag".to_string()),
+            || Ok(file(Some("postgres://file"))),
+        );
+
+        assert_eq!(url.unwrap().as_deref(), Some("postgres://env"));
+    }
+
+    #[test]
+    fn should_fall_back_to_config_file_then_flag() {
+        let from_file = resolve_database_url(None, Some("postgres://flag".to_string()), || {
+            Ok(file(Some("postgres://file")))
+        });
+        let from_flag =
+            resolve_database_url(None, Some("postgres://flag".to_string()), || Ok(file(None)));
+
+        assert_eq!(from_file.unwrap().as_deref(), Some("postgres://file"));
+        assert_eq!(from_flag.unwrap().as_deref(), Some("postgres://flag"));
+    }
+
+    #[test]
+    fn should_not_read_config_file_when_environment_is_set() {
+        let broken =
+            || -> Result<ConfigFile, ConfigError> { panic!("the config file should not be read") };
+
+        let from_env = resolve_database_url(
+            Some("postgres://env".to_string()),
+            Some("postgres://flag".to_string()),
+            broken,
+        );
+
+        assert_eq!(from_env.unwrap().as_deref(), Some("postgres://env"));
+    }
+
+    #[test]
+    fn should_ignore_empty_values() {
+        let url = resolve_database_url(Some(" ".to_string()), None, || Ok(file(Some(""))));
+
+        assert_eq!(url.unwrap(), None);
+    }
+
+    #[test]
+    fn should_parse_config_file() {
+        let config: ConfigFile =
+            toml::from_str("database_url = \"host=localhost dbname=todo\"").unwrap();
+
+        assert_eq!(
+            config.database_url.as_deref(),
+            Some("host=localhost dbname=todo")
+        );
+    }
+}
@@ -0,0 +1,33 @@
+use std::{fmt, io, path::PathBuf};
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+    MissingDatabaseUrl(Option<PathBuf>),
+}
+
+impl std::error::Error for ConfigError {}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, error) => {
+                write!(f, "Could not read config file {}: {error}", path.display())
+            }
+            ConfigError::Parse(path, error) => {
+                write!(f, "Invalid config file {}: {error}", path.display())
+            }
+            ConfigError::MissingDatabaseUrl(path) => {
+                write!(
+                    f,
+                    "No database configured. Set the DATABASE_URL environment variable, "
+                )?;
+                if let Some(path) = path {
+                    write!(f, "add `database_url` to {}, ", path.display())?;
+                }
+                write!(f, "or pass --database-url.")
+            }
+        }
+    }
+}
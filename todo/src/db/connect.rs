@@ -1,15 +1,48 @@
+use async_trait::async_trait;
+use std::ops::DerefMut;
 use std::sync::Arc;
-
-use http_problem::prelude::*;
+use tokio::sync::{Mutex, OwnedMutexGuard};
 use tokio_postgres::Client;
 
-pub async fn connect() -> Result<Arc<Client>> {
-    let (client, connection) = tokio_postgres::connect(
-        "host=localhost user=postgres password=postgres dbname=onboarding",
-        tokio_postgres::NoTls,
-    )
-    .await?;
+use crate::db::migrations;
+use crate::repository::todo::error::StorageError;
+
+// Opens a connection to a database with every pending migration applied.
+pub async fn open(database_url: &str) -> Result<Client, StorageError> {
+    let client = open_unmigrated(database_url).await?;
+    migrations::postgres::migrate_up(&client).await?;
+    Ok(client)
+}
+
+// For the `migrate` command, which decides itself what to apply or revert.
+pub async fn open_unmigrated(database_url: &str) -> Result<Client, tokio_postgres::Error> {
+    let (client, connection) = tokio_postgres::connect(database_url, tokio_postgres::NoTls).await?;
+
+    tokio::spawn(async move {
+        if let Err(err) = connection.await {
+            eprintln!("connection error: {err}")
+        }
+    });
+    Ok(client)
+}
+
+// A single connection lent to one operation at a time, so that a transaction opened on it
+// is never interleaved with another operation's statements.
+pub type SharedClient = Arc<Mutex<Client>>;
+
+// Where `PostgresTodoRepository` gets a client from for each operation.
+#[async_trait]
+pub trait ClientSource {
+    type Handle: DerefMut<Target = Client> + Send;
+
+    async fn get(&self) -> Result<Self::Handle, StorageError>;
+}
+
+#[async_trait]
+impl ClientSource for SharedClient {
+    type Handle = OwnedMutexGuard<Client>;
 
-    tokio::spawn(async move { connection.await.unwrap() });
-    Ok(Arc::new(client))
+    async fn get(&self) -> Result<OwnedMutexGuard<Client>, StorageError> {
+        Ok(self.clone().lock_owned().await)
+    }
 }
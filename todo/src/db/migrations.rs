@@ -0,0 +1,166 @@
+use chrono::{DateTime, Utc};
+
+pub mod postgres;
+pub mod sqlite;
+
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub up: &'static str,
+    pub down: &'static str,
+}
+
+pub struct MigrationStatus {
+    pub migration: &'static Migration,
+    pub applied_at: Option<DateTime<Utc>>,
+}
+
+macro_rules! migration {
+    ($dialect:literal, $version:literal, $name:literal) => {
+        Migration {
+            version: $version,
+            name: $name,
+            up: include_str!(concat!(
+                "../../migrations/",
+                $dialect,
+                "/",
+                $name,
+                ".up.sql"
+            )),
+            down: include_str!(concat!(
+                "../../migrations/",
+                $dialect,
+                "/",
+                $name,
+                ".down.sql"
+            )),
+        }
+    };
+}
+
+pub static POSTGRES: &[Migration] = &[
+    migration!("postgres", 1, "0001_create_todos"),
+    migration!("postgres", 2, "0002_add_priority"),
+    migration!("postgres", 3, "0003_add_due_dates"),
+    migration!("postgres", 4, "0004_add_timestamps"),
+    migration!("postgres", 5, "0005_create_tags"),
+    migration!("postgres", 6, "0006_create_lists"),
+    migration!("postgres", 7, "0007_add_subtasks"),
+    migration!("postgres", 8, "0008_add_recurrence"),
+    migration!("postgres", 9, "0009_create_dependencies"),
+    migration!("postgres", 10, "0010_add_positions"),
+    migration!("postgres", 11, "0011_add_trash"),
+    migration!("postgres", 12, "0012_add_archive"),
+    migration!("postgres", 13, "0013_add_origins"),
+];
+
+pub static SQLITE: &[Migration] = &[
+    migration!("sqlite", 1, "0001_create_todos"),
+    migration!("sqlite", 2, "0002_add_priority"),
+    migration!("sqlite", 3, "0003_add_due_dates"),
+    migration!("sqlite", 4, "0004_add_timestamps"),
+    migration!("sqlite", 5, "0005_create_tags"),
+    migration!("sqlite", 6, "0006_create_lists"),
+    migration!("sqlite", 7, "0007_add_subtasks"),
+    migration!("sqlite", 8, "0008_add_recurrence"),
+    migration!("sqlite", 9, "0009_create_dependencies"),
+    migration!("sqlite", 10, "0010_add_positions"),
+    migration!("sqlite", 11, "0011_add_trash"),
+    migration!("sqlite", 12, "0012_add_archive"),
+    migration!("sqlite", 13, "0013_add_origins"),
+];
+
+pub fn pending<'a>(migrations: &'a [Migration], applied: &[i64]) -> Vec<&'a Migration> {
+    migrations
+        .iter()
+        .filter(|migration| !applied.contains(&migration.version))
+        .collect()
+}
+
+// Newest first, so they can be reverted in order.
+pub fn to_revert<'a>(
+    migrations: &'a [Migration],
+    applied: &[i64],
+    steps: usize,
+) -> Vec<&'a Migration> {
+    migrations
+        .iter()
+        .rev()
+        .filter(|migration| applied.contains(&migration.version))
+        .take(steps)
+        .collect()
+}
+
+pub fn statuses(
+    migrations: &'static [Migration],
+    applied: &[(i64, DateTime<Utc>)],
+) -> Vec<MigrationStatus> {
+    migrations
+        .iter()
+        .map(|migration| MigrationStatus {
+            migration,
+            applied_at: applied
+                .iter()
+                .find(|(version, _)| *version == migration.version)
+                .map(|(_, applied_at)| *applied_at),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static MIGRATIONS: &[Migration] = &[
+        Migration {
+            version: 1,
+            name: "first",
+            up: "",
+            down: "",
+        },
+        Migration {
+            version: 2,
+            name: "second",
+            up: "",
+            down: "",
+        },
+        Migration {
+            version: 3,
+            name: "third",
+            up: "",
+            down: "",
+        },
+    ];
+
+    fn versions(migrations: Vec<&Migration>) -> Vec<i64> {
+        migrations
+            .iter()
+            .map(|migration| migration.version)
+            .collect()
+    }
+
+    #[test]
+    fn should_list_pending_migrations_in_order() {
+        assert_eq!(versions(pending(MIGRATIONS, &[1])), vec![2, 3]);
+        assert_eq!(versions(pending(MIGRATIONS, &[1, 2, 3])), Vec::<i64>::new());
+    }
+
+    #[test]
+    fn should_revert_newest_migrations_first() {
+        assert_eq!(versions(to_revert(MIGRATIONS, &[1, 2], 1)), vec![2]);
+        assert_eq!(
+            versions(to_revert(MIGRATIONS, &[1, 2, 3], 5)),
+            vec![3, 2, 1]
+        );
+    }
+
+    #[test]
+    fn should_have_increasing_versions() {
+        for migrations in [POSTGRES, SQLITE] {
+            assert!(migrations
+                .windows(2)
+                .all(|pair| pair[0].version < pair[1].version));
+        }
+        assert_eq!(POSTGRES.len(), SQLITE.len());
+    }
+}
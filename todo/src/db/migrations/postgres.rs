@@ -0,0 +1,116 @@
+use chrono::{DateTime, Utc};
+use tokio_postgres::Client;
+
+use crate::db::migrations::{self, Migration, MigrationStatus, POSTGRES};
+use crate::repository::todo::error::StorageError;
+
+// Serializes concurrent migration runs against the same database.
+const LOCK_KEY: i64 = 0x746f646f;
+
+const CREATE_TABLE: &str = "CREATE TABLE IF NOT EXISTS schema_migrations (
+    version BIGINT PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    applied_at TIMESTAMPTZ NOT NULL
+)";
+
+async fn applied(client: &Client) -> Result<Vec<(i64, DateTime<Utc>)>, StorageError> {
+    client.execute(CREATE_TABLE, &[]).await?;
+    let rows = client
+        .query(
+            "SELECT version, applied_at FROM schema_migrations ORDER BY version",
+            &[],
+        )
+        .await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.get("version"), row.get("applied_at")))
+        .collect())
+}
+
+async fn applied_versions(client: &Client) -> Result<Vec<i64>, StorageError> {
+    Ok(applied(client)
+        .await?
+        .into_iter()
+        .map(|(version, _)| version)
+        .collect())
+}
+
+// The migration and its bookkeeping row go out as one simple-query batch, which Postgres
+// runs as a single implicit transaction.
+async fn apply(client: &Client, sql: &str, bookkeeping: String) -> Result<(), StorageError> {
+    client
+        .batch_execute(&format!("{sql};\n{bookkeeping};"))
+        .await?;
+    Ok(())
+}
+
+async fn with_lock<T>(
+    client: &Client,
+    run: impl std::future::Future<Output = Result<T, StorageError>>,
+) -> Result<T, StorageError> {
+    client
+        .execute("SELECT pg_advisory_lock($1)", &[&LOCK_KEY])
+        .await?;
+    let result = run.await;
+    client
+        .execute("SELECT pg_advisory_unlock($1)", &[&LOCK_KEY])
+        .await?;
+    result
+}
+
+pub async fn migrate_up(client: &Client) -> Result<Vec<&'static Migration>, StorageError> {
+    with_lock(client, async {
+        let pending = migrations::pending(POSTGRES, &applied_versions(client).await?);
+        for migration in &pending {
+            let bookkeeping = format!(
+                "INSERT INTO schema_migrations(version, name, applied_at) VALUES ({}, '{}', now())",
+                migration.version, migration.name
+            );
+            apply(client, migration.up, bookkeeping).await?;
+        }
+        Ok(pending)
+    })
+    .await
+}
+
+pub async fn migrate_down(
+    client: &Client,
+    steps: usize,
+) -> Result<Vec<&'static Migration>, StorageError> {
+    with_lock(client, async {
+        let reverted = migrations::to_revert(POSTGRES, &applied_versions(client).await?, steps);
+        for migration in &reverted {
+            let bookkeeping = format!(
+                "DELETE FROM schema_migrations WHERE version = {}",
+                migration.version
+            );
+            apply(client, migration.down, bookkeeping).await?;
+        }
+        Ok(reverted)
+    })
+    .await
+}
+
+pub async fn status(client: &Client) -> Result<Vec<MigrationStatus>, StorageError> {
+    Ok(migrations::statuses(POSTGRES, &applied(client).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::test_utils;
+
+    #[tokio::test]
+    async fn should_leave_nothing_pending_after_migrating() {
+        test_utils::with_client(|client| async move {
+            let client = client.lock().await;
+            let applied = migrate_up(&client).await.unwrap();
+            let statuses = status(&client).await.unwrap();
+
+            assert!(applied.is_empty());
+            assert_eq!(statuses.len(), POSTGRES.len());
+            assert!(statuses.iter().all(|status| status.applied_at.is_some()));
+        })
+        .await;
+    }
+}
@@ -0,0 +1,162 @@
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+
+use crate::db::migrations::{self, Migration, MigrationStatus, SQLITE};
+use crate::repository::todo::error::StorageError;
+
+const CREATE_TABLE: &str = "CREATE TABLE IF NOT EXISTS schema_migrations (
+    version INTEGER PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    applied_at TEXT NOT NULL
+)";
+
+fn applied(connection: &Connection) -> Result<Vec<(i64, DateTime<Utc>)>, StorageError> {
+    connection.execute(CREATE_TABLE, [])?;
+    let mut statement =
+        connection.prepare("SELECT version, applied_at FROM schema_migrations ORDER BY version")?;
+    let applied = statement
+        .query_map([], |row| Ok((row.get("version")?, row.get("applied_at")?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(applied)
+}
+
+fn applied_versions(connection: &Connection) -> Result<Vec<i64>, StorageError> {
+    Ok(applied(connection)?
+        .into_iter()
+        .map(|(version, _)| version)
+        .collect())
+}
+
+// SQLite refuses some schema changes, like adding a REFERENCES column with a default, to
+// tables with rows while foreign keys are enforced. The pragma has no effect inside a
+// transaction, so it is switched off around them.
+fn without_foreign_keys<T>(
+    connection: &mut Connection,
+    run: impl FnOnce(&mut Connection) -> Result<T, StorageError>,
+) -> Result<T, StorageError> {
+    let enforced: bool = connection.pragma_query_value(None, "foreign_keys", |row| row.get(0))?;
+    connection.pragma_update(None, "foreign_keys", false)?;
+    let result = run(connection);
+    connection.pragma_update(None, "foreign_keys", enforced)?;
+    result
+}
+
+pub fn migrate_up(connection: &mut Connection) -> Result<Vec<&'static Migration>, StorageError> {
+    without_foreign_keys(connection, apply_pending)
+}
+
+fn apply_pending(connection: &mut Connection) -> Result<Vec<&'static Migration>, StorageError> {
+    let pending = migrations::pending(SQLITE, &applied_versions(connection)?);
+    for migration in &pending {
+        let transaction = connection.transaction()?;
+        transaction.execute_batch(migration.up)?;
+        transaction.execute(
+            "INSERT INTO schema_migrations(version, name, applied_at) VALUES (?1, ?2, ?3)",
+            params![migration.version, migration.name, Utc::now()],
+        )?;
+        transaction.commit()?;
+    }
+    Ok(pending)
+}
+
+pub fn migrate_down(
+    connection: &mut Connection,
+    steps: usize,
+) -> Result<Vec<&'static Migration>, StorageError> {
+    without_foreign_keys(connection, |connection| revert(connection, steps))
+}
+
+fn revert(
+    connection: &mut Connection,
+    steps: usize,
+) -> Result<Vec<&'static Migration>, StorageError> {
+    let reverted = migrations::to_revert(SQLITE, &applied_versions(connection)?, steps);
+    for migration in &reverted {
+        let transaction = connection.transaction()?;
+        transaction.execute_batch(migration.down)?;
+        transaction.execute(
+            "DELETE FROM schema_migrations WHERE version = ?1",
+            params![migration.version],
+        )?;
+        transaction.commit()?;
+    }
+    Ok(reverted)
+}
+
+pub fn status(connection: &Connection) -> Result<Vec<MigrationStatus>, StorageError> {
+    Ok(migrations::statuses(SQLITE, &applied(connection)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table_exists(connection: &Connection, table: &str) -> bool {
+        connection
+            .query_row(
+                "SELECT count(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+                params![table],
+                |row| row.get::<_, i64>(0),
+            )
+            .unwrap()
+            == 1
+    }
+
+    #[test]
+    fn should_apply_every_migration_once() {
+        let mut connection = Connection::open_in_memory().unwrap();
+
+        let applied = migrate_up(&mut connection).unwrap();
+        let applied_again = migrate_up(&mut connection).unwrap();
+
+        assert_eq!(applied.len(), SQLITE.len());
+        assert!(applied_again.is_empty());
+        assert!(table_exists(&connection, "todos"));
+        assert!(status(&connection)
+            .unwrap()
+            .iter()
+            .all(|status| status.applied_at.is_some()));
+    }
+
+    #[test]
+    fn should_migrate_tables_with_rows_while_enforcing_foreign_keys() {
+        let mut connection = Connection::open_in_memory().unwrap();
+        connection
+            .pragma_update(None, "foreign_keys", true)
+            .unwrap();
+        migrate_up(&mut connection).unwrap();
+        connection
+            .execute(
+                "INSERT INTO todos(id, message) VALUES('7f35b30f-9121-4be9-a1da-1d35e218c7f9', 'kept')",
+                [],
+            )
+            .unwrap();
+
+        migrate_down(&mut connection, 1).unwrap();
+        migrate_up(&mut connection).unwrap();
+
+        let enforced: bool = connection
+            .pragma_query_value(None, "foreign_keys", |row| row.get(0))
+            .unwrap();
+        assert!(enforced);
+        let count: i64 = connection
+            .query_row("SELECT count(*) FROM todos", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn should_revert_migrations() {
+        let mut connection = Connection::open_in_memory().unwrap();
+        migrate_up(&mut connection).unwrap();
+
+        let reverted = migrate_down(&mut connection, SQLITE.len()).unwrap();
+
+        assert_eq!(reverted.len(), SQLITE.len());
+        assert!(!table_exists(&connection, "todos"));
+        assert!(status(&connection)
+            .unwrap()
+            .iter()
+            .all(|status| status.applied_at.is_none()));
+    }
+}
@@ -0,0 +1,123 @@
+use async_trait::async_trait;
+use bb8::{Pool, PooledConnection, RunError};
+use bb8_postgres::PostgresConnectionManager;
+use std::time::Duration;
+use tokio_postgres::NoTls;
+
+use crate::db::connect::ClientSource;
+use crate::db::migrations;
+use crate::repository::todo::error::StorageError;
+
+pub type PostgresPool = Pool<PostgresConnectionManager<NoTls>>;
+
+pub struct PoolConfig {
+    pub max_size: u32,
+    pub idle_timeout: Option<Duration>,
+    pub health_check: bool,
+    pub connection_timeout: Duration,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        PoolConfig {
+            max_size: 4,
+            idle_timeout: Some(Duration::from_secs(600)),
+            health_check: true,
+            connection_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+pub async fn build_pool(
+    database_url: &str,
+    config: &PoolConfig,
+) -> Result<PostgresPool, StorageError> {
+    let manager = PostgresConnectionManager::new_from_stringlike(database_url, NoTls)?;
+    let pool = Pool::builder()
+        .max_size(config.max_size)
+        .idle_timeout(config.idle_timeout)
+        .test_on_check_out(config.health_check)
+        .connection_timeout(config.connection_timeout)
+        .build(manager)
+        .await?;
+    migrations::postgres::migrate_up(&*ClientSource::get(&pool).await?).await?;
+    Ok(pool)
+}
+
+#[async_trait]
+impl ClientSource for PostgresPool {
+    type Handle = PooledConnection<'static, PostgresConnectionManager<NoTls>>;
+
+    async fn get(&self) -> Result<Self::Handle, StorageError> {
+        self.get_owned().await.map_err(|error| match error {
+            RunError::User(error) => StorageError::Postgres(error),
+            RunError::TimedOut => StorageError::PoolTimeout,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::test_utils;
+
+    async fn backend_pid(client: &tokio_postgres::Client) -> i32 {
+        client
+            .query_one("SELECT pg_backend_pid()", &[])
+            .await
+            .unwrap()
+            .get(0)
+    }
+
+    #[tokio::test]
+    async fn should_lend_a_separate_connection_per_borrower() {
+        let pool = build_pool(&test_utils::database_url(), &PoolConfig::default())
+            .await
+            .unwrap();
+
+        let first = pool.get().await.unwrap();
+        let second = pool.get().await.unwrap();
+
+        assert_ne!(backend_pid(&first).await, backend_pid(&second).await);
+    }
+
+    #[tokio::test]
+    async fn should_replace_a_dropped_connection_on_check_out() {
+        let config = PoolConfig {
+            max_size: 1,
+            ..PoolConfig::default()
+        };
+        let pool = build_pool(&test_utils::database_url(), &config)
+            .await
+            .unwrap();
+        let pid = backend_pid(&pool.get().await.unwrap()).await;
+
+        let admin = test_utils::connect().await.unwrap();
+        admin
+            .execute("SELECT pg_terminate_backend($1)", &[&pid])
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let new_pid = backend_pid(&pool.get().await.unwrap()).await;
+        assert_ne!(new_pid, pid);
+    }
+
+    #[tokio::test]
+    async fn should_time_out_when_the_pool_is_exhausted() {
+        let config = PoolConfig {
+            max_size: 1,
+            connection_timeout: Duration::from_millis(100),
+            ..PoolConfig::default()
+        };
+        let pool = build_pool(&test_utils::database_url(), &config)
+            .await
+            .unwrap();
+
+        let _borrowed = pool.get().await.unwrap();
+        let error = ClientSource::get(&pool).await.err().unwrap();
+
+        assert!(matches!(error, StorageError::PoolTimeout));
+        assert!(error.is_retryable());
+    }
+}
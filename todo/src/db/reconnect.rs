@@ -0,0 +1,138 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, OwnedMutexGuard};
+use tokio_postgres::Client;
+
+use crate::db::connect::{open, ClientSource};
+use crate::repository::todo::error::StorageError;
+
+#[derive(Clone, Copy)]
+pub struct Backoff {
+    pub initial: Duration,
+    pub max: Duration,
+    pub attempts: u32,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Backoff {
+            initial: Duration::from_millis(100),
+            max: Duration::from_secs(5),
+            attempts: 6,
+        }
+    }
+}
+
+impl Backoff {
+    // How long to wait before each retry, doubling up to `max`.
+    pub fn delays(&self) -> impl Iterator<Item = Duration> + '_ {
+        (0..self.attempts.saturating_sub(1)).map(|retry| {
+            self.initial
+                .saturating_mul(2u32.saturating_pow(retry))
+                .min(self.max)
+        })
+    }
+}
+
+// A single Postgres connection that is transparently re-established, with exponential
+// backoff, the next time it is needed after the server drops it.
+pub struct ReconnectingClient {
+    database_url: String,
+    backoff: Backoff,
+    client: Arc<Mutex<Client>>,
+}
+
+impl ReconnectingClient {
+    pub async fn connect(database_url: String, backoff: Backoff) -> Result<Self, StorageError> {
+        let client = connect_with_backoff(&database_url, &backoff).await?;
+        Ok(ReconnectingClient {
+            database_url,
+            backoff,
+            client: Arc::new(Mutex::new(client)),
+        })
+    }
+}
+
+#[async_trait]
+impl ClientSource for ReconnectingClient {
+    type Handle = OwnedMutexGuard<Client>;
+
+    async fn get(&self) -> Result<OwnedMutexGuard<Client>, StorageError> {
+        let mut client = self.client.clone().lock_owned().await;
+        if client.is_closed() {
+            *client = connect_with_backoff(&self.database_url, &self.backoff).await?;
+        }
+        Ok(client)
+    }
+}
+
+async fn connect_with_backoff(
+    database_url: &str,
+    backoff: &Backoff,
+) -> Result<Client, StorageError> {
+    let mut delays = backoff.delays();
+    loop {
+        match open(database_url).await {
+            Ok(client) => return Ok(client),
+            Err(error) => match delays.next() {
+                Some(delay) if error.is_retryable() => tokio::time::sleep(delay).await,
+                _ => return Err(error),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::test_utils;
+
+    #[test]
+    fn should_double_delays_up_to_the_maximum() {
+        let backoff = Backoff {
+            initial: Duration::from_millis(100),
+            max: Duration::from_millis(500),
+            attempts: 6,
+        };
+
+        let delays: Vec<u64> = backoff
+            .delays()
+            .map(|delay| delay.as_millis() as u64)
+            .collect();
+
+        assert_eq!(delays, vec![100, 200, 400, 500, 500]);
+    }
+
+    #[tokio::test]
+    async fn should_reconnect_after_the_connection_is_dropped() {
+        let source = ReconnectingClient::connect(test_utils::database_url(), Backoff::default())
+            .await
+            .unwrap();
+        let client = source.get().await.unwrap();
+        let backend_pid: i32 = client
+            .query_one("SELECT pg_backend_pid()", &[])
+            .await
+            .unwrap()
+            .get(0);
+
+        let admin = test_utils::connect().await.unwrap();
+        admin
+            .execute("SELECT pg_terminate_backend($1)", &[&backend_pid])
+            .await
+            .unwrap();
+        let error = client.query_one("SELECT 1", &[]).await.unwrap_err();
+        assert!(StorageError::from(error).is_retryable());
+        drop(client);
+
+        let new_backend_pid: i32 = source
+            .get()
+            .await
+            .unwrap()
+            .query_one("SELECT pg_backend_pid()", &[])
+            .await
+            .unwrap()
+            .get(0);
+        assert_ne!(new_backend_pid, backend_pid);
+    }
+}
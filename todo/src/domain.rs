@@ -1,2 +1,10 @@
+pub mod age;
+pub mod due;
+pub mod list;
+pub mod priority;
+pub mod recurrence;
+pub mod subtask;
+pub mod tag;
 pub mod todo;
+pub mod todo_ref;
 pub mod todos;
@@ -0,0 +1,100 @@
+use chrono::{DateTime, Duration, Utc};
+use std::{fmt, str::FromStr};
+
+// How long ago something happened, written like "30d", "2w" or "12h".
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Age {
+    amount: u32,
+    unit: AgeUnit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AgeUnit {
+    Hours,
+    Days,
+    Weeks,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct InvalidAge(pub String);
+
+impl fmt::Display for InvalidAge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "\"{}\" is not an age; try \"30d\", \"2w\" or \"12h\"",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for InvalidAge {}
+
+impl Age {
+    // The moment this long before `now`.
+    pub fn before(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let amount = i64::from(self.amount);
+        now - match self.unit {
+            AgeUnit::Hours => Duration::hours(amount),
+            AgeUnit::Days => Duration::days(amount),
+            AgeUnit::Weeks => Duration::weeks(amount),
+        }
+    }
+}
+
+impl FromStr for Age {
+    type Err = InvalidAge;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+        let invalid = || InvalidAge(input.to_string());
+        let split = input
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(invalid)?;
+        let (amount, unit) = input.split_at(split);
+        let unit = match unit.to_ascii_lowercase().as_str() {
+            "h" => AgeUnit::Hours,
+            "d" => AgeUnit::Days,
+            "w" => AgeUnit::Weeks,
+            _ => return Err(invalid()),
+        };
+        let amount = amount.parse().map_err(|_| invalid())?;
+        Ok(Age { amount, unit })
+    }
+}
+
+impl fmt::Display for Age {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit = match self.unit {
+            AgeUnit::Hours => 'h',
+            AgeUnit::Days => 'd',
+            AgeUnit::Weeks => 'w',
+        };
+        write!(f, "{}{unit}", self.amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_ages_and_count_back_from_now() {
+        let now = "2026-10-18T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+
+        let age: Age = "30d".parse().unwrap();
+        assert_eq!(age.to_string(), "30d");
+        assert_eq!(
+            age.before(now),
+            "2026-09-18T12:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+        assert_eq!(
+            "2W".parse::<Age>().unwrap().before(now),
+            "2026-10-04T12:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+        assert_eq!("0h".parse::<Age>().unwrap().before(now), now);
+        for invalid in ["", "30", "d", "-3d", "3 days", "3y"] {
+            assert_eq!(invalid.parse::<Age>(), Err(InvalidAge(invalid.to_string())));
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use chrono::{Datelike, Days, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use serde::Serialize;
+use std::fmt;
+
+// A deadline in local wall-clock time. Without a time the todo is due by the end of the day.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Due {
+    pub date: NaiveDate,
+    pub time: Option<NaiveTime>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DueStatus {
+    Overdue,
+    Today,
+    Upcoming,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DueFilter {
+    Today,
+    Overdue,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct InvalidDue(pub String);
+
+impl fmt::Display for InvalidDue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "\"{}\" is not a due date; try \"tomorrow\", \"2026-11-01\" or \"fri 17:00\"",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for InvalidDue {}
+
+impl Due {
+    // Understands "today", "tomorrow", weekday names (the next one, counting today), ISO
+    // dates and an optional "HH:MM" time. A time alone means today.
+    pub fn parse(input: &str, today: NaiveDate) -> Result<Due, InvalidDue> {
+        let invalid = || InvalidDue(input.trim().to_string());
+        let words: Vec<String> = input
+            .split_whitespace()
+            .map(str::to_ascii_lowercase)
+            .collect();
+
+        let (date, time) = match words.as_slice() {
+            [time] if parse_time(time).is_some() => (today, parse_time(time)),
+            [date] => (parse_date(date, today).ok_or_else(invalid)?, None),
+            [date, time] => (
+                parse_date(date, today).ok_or_else(invalid)?,
+                Some(parse_time(time).ok_or_else(invalid)?),
+            ),
+            _ => return Err(invalid()),
+        };
+        Ok(Due { date, time })
+    }
+
+    pub fn status(&self, now: NaiveDateTime) -> DueStatus {
+        let today = now.date();
+        let past = match self.time {
+            Some(time) => self.date.and_time(time) < now,
+            None => self.date < today,
+        };
+        if past {
+            DueStatus::Overdue
+        } else if self.date == today {
+            DueStatus::Today
+        } else {
+            DueStatus::Upcoming
+        }
+    }
+}
+
+impl fmt::Display for Due {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.date.format("%a %Y-%m-%d"))?;
+        if let Some(time) = self.time {
+            write!(f, " {}", time.format("%H:%M"))?;
+        }
+        Ok(())
+    }
+}
+
+impl DueFilter {
+    pub fn matches(&self, due: Option<&Due>, now: NaiveDateTime) -> bool {
+        let status = due.map(|due| due.status(now));
+        match self {
+            DueFilter::Today => status == Some(DueStatus::Today),
+            DueFilter::Overdue => status == Some(DueStatus::Overdue),
+        }
+    }
+}
+
+fn parse_date(word: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match word {
+        "today" => return Some(today),
+        "tomorrow" => return today.checked_add_days(Days::new(1)),
+        _ => {}
+    }
+    if let Ok(weekday) = word.parse::<Weekday>() {
+        let days_ahead =
+            (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+        return today.checked_add_days(Days::new(days_ahead.into()));
+    }
+    NaiveDate::parse_from_str(word, "%Y-%m-%d").ok()
+}
+
+fn parse_time(word: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(word, "%H:%M").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(input: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(input, "%Y-%m-%d").unwrap()
+    }
+
+    fn time(input: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(input, "%H:%M").unwrap()
+    }
+
+    // A Wednesday.
+    fn today() -> NaiveDate {
+        date("2026-10-14")
+    }
+
+    #[test]
+    fn should_parse_relative_and_absolute_dates() {
+        let due = |input| Due::parse(input, today()).unwrap();
+
+        assert_eq!(due("today").date, today());
+        assert_eq!(due("Tomorrow").date, date("2026-10-15"));
+        assert_eq!(due("2026-11-01").date, date("2026-11-01"));
+        assert_eq!(due("fri").date, date("2026-10-16"));
+        assert_eq!(due("wednesday").date, today());
+        assert_eq!(due("mon").date, date("2026-10-19"));
+        assert_eq!(
+            due("fri 17:00"),
+            Due {
+                date: date("2026-10-16"),
+                time: Some(time("17:00"))
+            }
+        );
+        assert_eq!(due("09:30").time, Some(time("09:30")));
+    }
+
+    #[test]
+    fn should_reject_unknown_input() {
+        for input in ["", "someday", "fri 25:00", "2026-13-01", "next fri at noon"] {
+            assert!(Due::parse(input, today()).is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn should_tell_overdue_from_due_today() {
+        let now = today().and_time(time("12:00"));
+        let due = |input| Due::parse(input, today()).unwrap().status(now);
+
+        assert_eq!(due("today"), DueStatus::Today);
+        assert_eq!(due("today 13:00"), DueStatus::Today);
+        assert_eq!(due("today 11:00"), DueStatus::Overdue);
+        assert_eq!(due("2026-10-13"), DueStatus::Overdue);
+        assert_eq!(due("tomorrow"), DueStatus::Upcoming);
+    }
+}
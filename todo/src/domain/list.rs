@@ -0,0 +1,87 @@
+use serde::Serialize;
+use std::{fmt, str::FromStr};
+
+// Name of a todo list (project). Names are single words so commands can take them as an
+// argument, and the default `inbox` list always exists.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(transparent)]
+pub struct ListName(String);
+
+// A list with how many of its todos are open and done.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TodoList {
+    pub name: ListName,
+    pub open: u64,
+    pub done: u64,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct InvalidListName(pub String);
+
+impl fmt::Display for InvalidListName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "\"{}\" is not a list name; use a single word of letters, digits, \"-\", \"_\" or \".\"",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for InvalidListName {}
+
+impl ListName {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == ListName::default()
+    }
+}
+
+impl Default for ListName {
+    fn default() -> Self {
+        ListName("inbox".to_string())
+    }
+}
+
+impl FromStr for ListName {
+    type Err = InvalidListName;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+        let valid = input.starts_with(|char: char| char.is_alphanumeric())
+            && input
+                .chars()
+                .all(|char| char.is_alphanumeric() || matches!(char, '-' | '_' | '.'));
+        if !valid {
+            return Err(InvalidListName(input.to_string()));
+        }
+        Ok(ListName(input.to_string()))
+    }
+}
+
+impl fmt::Display for ListName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_only_accept_single_word_names() {
+        assert_eq!("work".parse::<ListName>().unwrap().as_str(), "work");
+        assert_eq!(
+            " side-project ".parse::<ListName>().unwrap().as_str(),
+            "side-project"
+        );
+        assert!("inbox".parse::<ListName>().unwrap().is_default());
+        for input in ["", "two words", "-work", "#work"] {
+            assert!(input.parse::<ListName>().is_err(), "{input}");
+        }
+    }
+}
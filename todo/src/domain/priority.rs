@@ -0,0 +1,79 @@
+use serde::Serialize;
+use std::{fmt, str::FromStr};
+
+// Ordered from least to most pressing, so sorting in reverse puts urgent todos first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    Urgent,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct InvalidPriority(pub String);
+
+impl fmt::Display for InvalidPriority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "\"{}\" is not a priority; use low, normal, high or urgent",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for InvalidPriority {}
+
+impl FromStr for Priority {
+    type Err = InvalidPriority;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.trim().to_ascii_lowercase().as_str() {
+            "low" | "l" => Ok(Priority::Low),
+            "normal" | "n" => Ok(Priority::Normal),
+            "high" | "h" => Ok(Priority::High),
+            "urgent" | "u" => Ok(Priority::Urgent),
+            _ => Err(InvalidPriority(input.trim().to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Priority::Low => "low",
+            Priority::Normal => "normal",
+            Priority::High => "high",
+            Priority::Urgent => "urgent",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_priorities_case_insensitively() {
+        assert_eq!("HIGH".parse(), Ok(Priority::High));
+        assert_eq!("u".parse(), Ok(Priority::Urgent));
+        assert_eq!(" low ".parse(), Ok(Priority::Low));
+        assert!("soon".parse::<Priority>().is_err());
+    }
+
+    #[test]
+    fn should_round_trip_through_display() {
+        for priority in [
+            Priority::Low,
+            Priority::Normal,
+            Priority::High,
+            Priority::Urgent,
+        ] {
+            assert_eq!(priority.to_string().parse(), Ok(priority));
+        }
+    }
+}
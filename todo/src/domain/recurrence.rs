@@ -0,0 +1,195 @@
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+use serde::{Serialize, Serializer};
+use std::{fmt, str::FromStr};
+
+// How often a todo comes back once it is done.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Recurrence {
+    Daily,
+    Weekdays,
+    EveryDays(u32),
+    // Months without that day fall back to their last day.
+    Monthly(u32),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct InvalidRecurrence(pub String);
+
+impl fmt::Display for InvalidRecurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "\"{}\" is not a recurrence; try \"daily\", \"weekdays\", \"every 3 days\" or \"monthly on 15\"",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for InvalidRecurrence {}
+
+impl Recurrence {
+    // The first date the todo is due again after `date`.
+    pub fn next(&self, date: NaiveDate) -> NaiveDate {
+        match *self {
+            Recurrence::Daily => date + Days::new(1),
+            Recurrence::Weekdays => {
+                let mut next = date + Days::new(1);
+                while matches!(next.weekday(), Weekday::Sat | Weekday::Sun) {
+                    next = next + Days::new(1);
+                }
+                next
+            }
+            Recurrence::EveryDays(days) => date + Days::new(days.into()),
+            Recurrence::Monthly(day) => {
+                let first = date.with_day(1).expect("every month has a first day");
+                let this_month = day_of_month(first, day);
+                if this_month > date {
+                    this_month
+                } else {
+                    day_of_month(first + Months::new(1), day)
+                }
+            }
+        }
+    }
+
+    // The first date the todo is due again after `date` that is not before `today`, skipping
+    // the occurrences missed while it was overdue.
+    pub fn next_from(&self, date: NaiveDate, today: NaiveDate) -> NaiveDate {
+        let mut next = self.next(date);
+        while next < today {
+            next = self.next(next);
+        }
+        next
+    }
+}
+
+// `day` in the month starting at `first`, or the month's last day when it is shorter.
+fn day_of_month(first: NaiveDate, day: u32) -> NaiveDate {
+    (1..=day)
+        .rev()
+        .find_map(|day| first.with_day(day))
+        .unwrap_or(first)
+}
+
+impl FromStr for Recurrence {
+    type Err = InvalidRecurrence;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidRecurrence(input.trim().to_string());
+        let words: Vec<String> = input
+            .split_whitespace()
+            .map(str::to_ascii_lowercase)
+            .collect();
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+
+        match words.as_slice() {
+            ["daily"] | ["every", "day"] => Ok(Recurrence::Daily),
+            ["weekdays"] | ["every", "weekday"] => Ok(Recurrence::Weekdays),
+            ["every", days, "days" | "day"] => match days.parse() {
+                Ok(0) | Err(_) => Err(invalid()),
+                Ok(1) => Ok(Recurrence::Daily),
+                Ok(days) => Ok(Recurrence::EveryDays(days)),
+            },
+            ["monthly", "on", day] | ["monthly", day] => {
+                let day = day.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+                match day.parse() {
+                    Ok(day @ 1..=31) => Ok(Recurrence::Monthly(day)),
+                    _ => Err(invalid()),
+                }
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Recurrence::Daily => write!(f, "daily"),
+            Recurrence::Weekdays => write!(f, "weekdays"),
+            Recurrence::EveryDays(days) => write!(f, "every {days} days"),
+            Recurrence::Monthly(day) => write!(f, "monthly on {day}"),
+        }
+    }
+}
+
+// Written the way it is typed, like "every 3 days".
+impl Serialize for Recurrence {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(input: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(input, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn should_parse_recurrences_and_round_trip_through_display() {
+        assert_eq!("Daily".parse(), Ok(Recurrence::Daily));
+        assert_eq!("every weekday".parse(), Ok(Recurrence::Weekdays));
+        assert_eq!("every 3 days".parse(), Ok(Recurrence::EveryDays(3)));
+        assert_eq!("monthly on 15th".parse(), Ok(Recurrence::Monthly(15)));
+        assert!("every 0 days".parse::<Recurrence>().is_err());
+        assert!("monthly on 32".parse::<Recurrence>().is_err());
+        assert!("sometimes".parse::<Recurrence>().is_err());
+
+        for recurrence in [
+            Recurrence::Daily,
+            Recurrence::Weekdays,
+            Recurrence::EveryDays(10),
+            Recurrence::Monthly(31),
+        ] {
+            assert_eq!(recurrence.to_string().parse(), Ok(recurrence));
+        }
+    }
+
+    #[test]
+    fn should_find_the_next_occurrence() {
+        // A Friday.
+        let friday = date("2026-10-16");
+
+        assert_eq!(Recurrence::Daily.next(friday), date("2026-10-17"));
+        assert_eq!(Recurrence::Weekdays.next(friday), date("2026-10-19"));
+        assert_eq!(Recurrence::EveryDays(14).next(friday), date("2026-10-30"));
+        assert_eq!(Recurrence::Monthly(20).next(friday), date("2026-10-20"));
+        assert_eq!(Recurrence::Monthly(16).next(friday), date("2026-11-16"));
+        assert_eq!(
+            Recurrence::Monthly(31).next(date("2027-01-31")),
+            date("2027-02-28")
+        );
+        assert_eq!(
+            Recurrence::Monthly(31).next(date("2027-02-28")),
+            date("2027-03-31")
+        );
+    }
+
+    #[test]
+    fn should_skip_occurrences_missed_while_overdue() {
+        let due = date("2026-10-12");
+        // A Saturday.
+        let today = date("2026-10-17");
+
+        assert_eq!(Recurrence::Daily.next_from(due, today), today);
+        assert_eq!(
+            Recurrence::Weekdays.next_from(due, today),
+            date("2026-10-19")
+        );
+        assert_eq!(
+            Recurrence::EveryDays(2).next_from(due, today),
+            date("2026-10-18")
+        );
+        assert_eq!(
+            Recurrence::Monthly(12).next_from(due, today),
+            date("2026-11-12")
+        );
+        assert_eq!(
+            Recurrence::Daily.next_from(date("2026-10-20"), today),
+            date("2026-10-21")
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use serde::Serialize;
+use std::{fmt, str::FromStr};
+
+// How many direct subtasks of a todo are done, shown as "2/5".
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Progress {
+    pub done: usize,
+    pub total: usize,
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.done, self.total)
+    }
+}
+
+// What marking a todo done does while some of its subtasks are still open.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CompletePolicy {
+    #[default]
+    Block,
+    Cascade,
+}
+
+// What happens to the subtasks of a removed todo: removed with it, or handed to its parent.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum RemovePolicy {
+    #[default]
+    Cascade,
+    Reparent,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SubtaskPolicy {
+    pub complete: CompletePolicy,
+    pub remove: RemovePolicy,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct InvalidPolicy(pub String);
+
+impl fmt::Display for InvalidPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{}\" is not a known policy", self.0)
+    }
+}
+
+impl std::error::Error for InvalidPolicy {}
+
+impl FromStr for CompletePolicy {
+    type Err = InvalidPolicy;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.trim().to_ascii_lowercase().as_str() {
+            "block" => Ok(CompletePolicy::Block),
+            "cascade" => Ok(CompletePolicy::Cascade),
+            _ => Err(InvalidPolicy(input.trim().to_string())),
+        }
+    }
+}
+
+impl fmt::Display for CompletePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompletePolicy::Block => write!(f, "block"),
+            CompletePolicy::Cascade => write!(f, "cascade"),
+        }
+    }
+}
+
+impl FromStr for RemovePolicy {
+    type Err = InvalidPolicy;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.trim().to_ascii_lowercase().as_str() {
+            "cascade" => Ok(RemovePolicy::Cascade),
+            "reparent" => Ok(RemovePolicy::Reparent),
+            _ => Err(InvalidPolicy(input.trim().to_string())),
+        }
+    }
+}
+
+impl fmt::Display for RemovePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RemovePolicy::Cascade => write!(f, "cascade"),
+            RemovePolicy::Reparent => write!(f, "reparent"),
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use serde::{Serialize, Serializer};
+use std::{fmt, str::FromStr};
+
+// A lowercase label. Contexts keep their "@" (`@home`); the "#" of plain tags is dropped
+// when stored and added back when shown.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Tag(String);
+
+#[derive(Debug, PartialEq)]
+pub struct InvalidTag(pub String);
+
+impl fmt::Display for InvalidTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "\"{}\" is not a tag; tags start with a letter and only use letters, digits, \"-\", \"_\" and \"/\"",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for InvalidTag {}
+
+impl Tag {
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for Tag {
+    type Err = InvalidTag;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+        let (sigil, name) = match input.strip_prefix('@') {
+            Some(name) => ("@", name),
+            None => ("", input.strip_prefix('#').unwrap_or(input)),
+        };
+        let valid = name.starts_with(|char: char| char.is_alphabetic())
+            && name
+                .chars()
+                .all(|char| char.is_alphanumeric() || matches!(char, '-' | '_' | '/'));
+        if !valid {
+            return Err(InvalidTag(input.to_string()));
+        }
+        Ok(Tag(format!("{sigil}{}", name.to_lowercase())))
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.starts_with('@') {
+            write!(f, "{}", self.0)
+        } else {
+            write!(f, "#{}", self.0)
+        }
+    }
+}
+
+// Serialized the way it is shown, like "#backend" or "@home".
+impl Serialize for Tag {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+// The `#tag` and `@context` words of a todo message, sorted and without duplicates.
+pub fn tags_in(message: &str) -> Vec<Tag> {
+    let mut tags: Vec<Tag> = message
+        .split_whitespace()
+        .filter(|word| word.starts_with(['#', '@']))
+        .map(|word| word.trim_end_matches(|char: char| char.is_ascii_punctuation()))
+        .filter_map(|word| word.parse().ok())
+        .collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(input: &str) -> Tag {
+        input.parse().unwrap()
+    }
+
+    #[test]
+    fn should_normalize_tags() {
+        assert_eq!(tag("#Backend"), tag("backend"));
+        assert_eq!(tag("@Home").name(), "@home");
+        assert_eq!(tag("backend").to_string(), "#backend");
+        assert_eq!(tag("@home").to_string(), "@home");
+        assert!("#12".parse::<Tag>().is_err());
+        assert!("two words".parse::<Tag>().is_err());
+        assert!("#".parse::<Tag>().is_err());
+    }
+
+    #[test]
+    fn should_find_tags_in_messages() {
+        assert_eq!(
+            tags_in("Fix login #backend, then call mom @home #Backend (issue #12)"),
+            vec![tag("@home"), tag("backend")]
+        );
+        assert!(tags_in("no tags here").is_empty());
+    }
+}
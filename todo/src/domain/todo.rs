@@ -1,22 +1,106 @@
+use chrono::{DateTime, NaiveDate, SubsecRound, Utc};
+use serde::Serialize;
 use std::fmt;
 
 use uuid::Uuid;
 
-#[derive(Debug, Clone, PartialEq)]
+use crate::domain::due::Due;
+use crate::domain::priority::Priority;
+use crate::domain::recurrence::Recurrence;
+use crate::domain::tag::{tags_in, Tag};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct Todo {
     pub message: String,
     pub done: bool,
     pub id: Uuid,
+    pub priority: Priority,
+    pub due: Option<Due>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    // Set while the todo sits in the trash.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
+    // Set while the todo sits in the archive.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archived_at: Option<DateTime<Utc>>,
+    pub tags: Vec<Tag>,
+    pub parent: Option<Uuid>,
+    pub recurrence: Option<Recurrence>,
+    // The recurring todo this one was spawned from as its next occurrence.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub origin: Option<Uuid>,
+    // Ids of the todos that have to be done first, sorted.
+    pub blocked_by: Vec<Uuid>,
+}
+
+// The current time at the precision every storage keeps (Postgres stores microseconds).
+pub fn timestamp() -> DateTime<Utc> {
+    Utc::now().trunc_subsecs(6)
 }
 
 impl Todo {
     pub fn new(message: String, id: Uuid) -> Self {
+        let now = timestamp();
+        let tags = tags_in(&message);
         Todo {
             message,
             done: false,
             id,
+            priority: Priority::default(),
+            due: None,
+            created_at: now,
+            updated_at: now,
+            completed_at: None,
+            deleted_at: None,
+            archived_at: None,
+            tags,
+            parent: None,
+            recurrence: None,
+            origin: None,
+            blocked_by: Vec::new(),
         }
     }
+
+    // Keeps the tags sorted and without duplicates; removing wins over adding.
+    pub fn update_tags(&mut self, add: &[Tag], remove: &[Tag]) {
+        self.tags.extend_from_slice(add);
+        self.tags.retain(|tag| !remove.contains(tag));
+        self.tags.sort();
+        self.tags.dedup();
+    }
+
+    pub fn with_priority(self, priority: Priority) -> Self {
+        Todo { priority, ..self }
+    }
+
+    pub fn with_parent(self, parent: Uuid) -> Self {
+        Todo {
+            parent: Some(parent),
+            ..self
+        }
+    }
+
+    // The fresh copy that replaces a recurring todo once it is done, due one period after
+    // this one, or after `today` when it had no due date. An overdue todo's copy is due on the
+    // first occurrence from `today` on.
+    pub fn next_occurrence(&self, today: NaiveDate) -> Option<Todo> {
+        let recurrence = self.recurrence?;
+        let from = self.due.map_or(today, |due| due.date);
+        Some(Todo {
+            priority: self.priority,
+            due: Some(Due {
+                date: recurrence.next_from(from, today),
+                time: self.due.and_then(|due| due.time),
+            }),
+            tags: self.tags.clone(),
+            parent: self.parent,
+            recurrence: self.recurrence,
+            origin: Some(self.id),
+            ..Todo::new(self.message.clone(), Uuid::new_v4())
+        })
+    }
 }
 
 impl fmt::Display for Todo {
@@ -27,6 +111,7 @@ impl fmt::Display for Todo {
     }
 }
 
+#[cfg(test)]
 pub mod mocks {
     use crate::domain::todo::Todo;
     use crate::domain::todos::Todos;
@@ -51,7 +136,7 @@ pub mod mocks {
 
     factori::factori!(Todos, {
         default {
-            _list:Vec<Todo> = vec![],
+            _list:Vec<Todo> = Vec::new(),
             number_todos: usize = 0,
             done_todo: Option<usize> = None
         }
@@ -0,0 +1,149 @@
+use std::{fmt, str::FromStr};
+use uuid::Uuid;
+
+use crate::domain::todos::Todos;
+
+// How a user points at a todo: its 1-based position in the last displayed list, its full
+// id, or a prefix of its id, like a git short hash.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TodoRef {
+    Position(usize),
+    Id(Uuid),
+    Prefix(String),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Resolution {
+    Found(Uuid),
+    NotFound,
+    Ambiguous(usize),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct InvalidTodoRef(pub String);
+
+impl fmt::Display for InvalidTodoRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "\"{}\" is neither a position in the list nor a todo id",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for InvalidTodoRef {}
+
+impl FromStr for TodoRef {
+    type Err = InvalidTodoRef;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+        let invalid = || InvalidTodoRef(input.to_string());
+
+        if input.is_empty() {
+            return Err(invalid());
+        }
+        if input.chars().all(|char| char.is_ascii_digit()) {
+            return match input.parse::<usize>() {
+                Ok(position) if position > 0 => Ok(TodoRef::Position(position)),
+                _ => Err(invalid()),
+            };
+        }
+        if let Ok(id) = Uuid::parse_str(input) {
+            return Ok(TodoRef::Id(id));
+        }
+        if input
+            .chars()
+            .all(|char| char.is_ascii_hexdigit() || char == '-')
+        {
+            return Ok(TodoRef::Prefix(input.to_ascii_lowercase()));
+        }
+        Err(invalid())
+    }
+}
+
+impl fmt::Display for TodoRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TodoRef::Position(position) => write!(f, "{position}"),
+            TodoRef::Id(id) => write!(f, "{id}"),
+            TodoRef::Prefix(prefix) => write!(f, "{prefix}"),
+        }
+    }
+}
+
+impl TodoRef {
+    // `shown` holds the ids in the order they were last displayed; prefixes are matched
+    // against every todo in `todos`.
+    pub fn resolve(&self, shown: &[Uuid], todos: &Todos) -> Resolution {
+        match self {
+            TodoRef::Position(position) => shown
+                .get(position - 1)
+                .map_or(Resolution::NotFound, |id| Resolution::Found(*id)),
+            TodoRef::Id(id) => Resolution::Found(*id),
+            TodoRef::Prefix(prefix) => {
+                let matches: Vec<Uuid> = todos
+                    .iter()
+                    .map(|todo| todo.id)
+                    .filter(|id| id.to_string().starts_with(prefix.as_str()))
+                    .collect();
+                match matches.as_slice() {
+                    [] => Resolution::NotFound,
+                    [id] => Resolution::Found(*id),
+                    _ => Resolution::Ambiguous(matches.len()),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::todo::Todo;
+
+    fn todo_with_id(id: &str) -> Todo {
+        Todo::new("todo".to_string(), Uuid::parse_str(id).unwrap())
+    }
+
+    #[test]
+    fn should_parse_positions_ids_and_prefixes() {
+        let id = Uuid::new_v4();
+
+        assert_eq!("3".parse(), Ok(TodoRef::Position(3)));
+        assert_eq!(id.to_string().parse(), Ok(TodoRef::Id(id)));
+        assert_eq!("C13D9b".parse(), Ok(TodoRef::Prefix("c13d9b".to_string())));
+        assert!("0".parse::<TodoRef>().is_err());
+        assert!("buy milk".parse::<TodoRef>().is_err());
+    }
+
+    #[test]
+    fn should_resolve_positions_against_the_shown_list() {
+        let shown = vec![Uuid::new_v4(), Uuid::new_v4()];
+        let todos = Todos::new(vec![]);
+
+        assert_eq!(
+            TodoRef::Position(2).resolve(&shown, &todos),
+            Resolution::Found(shown[1])
+        );
+        assert_eq!(
+            TodoRef::Position(3).resolve(&shown, &todos),
+            Resolution::NotFound
+        );
+    }
+
+    #[test]
+    fn should_resolve_unique_prefixes_only() {
+        let todos = Todos::new(vec![
+            todo_with_id("c13d9b75-380c-4a5c-b1a6-ad4f02f83d7e"),
+            todo_with_id("c13e0000-380c-4a5c-b1a6-ad4f02f83d7e"),
+            todo_with_id("41f76d66-7c9b-4510-962e-73b5f43c7f70"),
+        ]);
+        let prefix = |prefix: &str| TodoRef::Prefix(prefix.to_string()).resolve(&[], &todos);
+
+        assert_eq!(prefix("c13d"), Resolution::Found(todos.get(0).unwrap().id));
+        assert_eq!(prefix("c13"), Resolution::Ambiguous(2));
+        assert_eq!(prefix("ffff"), Resolution::NotFound);
+    }
+}
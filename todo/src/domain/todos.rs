@@ -1,5 +1,8 @@
+use crate::domain::subtask::Progress;
 use crate::domain::todo::Todo;
+use std::cmp::Reverse;
 use std::iter::Iterator;
+use uuid::Uuid;
 
 #[derive(PartialEq, Clone)]
 pub struct Todos {
@@ -42,23 +45,31 @@ impl Todos {
         Todos { list }
     }
 
-    pub fn iter(&self) -> Iter {
+    pub fn iter(&self) -> Iter<'_> {
         Iter {
             todos: self,
             index: 0,
         }
     }
 
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, Todo> {
+        self.list.iter_mut()
+    }
+
     pub fn push(&mut self, todo: Todo) {
         self.list.push(todo);
     }
 
+    pub fn insert(&mut self, index: usize, todo: Todo) {
+        self.list.insert(index, todo);
+    }
+
     pub fn len(&mut self) -> usize {
         self.list.len()
     }
 
-    pub fn remove(&mut self, index: usize) {
-        self.list.remove(index);
+    pub fn remove(&mut self, index: usize) -> Todo {
+        self.list.remove(index)
     }
 
     pub fn get_mut(&mut self, index: usize) -> Option<&mut Todo> {
@@ -72,4 +83,179 @@ impl Todos {
     pub fn is_empty(&self) -> bool {
         self.list.is_empty()
     }
+
+    pub fn clear(&mut self) {
+        self.list.clear();
+    }
+
+    pub fn position(&self, id: Uuid) -> Option<usize> {
+        self.list.iter().position(|todo| todo.id == id)
+    }
+
+    // Most pressing first; todos with the same priority keep their order.
+    pub fn sort_by_priority(&mut self) {
+        self.list.sort_by_key(|todo| Reverse(todo.priority));
+    }
+
+    // Puts every subtask right below its parent, keeping the current order among siblings.
+    // Todos whose parent is not in the list are shown at the top level.
+    pub fn nest_subtasks(&mut self) {
+        let is_root = |todo: &Todo| {
+            todo.parent
+                .is_none_or(|parent| self.position(parent).is_none())
+        };
+        let mut nested = Vec::with_capacity(self.list.len());
+        let mut pending: Vec<&Todo> = self
+            .list
+            .iter()
+            .filter(|todo| is_root(todo))
+            .rev()
+            .collect();
+        while let Some(todo) = pending.pop() {
+            nested.push(todo.clone());
+            pending.extend(self.children(todo.id).rev());
+        }
+        self.list = nested;
+    }
+
+    pub fn children(&self, id: Uuid) -> impl DoubleEndedIterator<Item = &Todo> {
+        self.list.iter().filter(move |todo| todo.parent == Some(id))
+    }
+
+    // How many ancestors of the todo are in the list.
+    pub fn depth(&self, todo: &Todo) -> usize {
+        let mut depth = 0;
+        let mut parent = todo.parent;
+        while let Some(index) = parent.and_then(|parent| self.position(parent)) {
+            depth += 1;
+            parent = self.list[index].parent;
+            if depth >= self.list.len() {
+                break;
+            }
+        }
+        depth
+    }
+
+    // None when the todo has no subtasks.
+    pub fn progress(&self, id: Uuid) -> Option<Progress> {
+        let (done, total) = self.children(id).fold((0, 0), |(done, total), child| {
+            (done + child.done as usize, total + 1)
+        });
+        (total > 0).then_some(Progress { done, total })
+    }
+
+    // The blockers of the todo that are in the list and still open. Blockers on other lists,
+    // or filtered out of it, do not hold it back.
+    pub fn open_blockers(&self, todo: &Todo) -> Vec<&Todo> {
+        todo.blocked_by
+            .iter()
+            .filter_map(|id| self.position(*id).map(|index| &self.list[index]))
+            .filter(|blocker| !blocker.done)
+            .collect()
+    }
+
+    pub fn is_blocked(&self, todo: &Todo) -> bool {
+        !self.open_blockers(todo).is_empty()
+    }
+
+    // Subtasks of the todo, their subtasks and so on.
+    pub fn descendants(&self, id: Uuid) -> Vec<&Todo> {
+        let mut descendants: Vec<&Todo> = self.children(id).collect();
+        let mut index = 0;
+        while index < descendants.len() {
+            let id = descendants[index].id;
+            descendants.extend(self.children(id));
+            index += 1;
+            if descendants.len() > self.list.len() {
+                break;
+            }
+        }
+        descendants
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::priority::Priority;
+
+    #[test]
+    fn should_sort_by_priority_keeping_insertion_order() {
+        let todo = |message: &str, priority| {
+            Todo::new(message.to_string(), Uuid::new_v4()).with_priority(priority)
+        };
+        let mut todos = Todos::new(vec![
+            todo("first normal", Priority::Normal),
+            todo("low", Priority::Low),
+            todo("urgent", Priority::Urgent),
+            todo("second normal", Priority::Normal),
+            todo("high", Priority::High),
+        ]);
+
+        todos.sort_by_priority();
+
+        let messages: Vec<&str> = todos.iter().map(|todo| todo.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec!["urgent", "high", "first normal", "second normal", "low"]
+        );
+    }
+
+    #[test]
+    fn should_nest_subtasks_below_their_parents() {
+        let release = Todo::new("release".to_string(), Uuid::new_v4());
+        let tests = Todo::new("run tests".to_string(), Uuid::new_v4()).with_parent(release.id);
+        let mut flaky =
+            Todo::new("fix flaky test".to_string(), Uuid::new_v4()).with_parent(tests.id);
+        flaky.done = true;
+        let notes = Todo::new("write notes".to_string(), Uuid::new_v4()).with_parent(release.id);
+        let other = Todo::new("other".to_string(), Uuid::new_v4());
+        let mut todos = Todos::new(vec![
+            flaky.clone(),
+            release.clone(),
+            other.clone(),
+            tests.clone(),
+            notes.clone(),
+        ]);
+
+        todos.nest_subtasks();
+
+        let messages: Vec<&str> = todos.iter().map(|todo| todo.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "release",
+                "run tests",
+                "fix flaky test",
+                "write notes",
+                "other"
+            ]
+        );
+        assert_eq!(todos.depth(&flaky), 2);
+        assert_eq!(todos.depth(&other), 0);
+        assert_eq!(
+            todos.progress(release.id),
+            Some(Progress { done: 0, total: 2 })
+        );
+        assert_eq!(
+            todos.progress(tests.id),
+            Some(Progress { done: 1, total: 1 })
+        );
+        assert_eq!(todos.progress(other.id), None);
+        assert_eq!(todos.descendants(release.id).len(), 3);
+    }
+
+    #[test]
+    fn should_only_count_open_blockers_in_the_list() {
+        let mut done = Todo::new("tag release".to_string(), Uuid::new_v4());
+        done.done = true;
+        let open = Todo::new("run tests".to_string(), Uuid::new_v4());
+        let mut release = Todo::new("publish".to_string(), Uuid::new_v4());
+        release.blocked_by = vec![done.id, open.id, Uuid::new_v4()];
+        let todos = Todos::new(vec![done.clone(), open.clone(), release.clone()]);
+
+        assert_eq!(todos.open_blockers(&release), vec![&open]);
+        assert!(todos.is_blocked(&release));
+        assert!(!todos.is_blocked(&open));
+    }
 }
@@ -1,7 +1,18 @@
+use crate::terminal::error::TerminalError;
+use crate::terminal::input::Input;
+use crate::terminal::json::{JsonInterface, JsonStyle};
 use crate::terminal::Terminal;
-use controllers::todo::{TodoController, TodoControllerImpl};
-use db::connect::connect;
-use repository::todo::PostgresTodoRepository;
+use clap::Parser;
+use cli::{Cli, Command, MigrateAction, OutputFormat};
+use controllers::todo::{Outcome, TodoController, TodoControllerImpl};
+use db::config;
+use db::pool::{build_pool, PoolConfig};
+use db::reconnect::{Backoff, ReconnectingClient};
+use repository::todo::memory::InMemoryStorage;
+use repository::todo::sqlite::SqliteTodoRepository;
+use repository::todo::{PostgresTodoRepository, Storage};
+use terminal::UserInterface;
+mod cli;
 mod controllers;
 mod db;
 mod domain;
@@ -9,25 +20,152 @@ mod repository;
 mod terminal;
 extern crate factori;
 
+use std::{process, time::Duration};
+
+const EXIT_NOT_FOUND: i32 = 1;
+const EXIT_ERROR: i32 = 2;
+
 #[tokio::main]
 async fn main() {
-    let client = connect()
-        .await
-        .expect("Database connection error. Quitting");
-    let todo_repository = Box::new(PostgresTodoRepository { client });
-    let user_interface = Box::new(Terminal::new());
+    let mut cli = Cli::parse();
+
+    if let Some(Command::Migrate { action }) = &cli.command {
+        let mut user_interface = user_interface(cli.format, Input::stdin());
+        let action = action.unwrap_or(MigrateAction::Up);
+        let result = cli::migrate::run(&cli, || database_url(&cli), action)
+            .await
+            .map_err(TerminalError::from)
+            .and_then(|outcome| cli::migrate::report(user_interface.as_mut(), outcome));
+        if let Err(error) = result {
+            user_interface.print_error(&error);
+            process::exit(EXIT_ERROR);
+        }
+        return;
+    }
+
+    let todo_repository = open_storage(&cli).await;
+    let input = match &cli.script {
+        Some(path) => Input::open_script(path).unwrap_or_else(|error| {
+            eprintln!("Could not read {}: {error}", path.display());
+            process::exit(EXIT_ERROR);
+        }),
+        None => Input::stdin(),
+    };
+    let script = input.is_script();
+    let user_interface = user_interface(cli.format, input);
 
-    let mut todo_controller = Box::new(TodoControllerImpl {
-        todo_repository,
-        user_interface,
-    });
+    let mut todo_controller = Box::new(
+        TodoControllerImpl::new(todo_repository, user_interface)
+            .with_subtask_policy(cli.subtask_policy())
+            .with_archive_after(cli.archive_after),
+    );
+
+    if let Some(list) = cli.list.take() {
+        let result = match todo_controller
+            .todo_repository
+            .switch_list(list.clone())
+            .await
+        {
+            Ok(true) => Ok(Outcome::Completed),
+            Ok(false) => todo_controller
+                .user_interface
+                .report_list_not_found(&list)
+                .map(|()| Outcome::NotFound),
+            Err(error) => Err(error.into()),
+        };
+        if !matches!(result, Ok(Outcome::Completed)) {
+            process::exit(exit_code(&mut todo_controller, result));
+        }
+    }
+
+    if let Some(command) = cli.command.take() {
+        let result = match command.user_option() {
+            Ok(Some(option)) => todo_controller.execute(option).await,
+            Ok(None) => Ok(Outcome::Completed),
+            Err(error) => Err(error),
+        };
+        process::exit(exit_code(&mut todo_controller, result));
+    }
+
+    if script {
+        let result = todo_controller.run_script().await;
+        process::exit(exit_code(&mut todo_controller, result));
+    }
 
     loop {
-        if let Err(error) = todo_controller.get_user_intention().await {
+        match todo_controller.get_user_intention().await {
+            Ok(()) => break,
+            Err(error) => {
+                todo_controller.user_interface.print_error(&error);
+                if error.is_fatal() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+fn exit_code(
+    todo_controller: &mut TodoControllerImpl,
+    result: Result<Outcome, TerminalError>,
+) -> i32 {
+    match result {
+        Ok(Outcome::NotFound) => EXIT_NOT_FOUND,
+        Ok(Outcome::Unrecognized) => EXIT_ERROR,
+        Ok(_) => 0,
+        Err(error) => {
             todo_controller.user_interface.print_error(&error);
-            if error.is_fatal() {
-                break;
+            EXIT_ERROR
+        }
+    }
+}
+
+fn user_interface(format: OutputFormat, input: Input) -> Box<dyn UserInterface + Send + Sync> {
+    match format {
+        OutputFormat::Text => Box::new(Terminal::new(input)),
+        OutputFormat::Json => Box::new(JsonInterface::new(JsonStyle::Pretty, input)),
+        OutputFormat::Ndjson => Box::new(JsonInterface::new(JsonStyle::Compact, input)),
+    }
+}
+
+async fn open_storage(cli: &Cli) -> Box<dyn Storage + Send + Sync> {
+    if cli.memory {
+        return Box::new(InMemoryStorage::new());
+    }
+
+    match &cli.sqlite {
+        Some(path) => Box::new(
+            SqliteTodoRepository::open(path).expect("Could not open the SQLite database. Quitting"),
+        ),
+        None => {
+            let database_url = database_url(cli);
+            match cli.pool.pool_size {
+                Some(max_size) => {
+                    let config = PoolConfig {
+                        max_size,
+                        idle_timeout: Some(Duration::from_secs(cli.pool.pool_idle_timeout)),
+                        health_check: !cli.pool.no_pool_health_check,
+                        ..PoolConfig::default()
+                    };
+                    let client = build_pool(&database_url, &config)
+                        .await
+                        .expect("Database connection error. Quitting");
+                    Box::new(PostgresTodoRepository::new(client))
+                }
+                None => {
+                    let client = ReconnectingClient::connect(database_url, Backoff::default())
+                        .await
+                        .expect("Database connection error. Quitting");
+                    Box::new(PostgresTodoRepository::new(client))
+                }
             }
         }
     }
 }
+
+fn database_url(cli: &Cli) -> String {
+    config::database_url(cli.database_url.as_deref()).unwrap_or_else(|error| {
+        eprintln!("{error}");
+        process::exit(EXIT_ERROR);
+    })
+}
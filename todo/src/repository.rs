@@ -1,34 +1,31 @@
 pub mod todo;
 
 #[cfg(test)]
-mod test_utils {
+pub(crate) mod test_utils {
+    use crate::db::connect::SharedClient;
     use crate::domain::todo::Todo;
     use crate::repository::todo::error::StorageError;
-    use crate::repository::todo::get_todo_from_sql;
+    use crate::repository::todo::{get_todo_from_sql, SELECT_TODOS};
     use http_problem::prelude::*;
     use std::{future::Future, panic::AssertUnwindSafe, sync::Arc};
+    use tokio::sync::Mutex;
     use tokio_postgres::Client;
     use uuid::Uuid;
 
     use futures_util::FutureExt;
 
-    pub async fn connect() -> Result<Arc<Client>> {
-        let (client, connection) = tokio_postgres::connect(
-            "host=localhost user=postgres password=postgres dbname=onboarding_test",
-            tokio_postgres::NoTls,
-        )
-        .await?;
+    const DEFAULT_TEST_DATABASE_URL: &str =
+        "host=localhost user=postgres password=postgres dbname=onboarding_test";
 
-        tokio::spawn(async move {
-            if let Err(err) = connection.await {
-                println!("connection error: {err}")
-            }
-        });
-        println!("Successfully connected to database");
-        Ok(Arc::new(client))
+    pub fn database_url() -> String {
+        std::env::var("TEST_DATABASE_URL").unwrap_or_else(|_| DEFAULT_TEST_DATABASE_URL.to_string())
     }
 
-    pub async fn add_todo(client: Arc<Client>) -> Result<(), StorageError> {
+    pub async fn connect() -> Result<Client, StorageError> {
+        crate::db::connect::open(&database_url()).await
+    }
+
+    pub async fn add_todo(client: &Client) -> Result<(), StorageError> {
         let todo_uuid = Uuid::new_v4();
         client
             .execute(
@@ -36,34 +33,52 @@ mod test_utils {
                 &[&todo_uuid],
             )
             .await
-            .map_err(|error| StorageError { error })?;
+            .map_err(StorageError::Postgres)?;
         Ok(())
     }
 
-    pub async fn get_todo_by_id(
-        client: Arc<Client>,
-        id: Uuid,
-    ) -> Result<Option<Todo>, StorageError> {
-        println!("{id}");
+    pub async fn get_todo_by_id(client: &Client, id: Uuid) -> Result<Option<Todo>, StorageError> {
         let todo = client
-            .query_opt("SELECT id, message, done FROM todos WHERE id = $1;", &[&id])
+            .query_opt(&format!("{SELECT_TODOS} WHERE todos.id = $1"), &[&id])
             .await
-            .map_err(|error| StorageError { error })?
+            .map_err(StorageError::Postgres)?
             .map(get_todo_from_sql);
         Ok(todo)
     }
 
+    // Every test gets a schema of its own, dropped afterwards, so that storage code can
+    // commit its own transactions without the tests running in parallel seeing them.
     pub async fn with_client<Fn, Fut>(f: Fn)
     where
-        Fn: FnOnce(Arc<Client>) -> Fut,
+        Fn: FnOnce(SharedClient) -> Fut,
         Fut: Future<Output = ()>,
     {
-        let client = connect().await.unwrap();
-        client.execute("BEGIN;", &[]).await.expect("tx begin");
+        let admin = connect().await.unwrap();
+        let schema = format!("test_{}", Uuid::new_v4().to_simple());
+        admin
+            .execute(&format!("CREATE SCHEMA {schema}"), &[])
+            .await
+            .expect("create test schema");
 
-        let res = AssertUnwindSafe(f(client.clone())).catch_unwind().await;
+        let client = crate::db::connect::open_unmigrated(&database_url())
+            .await
+            .unwrap();
+        client
+            .execute(&format!("SET search_path TO {schema}"), &[])
+            .await
+            .expect("use test schema");
+        crate::db::migrations::postgres::migrate_up(&client)
+            .await
+            .expect("test schema migrations");
 
-        client.execute("ROLLBACK;", &[]).await.expect("tx rollback");
+        let res = AssertUnwindSafe(f(Arc::new(Mutex::new(client))))
+            .catch_unwind()
+            .await;
+
+        admin
+            .execute(&format!("DROP SCHEMA {schema} CASCADE"), &[])
+            .await
+            .expect("drop test schema");
 
         if let Err(panic) = res {
             std::panic::resume_unwind(panic);
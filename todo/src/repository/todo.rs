@@ -1,36 +1,368 @@
 use async_trait::async_trait;
-use tokio_postgres::Client;
+use chrono::{DateTime, NaiveDate, Utc};
 use uuid::Uuid;
+#[cfg(test)]
+pub(crate) mod conformance;
 pub(crate) mod error;
-use crate::domain::todo::Todo;
+pub(crate) mod memory;
+pub(crate) mod sqlite;
+use crate::db::connect::{ClientSource, SharedClient};
+use crate::domain::due::Due;
+use crate::domain::list::{ListName, TodoList};
+use crate::domain::priority::Priority;
+use crate::domain::recurrence::Recurrence;
+use crate::domain::tag::Tag;
+use crate::domain::todo::{timestamp, Todo};
 use crate::domain::todos::Todos;
 use crate::repository::todo::error::StorageError;
-use std::sync::Arc;
 use tokio_postgres::Row;
 
-pub struct PostgresTodoRepository {
-    pub client: Arc<Client>,
+pub struct PostgresTodoRepository<C = SharedClient> {
+    pub client: C,
+    list: ListName,
+}
+
+impl<C> PostgresTodoRepository<C> {
+    // Starts out on the default list.
+    pub fn new(client: C) -> Self {
+        PostgresTodoRepository {
+            client,
+            list: ListName::default(),
+        }
+    }
 }
 
 #[cfg_attr(test, mockall::automock)]
 #[async_trait]
 pub trait Storage {
+    // Every other method only sees the todos of this list.
+    fn current_list(&self) -> ListName;
+    // Returns false when there is no such list.
+    async fn switch_list(&mut self, name: ListName) -> Result<bool, StorageError>;
+    async fn get_lists(&mut self) -> Result<Vec<TodoList>, StorageError>;
+    // Returns false when the list already exists.
+    async fn create_list(&mut self, name: ListName) -> Result<bool, StorageError>;
+    async fn rename_list(&mut self, from: ListName, to: ListName) -> Result<u64, StorageError>;
+    // Deletes the list with its todos. Deleting the current list switches to the default one.
+    async fn delete_list(&mut self, name: ListName) -> Result<u64, StorageError>;
+    // Moves the todo along with its subtasks, returning how many todos moved. The todo leaves
+    // its own parent behind.
+    async fn move_todo(&mut self, todo_uuid: Uuid, list: ListName) -> Result<u64, StorageError>;
     async fn add_todo(&mut self, todo: Todo) -> Result<(), StorageError>;
     async fn get_todo_list(&mut self) -> Result<Todos, StorageError>;
+    // Clearing and removing send todos to the trash, where no other method sees them.
     async fn clear_todo_list(&mut self) -> Result<(), StorageError>;
+    // Removing a todo also removes its subtasks. Returns how many todos went to the trash,
+    // subtasks included.
     async fn remove_todo(&mut self, todo_uuid: Uuid) -> Result<u64, StorageError>;
     async fn mark_todo_done(&mut self, todo_uuid: Uuid) -> Result<u64, StorageError>;
+    async fn mark_todo_undone(&mut self, todo_uuid: Uuid) -> Result<u64, StorageError>;
+    async fn get_todo(&mut self, todo_uuid: Uuid) -> Result<Option<Todo>, StorageError>;
+    async fn update_todo(&mut self, todo_uuid: Uuid, message: String) -> Result<u64, StorageError>;
+    async fn set_priority(
+        &mut self,
+        todo_uuid: Uuid,
+        priority: Priority,
+    ) -> Result<u64, StorageError>;
+    async fn set_due(&mut self, todo_uuid: Uuid, due: Option<Due>) -> Result<u64, StorageError>;
+    async fn update_tags(
+        &mut self,
+        todo_uuid: Uuid,
+        add: Vec<Tag>,
+        remove: Vec<Tag>,
+    ) -> Result<u64, StorageError>;
+    async fn get_todos_tagged(&mut self, tag: Tag) -> Result<Todos, StorageError>;
+    async fn set_parent(
+        &mut self,
+        todo_uuid: Uuid,
+        parent: Option<Uuid>,
+    ) -> Result<u64, StorageError>;
+    async fn set_recurrence(
+        &mut self,
+        todo_uuid: Uuid,
+        recurrence: Option<Recurrence>,
+    ) -> Result<u64, StorageError>;
+    // Returns false, adding nothing, when either todo is not on the current list, or when the
+    // blocker already waits for the todo, directly or not, or is the todo itself.
+    async fn add_blocker(&mut self, todo_uuid: Uuid, blocker: Uuid) -> Result<bool, StorageError>;
+    async fn remove_blocker(&mut self, todo_uuid: Uuid, blocker: Uuid)
+        -> Result<u64, StorageError>;
+    // Puts the todo right before `before`, or last when it is None. Returns 0 when either
+    // todo is not on the current list.
+    async fn reorder_todo(
+        &mut self,
+        todo_uuid: Uuid,
+        before: Option<Uuid>,
+    ) -> Result<u64, StorageError>;
+    // The trashed todos of the current list, most recently removed first.
+    async fn get_trash(&mut self) -> Result<Todos, StorageError>;
+    // Brings back a trashed todo with the subtasks removed along with it, returning how many
+    // came back. A todo whose parent is gone from the list, trashed or moved away, comes back
+    // on its own.
+    async fn restore_todo(&mut self, todo_uuid: Uuid) -> Result<u64, StorageError>;
+    // Deletes the todos trashed before `deleted_before` for good, returning how many.
+    async fn purge_trash(&mut self, deleted_before: DateTime<Utc>) -> Result<u64, StorageError>;
+    // The archived todos of the current list, most recently archived first. Like trashed
+    // ones, no other method sees them.
+    async fn get_archive(&mut self) -> Result<Todos, StorageError>;
+    // Archives every top-level todo that is done, along with its subtasks, once all of them
+    // are done and none changed after `untouched_since`. Returns how many todos it archived.
+    async fn archive_done(&mut self, untouched_since: DateTime<Utc>) -> Result<u64, StorageError>;
+    // Brings back an archived todo with the subtasks archived along with it, returning how
+    // many came back; a subtask comes back on its own. Its `updated_at` is bumped so it is not
+    // archived again right away.
+    async fn unarchive_todo(&mut self, todo_uuid: Uuid) -> Result<u64, StorageError>;
+}
+
+// Space between the positions of neighbouring todos once they are spread out.
+pub(crate) const POSITION_GAP: f64 = 1024.0;
+
+// A position between two neighbours, or None when they are too close to fit one in.
+fn position_between(previous: Option<f64>, next: Option<f64>) -> Option<f64> {
+    match (previous, next) {
+        (None, None) => Some(POSITION_GAP),
+        (Some(previous), None) => Some(previous + POSITION_GAP),
+        (None, Some(next)) => Some(next - POSITION_GAP),
+        (Some(previous), Some(next)) => {
+            let middle = previous + (next - previous) / 2.0;
+            (previous < middle && middle < next).then_some(middle)
+        }
+    }
+}
+
+// The new positions after moving `todo_uuid` right before `before` in `order`, the ids and
+// positions of a list in display order. Usually only the moved todo changes; when its new
+// neighbours are too close, the whole list is spread out again.
+pub(crate) fn reposition(
+    mut order: Vec<(Uuid, f64)>,
+    todo_uuid: Uuid,
+    before: Option<Uuid>,
+) -> Option<Vec<(Uuid, f64)>> {
+    let index = order.iter().position(|(id, _)| *id == todo_uuid)?;
+    order.remove(index);
+    let index = match before {
+        Some(before) => order.iter().position(|(id, _)| *id == before)?,
+        None => order.len(),
+    };
+    let previous = index.checked_sub(1).map(|index| order[index].1);
+    let next = order.get(index).map(|(_, position)| *position);
+    if let Some(position) = position_between(previous, next) {
+        return Some(vec![(todo_uuid, position)]);
+    }
+    order.insert(index, (todo_uuid, 0.0));
+    Some(
+        order
+            .into_iter()
+            .enumerate()
+            .map(|(index, (id, _))| (id, (index + 1) as f64 * POSITION_GAP))
+            .collect(),
+    )
+}
+
+// Every todo column plus its sorted tag names and the ids of blockers not in the trash.
+pub(crate) const SELECT_TODOS: &str = "SELECT todos.*, \
+     ARRAY(SELECT tags.name FROM tags JOIN todo_tags ON todo_tags.tag_id = tags.id \
+           WHERE todo_tags.todo_id = todos.id ORDER BY tags.name) AS tags, \
+     ARRAY(SELECT blocker_id FROM todo_dependencies \
+           JOIN todos AS blocker ON blocker.id = todo_dependencies.blocker_id \
+           WHERE todo_dependencies.todo_id = todos.id AND blocker.deleted_at IS NULL \
+           AND blocker.archived_at IS NULL \
+           ORDER BY blocker_id) AS blocked_by \
+     FROM todos";
+
+const ORDER_TODOS: &str = "ORDER BY todos.position, todos.created_at, todos.id";
+
+// Makes todo $1 wait for $2 when both are on list $3 and $2 does not already wait for $1,
+// following the chain of blockers, returning how many todos it touched.
+const ADD_BLOCKER: &str = "WITH RECURSIVE chain(id) AS ( \
+         SELECT $2::uuid \
+         UNION SELECT todo_dependencies.blocker_id FROM todo_dependencies \
+         JOIN chain ON todo_dependencies.todo_id = chain.id \
+     ), todo AS ( \
+         UPDATE todos SET updated_at=$4 WHERE id=$1 AND list=$3 AND deleted_at IS NULL \
+         AND archived_at IS NULL \
+         AND EXISTS (SELECT 1 FROM todos AS blocker WHERE blocker.id=$2 AND blocker.list=$3 \
+             AND blocker.deleted_at IS NULL AND blocker.archived_at IS NULL) \
+         AND NOT EXISTS (SELECT 1 FROM chain WHERE chain.id=$1) \
+         RETURNING id \
+     ), added AS ( \
+         INSERT INTO todo_dependencies(todo_id, blocker_id) SELECT id, $2 FROM todo \
+         ON CONFLICT DO NOTHING \
+     ) \
+     SELECT count(*) FROM todo";
+
+// Held by the transaction running ADD_BLOCKER. A statement only sees the dependencies
+// committed before it started, so two sessions could otherwise each add one half of a cycle.
+const DEPENDENCIES_LOCK: i64 = 0x746f_646f_5f64_6570;
+
+// Inserts the tag names of parameter `$names` that do not exist yet, once for every row of a
+// `todo` query, yielding the ids of new and existing tags alike. The no-op update makes
+// existing tags show up in RETURNING too.
+fn upsert_tags(names: usize) -> String {
+    format!(
+        "INSERT INTO tags(name) SELECT DISTINCT unnest(${names}::text[]) FROM todo \
+         ON CONFLICT (name) DO UPDATE SET name = EXCLUDED.name RETURNING id"
+    )
 }
 
 #[async_trait]
-impl Storage for PostgresTodoRepository {
+impl<C> Storage for PostgresTodoRepository<C>
+where
+    C: ClientSource + Send + Sync,
+{
+    fn current_list(&self) -> ListName {
+        self.list.clone()
+    }
+    async fn switch_list(&mut self, name: ListName) -> Result<bool, StorageError> {
+        let exists = self
+            .client
+            .get()
+            .await?
+            .query_opt("SELECT 1 FROM lists WHERE name=$1", &[&name.as_str()])
+            .await?
+            .is_some();
+        if exists {
+            self.list = name;
+        }
+        Ok(exists)
+    }
+    async fn get_lists(&mut self) -> Result<Vec<TodoList>, StorageError> {
+        let lists = self
+            .client
+            .get()
+            .await?
+            .query(
+                "SELECT lists.name, \
+                     count(todos.id) FILTER (WHERE NOT todos.done) AS open, \
+                     count(todos.id) FILTER (WHERE todos.done) AS done \
+                 FROM lists LEFT JOIN todos \
+                     ON todos.list = lists.name AND todos.deleted_at IS NULL \
+                     AND todos.archived_at IS NULL \
+                 GROUP BY lists.name ORDER BY lists.name",
+                &[],
+            )
+            .await?
+            .into_iter()
+            .map(|row| TodoList {
+                // Names were validated when the list was created.
+                name: row.get::<_, &str>("name").parse().unwrap_or_default(),
+                open: row.get::<_, i64>("open") as u64,
+                done: row.get::<_, i64>("done") as u64,
+            })
+            .collect();
+        Ok(lists)
+    }
+    async fn create_list(&mut self, name: ListName) -> Result<bool, StorageError> {
+        let created = self
+            .client
+            .get()
+            .await?
+            .execute(
+                "INSERT INTO lists(name) VALUES($1) ON CONFLICT DO NOTHING",
+                &[&name.as_str()],
+            )
+            .await?;
+        Ok(created == 1)
+    }
+    async fn rename_list(&mut self, from: ListName, to: ListName) -> Result<u64, StorageError> {
+        let number_modified = self
+            .client
+            .get()
+            .await?
+            .execute(
+                "UPDATE lists SET name=$1 WHERE name=$2",
+                &[&to.as_str(), &from.as_str()],
+            )
+            .await?;
+        if number_modified > 0 && self.list == from {
+            self.list = to;
+        }
+        Ok(number_modified)
+    }
+    async fn delete_list(&mut self, name: ListName) -> Result<u64, StorageError> {
+        let number_modified = self
+            .client
+            .get()
+            .await?
+            .execute("DELETE FROM lists WHERE name=$1", &[&name.as_str()])
+            .await?;
+        if number_modified > 0 && self.list == name {
+            self.list = ListName::default();
+        }
+        Ok(number_modified)
+    }
+    async fn move_todo(&mut self, todo_uuid: Uuid, list: ListName) -> Result<u64, StorageError> {
+        let number_modified = self
+            .client
+            .get()
+            .await?
+            .execute(
+                "WITH RECURSIVE subtree(id) AS ( \
+                     SELECT id FROM todos WHERE id=$3 AND list=$4 AND deleted_at IS NULL \
+                     AND archived_at IS NULL \
+                     UNION SELECT todos.id FROM todos JOIN subtree ON todos.parent_id = subtree.id \
+                     WHERE todos.deleted_at IS NULL AND todos.archived_at IS NULL \
+                 ) \
+                 UPDATE todos SET list=$1, updated_at=$2, \
+                     parent_id = CASE WHEN id=$3 THEN NULL ELSE parent_id END, \
+                     position = position + $5 \
+                         + (SELECT COALESCE(max(position), 0) FROM todos WHERE list=$1) \
+                         - (SELECT min(position) FROM todos WHERE id IN (SELECT id FROM subtree)) \
+                 WHERE id IN (SELECT id FROM subtree) AND EXISTS (SELECT 1 FROM lists WHERE name=$1)",
+                &[
+                    &list.as_str(),
+                    &timestamp(),
+                    &todo_uuid,
+                    &self.list.as_str(),
+                    &POSITION_GAP,
+                ],
+            )
+            .await?;
+        Ok(number_modified)
+    }
     async fn add_todo(&mut self, todo: Todo) -> Result<(), StorageError> {
         let message = todo.message;
         let todo_uuid = todo.id;
+        let priority = todo.priority.to_string();
+        let due_date = todo.due.map(|due| due.date);
+        let due_time = todo.due.and_then(|due| due.time);
+        let recurrence = todo.recurrence.map(|recurrence| recurrence.to_string());
+        let tags: Vec<&str> = todo.tags.iter().map(Tag::name).collect();
+        // One statement, so the todo never shows up without its tags or blockers.
         self.client
+            .get()
+            .await?
             .execute(
-                "INSERT INTO todos(message, id) VALUES($1, $2)",
-                &[&message, &todo_uuid],
+                &format!(
+                    "WITH todo AS ( \
+                         INSERT INTO todos(message, id, priority, due_date, due_time, created_at, updated_at, completed_at, list, parent_id, recurrence, position, origin_id) \
+                         VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, \
+                             (SELECT COALESCE(max(position), 0) + $12 FROM todos WHERE list=$9), $15) \
+                         RETURNING id \
+                     ), tag AS ({}), tagged AS ( \
+                         INSERT INTO todo_tags(todo_id, tag_id) SELECT todo.id, tag.id FROM todo, tag \
+                     ) \
+                     INSERT INTO todo_dependencies(todo_id, blocker_id) \
+                     SELECT todo.id, unnest($14::uuid[]) FROM todo",
+                    upsert_tags(13)
+                ),
+                &[
+                    &message,
+                    &todo_uuid,
+                    &priority,
+                    &due_date,
+                    &due_time,
+                    &todo.created_at,
+                    &todo.updated_at,
+                    &todo.completed_at,
+                    &self.list.as_str(),
+                    &todo.parent,
+                    &recurrence,
+                    &POSITION_GAP,
+                    &tags,
+                    &todo.blocked_by,
+                    &todo.origin,
+                ],
             )
             .await?;
         Ok(())
@@ -38,7 +370,15 @@ impl Storage for PostgresTodoRepository {
     async fn get_todo_list(&mut self) -> Result<Todos, StorageError> {
         let todos = self
             .client
-            .query("SELECT * FROM todos;", &[])
+            .get()
+            .await?
+            .query(
+                &format!(
+                    "{SELECT_TODOS} WHERE todos.list=$1 AND todos.deleted_at IS NULL \
+                     AND todos.archived_at IS NULL {ORDER_TODOS}"
+                ),
+                &[&self.list.as_str()],
+            )
             .await?
             .into_iter()
             .map(get_todo_from_sql)
@@ -47,38 +387,514 @@ impl Storage for PostgresTodoRepository {
         Ok(Todos::new(todos))
     }
     async fn clear_todo_list(&mut self) -> Result<(), StorageError> {
-        self.client.execute("DELETE FROM todos", &[]).await?;
+        self.client
+            .get()
+            .await?
+            .execute(
+                "UPDATE todos SET deleted_at=$1 WHERE list=$2 AND deleted_at IS NULL \
+                 AND archived_at IS NULL",
+                &[&timestamp(), &self.list.as_str()],
+            )
+            .await?;
         Ok(())
     }
     async fn remove_todo(&mut self, todo_uuid: Uuid) -> Result<u64, StorageError> {
         let number_modified = self
             .client
-            .execute("DELETE FROM todos WHERE id=$1", &[&todo_uuid])
+            .get()
+            .await?
+            .execute(
+                "WITH RECURSIVE subtree(id) AS ( \
+                     SELECT id FROM todos WHERE id=$2 AND list=$3 AND deleted_at IS NULL \
+                     AND archived_at IS NULL \
+                     UNION SELECT todos.id FROM todos JOIN subtree ON todos.parent_id = subtree.id \
+                     WHERE todos.deleted_at IS NULL AND todos.archived_at IS NULL \
+                 ) \
+                 UPDATE todos SET deleted_at=$1 WHERE id IN (SELECT id FROM subtree)",
+                &[&timestamp(), &todo_uuid, &self.list.as_str()],
+            )
             .await?;
         Ok(number_modified)
     }
     async fn mark_todo_done(&mut self, todo_uuid: Uuid) -> Result<u64, StorageError> {
         let number_modified = self
             .client
-            .execute("UPDATE todos SET done='t' WHERE id=$1", &[&todo_uuid])
+            .get()
+            .await?
+            .execute(
+                "UPDATE todos SET done='t', completed_at=COALESCE(completed_at, $1), updated_at=$1 \
+                 WHERE id=$2 AND list=$3 AND deleted_at IS NULL AND archived_at IS NULL",
+                &[&timestamp(), &todo_uuid, &self.list.as_str()],
+            )
+            .await?;
+        Ok(number_modified)
+    }
+    async fn mark_todo_undone(&mut self, todo_uuid: Uuid) -> Result<u64, StorageError> {
+        let number_modified = self
+            .client
+            .get()
+            .await?
+            .execute(
+                "UPDATE todos SET done='f', completed_at=NULL, updated_at=$1 \
+                 WHERE id=$2 AND list=$3 AND deleted_at IS NULL AND archived_at IS NULL",
+                &[&timestamp(), &todo_uuid, &self.list.as_str()],
+            )
+            .await?;
+        Ok(number_modified)
+    }
+    async fn get_todo(&mut self, todo_uuid: Uuid) -> Result<Option<Todo>, StorageError> {
+        let todo = self
+            .client
+            .get()
+            .await?
+            .query_opt(
+                &format!(
+                    "{SELECT_TODOS} \
+                     WHERE todos.id=$1 AND todos.list=$2 AND todos.deleted_at IS NULL \
+                     AND todos.archived_at IS NULL"
+                ),
+                &[&todo_uuid, &self.list.as_str()],
+            )
+            .await?
+            .map(get_todo_from_sql);
+        Ok(todo)
+    }
+    async fn update_todo(&mut self, todo_uuid: Uuid, message: String) -> Result<u64, StorageError> {
+        let number_modified = self
+            .client
+            .get()
+            .await?
+            .execute(
+                "UPDATE todos SET message=$1, updated_at=$2 \
+                 WHERE id=$3 AND list=$4 AND deleted_at IS NULL AND archived_at IS NULL",
+                &[&message, &timestamp(), &todo_uuid, &self.list.as_str()],
+            )
+            .await?;
+        Ok(number_modified)
+    }
+    async fn set_priority(
+        &mut self,
+        todo_uuid: Uuid,
+        priority: Priority,
+    ) -> Result<u64, StorageError> {
+        let number_modified = self
+            .client
+            .get()
+            .await?
+            .execute(
+                "UPDATE todos SET priority=$1, updated_at=$2 \
+                 WHERE id=$3 AND list=$4 AND deleted_at IS NULL AND archived_at IS NULL",
+                &[
+                    &priority.to_string(),
+                    &timestamp(),
+                    &todo_uuid,
+                    &self.list.as_str(),
+                ],
+            )
+            .await?;
+        Ok(number_modified)
+    }
+    async fn set_due(&mut self, todo_uuid: Uuid, due: Option<Due>) -> Result<u64, StorageError> {
+        let due_date = due.map(|due| due.date);
+        let due_time = due.and_then(|due| due.time);
+        let number_modified = self
+            .client
+            .get()
+            .await?
+            .execute(
+                "UPDATE todos SET due_date=$1, due_time=$2, updated_at=$3 \
+                 WHERE id=$4 AND list=$5 AND deleted_at IS NULL AND archived_at IS NULL",
+                &[
+                    &due_date,
+                    &due_time,
+                    &timestamp(),
+                    &todo_uuid,
+                    &self.list.as_str(),
+                ],
+            )
+            .await?;
+        Ok(number_modified)
+    }
+    async fn update_tags(
+        &mut self,
+        todo_uuid: Uuid,
+        add: Vec<Tag>,
+        remove: Vec<Tag>,
+    ) -> Result<u64, StorageError> {
+        let add: Vec<&str> = add.iter().map(Tag::name).collect();
+        let remove: Vec<&str> = remove.iter().map(Tag::name).collect();
+        // Tags are only touched when the todo is on the list, all in one statement.
+        let row = self
+            .client
+            .get()
+            .await?
+            .query_one(
+                &format!(
+                    "WITH todo AS ( \
+                         UPDATE todos SET updated_at=$1 WHERE id=$2 AND list=$3 \
+                         AND deleted_at IS NULL AND archived_at IS NULL RETURNING id \
+                     ), tag AS ({}), added AS ( \
+                         INSERT INTO todo_tags(todo_id, tag_id) SELECT todo.id, tag.id FROM todo, tag \
+                         ON CONFLICT DO NOTHING \
+                     ), removed AS ( \
+                         DELETE FROM todo_tags USING tags, todo \
+                         WHERE todo_tags.tag_id = tags.id AND todo_tags.todo_id = todo.id \
+                         AND tags.name = ANY($5) \
+                     ) \
+                     SELECT count(*) FROM todo",
+                    upsert_tags(4)
+                ),
+                &[&timestamp(), &todo_uuid, &self.list.as_str(), &add, &remove],
+            )
+            .await?;
+        Ok(row.get::<_, i64>(0) as u64)
+    }
+    async fn get_todos_tagged(&mut self, tag: Tag) -> Result<Todos, StorageError> {
+        let todos = self
+            .client
+            .get()
+            .await?
+            .query(
+                &format!(
+                    "{SELECT_TODOS} \
+                     WHERE todos.list = $2 AND todos.deleted_at IS NULL \
+                     AND todos.archived_at IS NULL AND EXISTS ( \
+                         SELECT 1 FROM todo_tags JOIN tags ON tags.id = todo_tags.tag_id \
+                         WHERE todo_tags.todo_id = todos.id AND tags.name = $1) {ORDER_TODOS}"
+                ),
+                &[&tag.name(), &self.list.as_str()],
+            )
+            .await?
+            .into_iter()
+            .map(get_todo_from_sql)
+            .collect();
+
+        Ok(Todos::new(todos))
+    }
+    async fn set_parent(
+        &mut self,
+        todo_uuid: Uuid,
+        parent: Option<Uuid>,
+    ) -> Result<u64, StorageError> {
+        let number_modified = self
+            .client
+            .get()
+            .await?
+            .execute(
+                "UPDATE todos SET parent_id=$1, updated_at=$2 \
+                 WHERE id=$3 AND list=$4 AND deleted_at IS NULL AND archived_at IS NULL",
+                &[&parent, &timestamp(), &todo_uuid, &self.list.as_str()],
+            )
+            .await?;
+        Ok(number_modified)
+    }
+    async fn set_recurrence(
+        &mut self,
+        todo_uuid: Uuid,
+        recurrence: Option<Recurrence>,
+    ) -> Result<u64, StorageError> {
+        let recurrence = recurrence.map(|recurrence| recurrence.to_string());
+        let number_modified = self
+            .client
+            .get()
+            .await?
+            .execute(
+                "UPDATE todos SET recurrence=$1, updated_at=$2 \
+                 WHERE id=$3 AND list=$4 AND deleted_at IS NULL AND archived_at IS NULL",
+                &[&recurrence, &timestamp(), &todo_uuid, &self.list.as_str()],
+            )
+            .await?;
+        Ok(number_modified)
+    }
+    async fn add_blocker(&mut self, todo_uuid: Uuid, blocker: Uuid) -> Result<bool, StorageError> {
+        let mut client = self.client.get().await?;
+        // Rolled back, and the lock released with it, if anything below fails or is cancelled.
+        let transaction = client.transaction().await?;
+        transaction
+            .execute("SELECT pg_advisory_xact_lock($1)", &[&DEPENDENCIES_LOCK])
+            .await?;
+        let added: i64 = transaction
+            .query_one(
+                ADD_BLOCKER,
+                &[&todo_uuid, &blocker, &self.list.as_str(), &timestamp()],
+            )
+            .await?
+            .get(0);
+        transaction.commit().await?;
+        Ok(added == 1)
+    }
+    async fn remove_blocker(
+        &mut self,
+        todo_uuid: Uuid,
+        blocker: Uuid,
+    ) -> Result<u64, StorageError> {
+        let row = self
+            .client
+            .get()
+            .await?
+            .query_one(
+                "WITH removed AS ( \
+                     DELETE FROM todo_dependencies USING todos \
+                     WHERE todo_dependencies.todo_id = todos.id \
+                     AND todo_id = $1 AND blocker_id = $2 AND todos.list = $3 \
+                     AND todos.deleted_at IS NULL AND todos.archived_at IS NULL \
+                     RETURNING todo_id \
+                 ), touched AS ( \
+                     UPDATE todos SET updated_at=$4 WHERE id IN (SELECT todo_id FROM removed) \
+                 ) \
+                 SELECT count(*) FROM removed",
+                &[&todo_uuid, &blocker, &self.list.as_str(), &timestamp()],
+            )
+            .await?;
+        Ok(row.get::<_, i64>(0) as u64)
+    }
+    async fn reorder_todo(
+        &mut self,
+        todo_uuid: Uuid,
+        before: Option<Uuid>,
+    ) -> Result<u64, StorageError> {
+        let client = self.client.get().await?;
+        let order = client
+            .query(
+                &format!(
+                    "SELECT id, position FROM todos WHERE list=$1 AND deleted_at IS NULL \
+                     AND archived_at IS NULL \
+                     {ORDER_TODOS}"
+                ),
+                &[&self.list.as_str()],
+            )
+            .await?
+            .into_iter()
+            .map(|row| (row.get("id"), row.get("position")))
+            .collect();
+        let Some(positions) = reposition(order, todo_uuid, before) else {
+            return Ok(0);
+        };
+        let (ids, positions): (Vec<Uuid>, Vec<f64>) = positions.into_iter().unzip();
+        // A respread moves every todo at once.
+        let row = client
+            .query_one(
+                "WITH moved AS ( \
+                     UPDATE todos SET position = new.position \
+                     FROM unnest($1::uuid[], $2::float8[]) AS new(id, position) \
+                     WHERE todos.id = new.id AND todos.list = $3 \
+                     AND todos.deleted_at IS NULL AND todos.archived_at IS NULL \
+                     RETURNING todos.id \
+                 ) \
+                 SELECT count(*) FROM moved WHERE id = $4",
+                &[&ids, &positions, &self.list.as_str(), &todo_uuid],
+            )
+            .await?;
+        Ok(row.get::<_, i64>(0) as u64)
+    }
+    async fn get_trash(&mut self) -> Result<Todos, StorageError> {
+        let todos = self
+            .client
+            .get()
+            .await?
+            .query(
+                &format!(
+                    "{SELECT_TODOS} WHERE todos.list=$1 AND todos.deleted_at IS NOT NULL \
+                     ORDER BY todos.deleted_at DESC, todos.position, todos.id"
+                ),
+                &[&self.list.as_str()],
+            )
+            .await?
+            .into_iter()
+            .map(get_todo_from_sql)
+            .collect();
+
+        Ok(Todos::new(todos))
+    }
+    async fn restore_todo(&mut self, todo_uuid: Uuid) -> Result<u64, StorageError> {
+        // Subtasks removed at the same moment came along with the todo.
+        let number_modified = self
+            .client
+            .get()
+            .await?
+            .execute(
+                "WITH RECURSIVE trashed AS ( \
+                     SELECT id, deleted_at FROM todos \
+                     WHERE id=$1 AND list=$2 AND deleted_at IS NOT NULL \
+                 ), subtree(id) AS ( \
+                     SELECT id FROM trashed \
+                     UNION SELECT todos.id FROM todos JOIN subtree ON todos.parent_id = subtree.id \
+                     WHERE todos.deleted_at = (SELECT deleted_at FROM trashed) \
+                 ) \
+                 UPDATE todos SET deleted_at=NULL, \
+                     parent_id = CASE WHEN id=$1 AND parent_id NOT IN ( \
+                         SELECT id FROM todos WHERE list=$2 AND deleted_at IS NULL \
+                         AND archived_at IS NULL \
+                     ) THEN NULL ELSE parent_id END \
+                 WHERE id IN (SELECT id FROM subtree)",
+                &[&todo_uuid, &self.list.as_str()],
+            )
+            .await?;
+        Ok(number_modified)
+    }
+    async fn purge_trash(&mut self, deleted_before: DateTime<Utc>) -> Result<u64, StorageError> {
+        // Counted up front, since subtasks deleted along with their parent are not.
+        let purged: i64 = self
+            .client
+            .get()
+            .await?
+            .query_one(
+                "WITH purged AS ( \
+                     SELECT id FROM todos WHERE list=$1 AND deleted_at <= $2 \
+                 ), deleted AS ( \
+                     DELETE FROM todos WHERE id IN (SELECT id FROM purged) \
+                 ) \
+                 SELECT count(*) FROM purged",
+                &[&self.list.as_str(), &deleted_before],
+            )
+            .await?
+            .get(0);
+        Ok(purged as u64)
+    }
+    async fn get_archive(&mut self) -> Result<Todos, StorageError> {
+        let todos = self
+            .client
+            .get()
+            .await?
+            .query(
+                &format!(
+                    "{SELECT_TODOS} WHERE todos.list=$1 AND todos.archived_at IS NOT NULL \
+                     ORDER BY todos.archived_at DESC, todos.position, todos.id"
+                ),
+                &[&self.list.as_str()],
+            )
+            .await?
+            .into_iter()
+            .map(get_todo_from_sql)
+            .collect();
+
+        Ok(Todos::new(todos))
+    }
+    async fn archive_done(&mut self, untouched_since: DateTime<Utc>) -> Result<u64, StorageError> {
+        let number_modified = self
+            .client
+            .get()
+            .await?
+            .execute(
+                "WITH RECURSIVE tree(root, id) AS ( \
+                     SELECT id, id FROM todos WHERE list=$1 AND parent_id IS NULL \
+                     AND deleted_at IS NULL AND archived_at IS NULL \
+                     UNION SELECT tree.root, todos.id FROM todos JOIN tree ON todos.parent_id = tree.id \
+                     WHERE todos.deleted_at IS NULL AND todos.archived_at IS NULL \
+                 ), finished(root) AS ( \
+                     SELECT tree.root FROM tree JOIN todos ON todos.id = tree.id GROUP BY tree.root \
+                     HAVING bool_and(todos.done AND todos.updated_at <= $2) \
+                 ) \
+                 UPDATE todos SET archived_at=$3 \
+                 WHERE id IN (SELECT id FROM tree WHERE root IN (SELECT root FROM finished))",
+                &[&self.list.as_str(), &untouched_since, &timestamp()],
+            )
+            .await?;
+        Ok(number_modified)
+    }
+    async fn unarchive_todo(&mut self, todo_uuid: Uuid) -> Result<u64, StorageError> {
+        // Subtasks archived at the same moment came along with the todo.
+        let number_modified = self
+            .client
+            .get()
+            .await?
+            .execute(
+                "WITH RECURSIVE archived AS ( \
+                     SELECT id, archived_at FROM todos \
+                     WHERE id=$1 AND list=$2 AND archived_at IS NOT NULL \
+                 ), subtree(id) AS ( \
+                     SELECT id FROM archived \
+                     UNION SELECT todos.id FROM todos JOIN subtree ON todos.parent_id = subtree.id \
+                     WHERE todos.archived_at = (SELECT archived_at FROM archived) \
+                 ) \
+                 UPDATE todos SET archived_at=NULL, updated_at=$3, \
+                     parent_id = CASE WHEN id=$1 AND parent_id NOT IN ( \
+                         SELECT id FROM todos WHERE list=$2 AND deleted_at IS NULL \
+                         AND archived_at IS NULL \
+                     ) THEN NULL ELSE parent_id END \
+                 WHERE id IN (SELECT id FROM subtree)",
+                &[&todo_uuid, &self.list.as_str(), &timestamp()],
+            )
             .await?;
         Ok(number_modified)
     }
 }
 
+pub fn get_todo_from_sql(row: Row) -> Todo {
+    Todo {
+        done: row.get("done"),
+        message: row.get("message"),
+        id: row.get("id"),
+        // The column's CHECK constraint only allows known priorities.
+        priority: row.get::<_, &str>("priority").parse().unwrap_or_default(),
+        due: row.get::<_, Option<NaiveDate>>("due_date").map(|date| Due {
+            date,
+            time: row.get("due_time"),
+        }),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+        completed_at: row.get("completed_at"),
+        deleted_at: row.get("deleted_at"),
+        archived_at: row.get("archived_at"),
+        tags: row
+            .get::<_, Vec<&str>>("tags")
+            .into_iter()
+            .filter_map(|name| name.parse().ok())
+            .collect(),
+        parent: row.get("parent_id"),
+        origin: row.get("origin_id"),
+        blocked_by: row.get("blocked_by"),
+        recurrence: row
+            .get::<_, Option<&str>>("recurrence")
+            .and_then(|recurrence| recurrence.parse().ok()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::domain::todo::mocks::*;
     use crate::repository::test_utils;
+    use crate::repository::todo::conformance::{storage_conformance_tests, BoxedStorage};
     use factori::create;
+    use std::future::Future;
+
+    async fn with_storage<F, Fut>(check: F)
+    where
+        F: FnOnce(BoxedStorage) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        test_utils::with_client(|client| async move {
+            check(Box::new(PostgresTodoRepository::new(client))).await;
+        })
+        .await;
+    }
+
+    storage_conformance_tests!(with_storage);
+
+    #[test]
+    fn should_only_move_one_todo_unless_its_neighbours_are_too_close() {
+        let [a, b, c] = [Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4()];
+
+        let order = vec![(a, 1024.0), (b, 2048.0), (c, 3072.0)];
+        assert_eq!(reposition(order, c, Some(b)), Some(vec![(c, 1536.0)]));
+
+        let order = vec![(a, 1.0), (b, 1.0 + f64::EPSILON), (c, 3072.0)];
+        assert_eq!(
+            reposition(order, c, Some(b)),
+            Some(vec![(a, 1024.0), (c, 2048.0), (b, 3072.0)])
+        );
+        assert_eq!(reposition(vec![(a, 1.0)], b, None), None);
+    }
 
     #[tokio::test]
     async fn test_list_todos() {
         test_utils::with_client(|client| async move {
-            self::test_utils::add_todo(client.clone()).await.unwrap();
+            self::test_utils::add_todo(&*client.lock().await)
+                .await
+                .unwrap();
 
-            let mut todo_storage = PostgresTodoRepository { client: client };
+            let mut todo_storage = PostgresTodoRepository::new(client);
             let mut todo_list = todo_storage.get_todo_list().await.unwrap();
 
             assert_eq!(todo_list.len(), 1);
@@ -91,11 +907,11 @@ mod tests {
         test_utils::with_client(|client| async move {
             let id = Uuid::new_v4();
             let todo = Todo::new("Test add todo".to_string(), id);
-            let mut todo_storage = PostgresTodoRepository {
-                client: client.clone(),
-            };
+            let mut todo_storage = PostgresTodoRepository::new(client.clone());
             todo_storage.add_todo(todo.clone()).await.unwrap();
-            let todo_created = self::test_utils::get_todo_by_id(client, id).await.unwrap();
+            let todo_created = self::test_utils::get_todo_by_id(&*client.lock().await, id)
+                .await
+                .unwrap();
             match todo_created {
                 Some(todo_returned) => assert_eq!(todo_returned, todo),
                 None => panic!("Could not find todo created"),
@@ -109,9 +925,7 @@ mod tests {
         test_utils::with_client(|client| async move {
             let todo_list = create!(Todos, number_todos: 3);
             let todo = todo_list.get(2).unwrap();
-            let mut todo_storage = PostgresTodoRepository {
-                client: client.clone(),
-            };
+            let mut todo_storage = PostgresTodoRepository::new(client.clone());
 
             for todo in todo_list.iter() {
                 let t = todo.clone();
@@ -128,9 +942,7 @@ mod tests {
     #[tokio::test]
     async fn test_clear_list() {
         test_utils::with_client(|client| async move {
-            let mut todo_storage = PostgresTodoRepository {
-                client: client.clone(),
-            };
+            let mut todo_storage = PostgresTodoRepository::new(client.clone());
 
             todo_storage.clear_todo_list().await.unwrap();
             let mut todo_list = todo_storage.get_todo_list().await.unwrap();
@@ -140,11 +952,3 @@ mod tests {
         .await;
     }
 }
-
-pub fn get_todo_from_sql(row: Row) -> Todo {
-    Todo {
-        done: row.get("done"),
-        message: row.get("message"),
-        id: row.get("id"),
-    }
-}
@@ -0,0 +1,862 @@
+use uuid::Uuid;
+
+use chrono::{NaiveDate, NaiveTime};
+
+use crate::domain::due::Due;
+use crate::domain::list::{ListName, TodoList};
+use crate::domain::priority::Priority;
+use crate::domain::recurrence::Recurrence;
+use crate::domain::tag::Tag;
+use crate::domain::todo::{timestamp, Todo};
+use crate::domain::todos::Todos;
+use crate::repository::todo::Storage;
+
+pub type BoxedStorage = Box<dyn Storage + Send + Sync>;
+
+fn ids(todo_list: &Todos) -> Vec<Uuid> {
+    todo_list.iter().map(|todo| todo.id).collect()
+}
+
+async fn add_todos(storage: &mut BoxedStorage, count: usize) -> Vec<Todo> {
+    let mut added = Vec::with_capacity(count);
+    for index in 0..count {
+        let todo = Todo::new(format!("conformance todo {index}"), Uuid::new_v4());
+        storage.add_todo(todo.clone()).await.unwrap();
+        added.push(todo);
+    }
+    added
+}
+
+pub async fn insert_get_round_trip(mut storage: BoxedStorage) {
+    let todo = Todo::new("Round trip".to_string(), Uuid::new_v4());
+
+    storage.add_todo(todo.clone()).await.unwrap();
+    let mut todo_list = storage.get_todo_list().await.unwrap();
+
+    assert_eq!(todo_list.len(), 1);
+    assert_eq!(todo_list.get(0), Some(&todo));
+}
+
+pub async fn add_rejects_duplicate_ids_and_unknown_parents(mut storage: BoxedStorage) {
+    let added = add_todos(&mut storage, 2).await;
+    storage.remove_todo(added[1].id).await.unwrap();
+
+    assert!(storage.add_todo(added[0].clone()).await.is_err());
+    assert!(storage.add_todo(added[1].clone()).await.is_err());
+    let orphan = Todo::new("orphan".to_string(), Uuid::new_v4()).with_parent(Uuid::new_v4());
+    assert!(storage.add_todo(orphan).await.is_err());
+    assert_eq!(
+        ids(&storage.get_todo_list().await.unwrap()),
+        vec![added[0].id]
+    );
+
+    // A parent in the trash still exists.
+    let subtask = Todo::new("subtask".to_string(), Uuid::new_v4()).with_parent(added[1].id);
+    storage.add_todo(subtask).await.unwrap();
+}
+
+pub async fn remove_returns_affected_rows(mut storage: BoxedStorage) {
+    let added = add_todos(&mut storage, 2).await;
+
+    assert_eq!(storage.remove_todo(added[0].id).await.unwrap(), 1);
+    assert_eq!(storage.remove_todo(added[0].id).await.unwrap(), 0);
+
+    let todo_list = storage.get_todo_list().await.unwrap();
+    assert_eq!(ids(&todo_list), vec![added[1].id]);
+
+    // Subtasks are counted along with their parent.
+    let subtask = Todo::new("subtask".to_string(), Uuid::new_v4()).with_parent(added[1].id);
+    let nested = Todo::new("nested".to_string(), Uuid::new_v4()).with_parent(subtask.id);
+    storage.add_todo(subtask).await.unwrap();
+    storage.add_todo(nested).await.unwrap();
+
+    assert_eq!(storage.remove_todo(added[1].id).await.unwrap(), 3);
+    assert_eq!(storage.restore_todo(added[1].id).await.unwrap(), 3);
+    assert_eq!(storage.get_todo_list().await.unwrap().len(), 3);
+}
+
+pub async fn remove_missing_todo_returns_zero(mut storage: BoxedStorage) {
+    add_todos(&mut storage, 1).await;
+
+    assert_eq!(storage.remove_todo(Uuid::new_v4()).await.unwrap(), 0);
+    assert_eq!(storage.get_todo_list().await.unwrap().len(), 1);
+}
+
+pub async fn mark_done_is_idempotent(mut storage: BoxedStorage) {
+    let added = add_todos(&mut storage, 2).await;
+
+    assert_eq!(storage.mark_todo_done(added[0].id).await.unwrap(), 1);
+    assert_eq!(storage.mark_todo_done(added[0].id).await.unwrap(), 1);
+
+    let todo_list = storage.get_todo_list().await.unwrap();
+    let done: Vec<bool> = added
+        .iter()
+        .map(|todo| todo_list.iter().find(|t| t.id == todo.id).unwrap().done)
+        .collect();
+    assert_eq!(done, vec![true, false]);
+}
+
+pub async fn mark_missing_todo_done_returns_zero(mut storage: BoxedStorage) {
+    assert_eq!(storage.mark_todo_done(Uuid::new_v4()).await.unwrap(), 0);
+}
+
+pub async fn mark_undone_reopens_todo(mut storage: BoxedStorage) {
+    let added = add_todos(&mut storage, 1).await;
+    storage.mark_todo_done(added[0].id).await.unwrap();
+
+    assert_eq!(storage.mark_todo_undone(added[0].id).await.unwrap(), 1);
+    assert_eq!(storage.mark_todo_undone(added[0].id).await.unwrap(), 1);
+    assert!(!storage.get_todo(added[0].id).await.unwrap().unwrap().done);
+    assert_eq!(storage.mark_todo_undone(Uuid::new_v4()).await.unwrap(), 0);
+}
+
+pub async fn get_todo_by_id(mut storage: BoxedStorage) {
+    let added = add_todos(&mut storage, 2).await;
+
+    assert_eq!(
+        storage.get_todo(added[1].id).await.unwrap(),
+        Some(added[1].clone())
+    );
+    assert_eq!(storage.get_todo(Uuid::new_v4()).await.unwrap(), None);
+}
+
+pub async fn update_changes_only_the_message(mut storage: BoxedStorage) {
+    let added = add_todos(&mut storage, 2).await;
+    storage.mark_todo_done(added[0].id).await.unwrap();
+
+    let number_modified = storage
+        .update_todo(added[0].id, "Edited".to_string())
+        .await
+        .unwrap();
+
+    let todo = storage.get_todo(added[0].id).await.unwrap().unwrap();
+    assert_eq!(number_modified, 1);
+    assert_eq!(todo.message, "Edited");
+    assert!(todo.done);
+    assert_eq!(
+        storage.get_todo(added[1].id).await.unwrap(),
+        Some(added[1].clone())
+    );
+}
+
+pub async fn update_missing_todo_returns_zero(mut storage: BoxedStorage) {
+    let number_modified = storage
+        .update_todo(Uuid::new_v4(), "Edited".to_string())
+        .await
+        .unwrap();
+
+    assert_eq!(number_modified, 0);
+}
+
+pub async fn priority_round_trips(mut storage: BoxedStorage) {
+    let urgent = Todo::new("Urgent".to_string(), Uuid::new_v4()).with_priority(Priority::Urgent);
+    storage.add_todo(urgent.clone()).await.unwrap();
+    let added = add_todos(&mut storage, 1).await;
+
+    assert_eq!(storage.get_todo(urgent.id).await.unwrap(), Some(urgent));
+    assert_eq!(
+        storage
+            .set_priority(added[0].id, Priority::Low)
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        storage
+            .get_todo(added[0].id)
+            .await
+            .unwrap()
+            .unwrap()
+            .priority,
+        Priority::Low
+    );
+    assert_eq!(
+        storage
+            .set_priority(Uuid::new_v4(), Priority::High)
+            .await
+            .unwrap(),
+        0
+    );
+}
+
+pub async fn due_round_trips(mut storage: BoxedStorage) {
+    let due = Due {
+        date: NaiveDate::from_ymd_opt(2026, 11, 6).unwrap(),
+        time: NaiveTime::from_hms_opt(17, 0, 0),
+    };
+    let mut with_due = Todo::new("Deadline".to_string(), Uuid::new_v4());
+    with_due.due = Some(due);
+    storage.add_todo(with_due.clone()).await.unwrap();
+    let added = add_todos(&mut storage, 1).await;
+
+    assert_eq!(
+        storage.get_todo(with_due.id).await.unwrap(),
+        Some(with_due.clone())
+    );
+
+    let date_only = Due { time: None, ..due };
+    assert_eq!(
+        storage.set_due(added[0].id, Some(date_only)).await.unwrap(),
+        1
+    );
+    assert_eq!(storage.set_due(with_due.id, None).await.unwrap(), 1);
+    assert_eq!(storage.set_due(Uuid::new_v4(), None).await.unwrap(), 0);
+
+    let todo_list = storage.get_todo_list().await.unwrap();
+    let dues: Vec<Option<Due>> = todo_list.iter().map(|todo| todo.due).collect();
+    assert!(dues.contains(&Some(date_only)));
+    assert!(dues.contains(&None));
+}
+
+pub async fn recurrence_round_trips(mut storage: BoxedStorage) {
+    let mut recurring = Todo::new("Send invoice".to_string(), Uuid::new_v4());
+    recurring.recurrence = Some(Recurrence::Monthly(31));
+    storage.add_todo(recurring.clone()).await.unwrap();
+    let added = add_todos(&mut storage, 1).await;
+
+    assert_eq!(
+        storage.get_todo(recurring.id).await.unwrap(),
+        Some(recurring.clone())
+    );
+
+    // The next occurrence remembers where it came from.
+    let today = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+    let next = recurring.next_occurrence(today).unwrap();
+    storage.add_todo(next.clone()).await.unwrap();
+    assert_eq!(storage.get_todo(next.id).await.unwrap(), Some(next.clone()));
+    assert_eq!(next.origin, Some(recurring.id));
+
+    let every = Some(Recurrence::EveryDays(3));
+    assert_eq!(storage.set_recurrence(added[0].id, every).await.unwrap(), 1);
+    assert_eq!(storage.set_recurrence(recurring.id, None).await.unwrap(), 1);
+    assert_eq!(
+        storage.set_recurrence(Uuid::new_v4(), None).await.unwrap(),
+        0
+    );
+
+    let get = |todo: Option<Todo>| todo.unwrap().recurrence;
+    assert_eq!(get(storage.get_todo(added[0].id).await.unwrap()), every);
+    assert_eq!(get(storage.get_todo(recurring.id).await.unwrap()), None);
+}
+
+pub async fn timestamps_are_maintained(mut storage: BoxedStorage) {
+    let added = add_todos(&mut storage, 1).await;
+    let id = added[0].id;
+    let created = storage.get_todo(id).await.unwrap().unwrap();
+    assert_eq!(created.created_at, added[0].created_at);
+    assert_eq!(created.completed_at, None);
+
+    storage.mark_todo_done(id).await.unwrap();
+    let done = storage.get_todo(id).await.unwrap().unwrap();
+    let completed_at = done
+        .completed_at
+        .expect("done todos have a completion time");
+    assert!(completed_at >= created.created_at);
+    assert!(done.updated_at >= created.updated_at);
+
+    storage.mark_todo_done(id).await.unwrap();
+    let done_again = storage.get_todo(id).await.unwrap().unwrap();
+    assert_eq!(done_again.completed_at, Some(completed_at));
+
+    storage.update_todo(id, "Edited".to_string()).await.unwrap();
+    let edited = storage.get_todo(id).await.unwrap().unwrap();
+    assert!(edited.updated_at >= done.updated_at);
+    assert_eq!(edited.created_at, created.created_at);
+
+    storage.mark_todo_undone(id).await.unwrap();
+    let reopened = storage.get_todo(id).await.unwrap().unwrap();
+    assert_eq!(reopened.completed_at, None);
+}
+
+pub async fn tags_round_trip_and_filter(mut storage: BoxedStorage) {
+    let tag = |name: &str| name.parse::<Tag>().unwrap();
+    let tagged = Todo::new("Deploy #backend @work".to_string(), Uuid::new_v4());
+    storage.add_todo(tagged.clone()).await.unwrap();
+    let added = add_todos(&mut storage, 2).await;
+
+    assert_eq!(
+        storage.get_todo(tagged.id).await.unwrap().unwrap().tags,
+        vec![tag("@work"), tag("backend")]
+    );
+
+    let number_modified = storage
+        .update_tags(tagged.id, vec![tag("frontend")], vec![tag("@work")])
+        .await
+        .unwrap();
+    assert_eq!(number_modified, 1);
+    storage
+        .update_tags(added[0].id, vec![tag("backend")], vec![])
+        .await
+        .unwrap();
+    assert_eq!(
+        storage.get_todo(tagged.id).await.unwrap().unwrap().tags,
+        vec![tag("backend"), tag("frontend")]
+    );
+
+    let backend = storage.get_todos_tagged(tag("backend")).await.unwrap();
+    let mut backend_ids = ids(&backend);
+    backend_ids.sort();
+    let mut expected = vec![tagged.id, added[0].id];
+    expected.sort();
+    assert_eq!(backend_ids, expected);
+    assert!(storage
+        .get_todos_tagged(tag("@work"))
+        .await
+        .unwrap()
+        .is_empty());
+
+    storage.remove_todo(tagged.id).await.unwrap();
+    let frontend = storage.get_todos_tagged(tag("frontend")).await.unwrap();
+    assert!(frontend.is_empty());
+    assert_eq!(
+        storage
+            .update_tags(Uuid::new_v4(), vec![tag("backend")], vec![])
+            .await
+            .unwrap(),
+        0
+    );
+}
+
+pub async fn clear_empties_the_list(mut storage: BoxedStorage) {
+    add_todos(&mut storage, 3).await;
+
+    storage.clear_todo_list().await.unwrap();
+
+    assert!(storage.get_todo_list().await.unwrap().is_empty());
+}
+
+pub async fn list_order_is_stable(mut storage: BoxedStorage) {
+    let added = add_todos(&mut storage, 4).await;
+    let added: Vec<Uuid> = added.iter().map(|todo| todo.id).collect();
+
+    // Todos are listed in the order they were added, even after updates.
+    assert_eq!(ids(&storage.get_todo_list().await.unwrap()), added);
+    storage.mark_todo_done(added[0]).await.unwrap();
+    storage
+        .update_todo(added[2], "changed".to_string())
+        .await
+        .unwrap();
+    assert_eq!(ids(&storage.get_todo_list().await.unwrap()), added);
+
+    storage.remove_todo(added[1]).await.unwrap();
+    let expected: Vec<Uuid> = added.iter().copied().filter(|id| *id != added[1]).collect();
+    assert_eq!(ids(&storage.get_todo_list().await.unwrap()), expected);
+}
+
+pub async fn todos_can_be_reordered(mut storage: BoxedStorage) {
+    let added = add_todos(&mut storage, 4).await;
+    let [a, b, c, d] = [added[0].id, added[1].id, added[2].id, added[3].id];
+    let order = |todo_list: Todos| ids(&todo_list);
+
+    assert_eq!(storage.reorder_todo(d, Some(a)).await.unwrap(), 1);
+    assert_eq!(order(storage.get_todo_list().await.unwrap()), [d, a, b, c]);
+    assert_eq!(storage.reorder_todo(d, None).await.unwrap(), 1);
+    assert_eq!(order(storage.get_todo_list().await.unwrap()), [a, b, c, d]);
+    assert_eq!(storage.reorder_todo(a, Some(c)).await.unwrap(), 1);
+    assert_eq!(order(storage.get_todo_list().await.unwrap()), [b, a, c, d]);
+    assert_eq!(storage.reorder_todo(Uuid::new_v4(), None).await.unwrap(), 0);
+    assert_eq!(
+        storage.reorder_todo(a, Some(Uuid::new_v4())).await.unwrap(),
+        0
+    );
+    assert_eq!(order(storage.get_todo_list().await.unwrap()), [b, a, c, d]);
+
+    // Squeezing todos into the same gap again and again runs out of room between positions
+    // at some point, and the list has to be spread out.
+    for round in 0..80 {
+        let (moved, anchor) = if round % 2 == 0 { (d, c) } else { (c, d) };
+        storage.reorder_todo(moved, Some(anchor)).await.unwrap();
+        assert_eq!(
+            order(storage.get_todo_list().await.unwrap()),
+            [b, a, moved, anchor]
+        );
+    }
+
+    let e = Todo::new("added last".to_string(), Uuid::new_v4());
+    storage.add_todo(e.clone()).await.unwrap();
+    assert_eq!(order(storage.get_todo_list().await.unwrap())[4], e.id);
+}
+
+fn list(name: &str) -> ListName {
+    name.parse().unwrap()
+}
+
+pub async fn lists_scope_todos(mut storage: BoxedStorage) {
+    let inbox = add_todos(&mut storage, 1).await;
+    assert_eq!(storage.current_list(), ListName::default());
+
+    assert!(storage.create_list(list("conformance-work")).await.unwrap());
+    assert!(storage.switch_list(list("conformance-work")).await.unwrap());
+    let work = add_todos(&mut storage, 2).await;
+
+    assert_eq!(
+        ids(&storage.get_todo_list().await.unwrap()),
+        vec![work[0].id, work[1].id]
+    );
+    assert_eq!(storage.get_todo(inbox[0].id).await.unwrap(), None);
+    assert_eq!(storage.mark_todo_done(inbox[0].id).await.unwrap(), 0);
+    assert_eq!(storage.remove_todo(inbox[0].id).await.unwrap(), 0);
+
+    storage.clear_todo_list().await.unwrap();
+    assert!(storage.get_todo_list().await.unwrap().is_empty());
+
+    assert!(storage.switch_list(ListName::default()).await.unwrap());
+    assert_eq!(
+        ids(&storage.get_todo_list().await.unwrap()),
+        vec![inbox[0].id]
+    );
+    assert!(!storage
+        .switch_list(list("conformance-missing"))
+        .await
+        .unwrap());
+    assert_eq!(storage.current_list(), ListName::default());
+}
+
+pub async fn lists_can_be_renamed_moved_and_deleted(mut storage: BoxedStorage) {
+    let added = add_todos(&mut storage, 2).await;
+    storage.mark_todo_done(added[1].id).await.unwrap();
+
+    assert!(storage.create_list(list("conformance-work")).await.unwrap());
+    assert!(!storage.create_list(list("conformance-work")).await.unwrap());
+    assert_eq!(
+        storage
+            .move_todo(added[1].id, list("conformance-work"))
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        storage
+            .move_todo(added[0].id, list("conformance-missing"))
+            .await
+            .unwrap(),
+        0
+    );
+    assert_eq!(
+        ids(&storage.get_todo_list().await.unwrap()),
+        vec![added[0].id]
+    );
+
+    let lists = storage.get_lists().await.unwrap();
+    assert!(lists.contains(&TodoList {
+        name: list("conformance-work"),
+        open: 0,
+        done: 1,
+    }));
+
+    storage.switch_list(list("conformance-work")).await.unwrap();
+    assert_eq!(
+        storage
+            .rename_list(list("conformance-work"), list("conformance-job"))
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(storage.current_list(), list("conformance-job"));
+    let moved = storage.get_todo(added[1].id).await.unwrap().unwrap();
+    assert!(moved.done);
+
+    assert_eq!(
+        storage
+            .rename_list(list("conformance-missing"), list("conformance-other"))
+            .await
+            .unwrap(),
+        0
+    );
+    assert_eq!(
+        storage.delete_list(list("conformance-job")).await.unwrap(),
+        1
+    );
+    assert_eq!(storage.current_list(), ListName::default());
+    assert_eq!(
+        storage.delete_list(list("conformance-job")).await.unwrap(),
+        0
+    );
+    let names: Vec<ListName> = storage
+        .get_lists()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|todo_list| todo_list.name)
+        .collect();
+    assert!(!names.contains(&list("conformance-job")));
+    assert!(!storage.switch_list(list("conformance-job")).await.unwrap());
+}
+
+pub async fn subtasks_move_and_cascade_with_their_parent(mut storage: BoxedStorage) {
+    let release = Todo::new("release".to_string(), Uuid::new_v4());
+    let tests = Todo::new("run tests".to_string(), Uuid::new_v4()).with_parent(release.id);
+    let flaky = Todo::new("fix flaky test".to_string(), Uuid::new_v4()).with_parent(tests.id);
+    let notes = Todo::new("write notes".to_string(), Uuid::new_v4()).with_parent(release.id);
+    for todo in [&release, &tests, &flaky, &notes] {
+        storage.add_todo(todo.clone()).await.unwrap();
+    }
+    assert_eq!(
+        storage.get_todo(flaky.id).await.unwrap().unwrap().parent,
+        Some(tests.id)
+    );
+
+    assert_eq!(storage.set_parent(notes.id, None).await.unwrap(), 1);
+    assert_eq!(storage.set_parent(Uuid::new_v4(), None).await.unwrap(), 0);
+
+    storage.create_list(list("conformance-work")).await.unwrap();
+    assert_eq!(
+        storage
+            .move_todo(tests.id, list("conformance-work"))
+            .await
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        ids(&storage.get_todo_list().await.unwrap()),
+        vec![release.id, notes.id]
+    );
+    storage
+        .set_parent(notes.id, Some(release.id))
+        .await
+        .unwrap();
+    assert_eq!(storage.remove_todo(release.id).await.unwrap(), 2);
+    assert!(storage.get_todo_list().await.unwrap().is_empty());
+
+    storage.switch_list(list("conformance-work")).await.unwrap();
+    assert_eq!(
+        ids(&storage.get_todo_list().await.unwrap()),
+        vec![tests.id, flaky.id]
+    );
+    let parent_of = |todo: Option<Todo>| todo.unwrap().parent;
+    assert_eq!(parent_of(storage.get_todo(tests.id).await.unwrap()), None);
+    assert_eq!(
+        parent_of(storage.get_todo(flaky.id).await.unwrap()),
+        Some(tests.id)
+    );
+}
+
+pub async fn blockers_refuse_cycles_and_go_with_removed_todos(mut storage: BoxedStorage) {
+    let added = add_todos(&mut storage, 3).await;
+    let (first, second, third) = (added[0].id, added[1].id, added[2].id);
+
+    assert!(storage.add_blocker(third, second).await.unwrap());
+    assert!(storage.add_blocker(second, first).await.unwrap());
+    // Adding it again changes nothing.
+    assert!(storage.add_blocker(second, first).await.unwrap());
+    assert!(!storage.add_blocker(first, third).await.unwrap());
+    assert!(!storage.add_blocker(first, first).await.unwrap());
+    // Both todos have to be on the list.
+    assert!(!storage.add_blocker(first, Uuid::new_v4()).await.unwrap());
+    assert!(!storage.add_blocker(Uuid::new_v4(), first).await.unwrap());
+
+    let blocked_by = |todo: Option<Todo>| todo.unwrap().blocked_by;
+    assert_eq!(
+        blocked_by(storage.get_todo(second).await.unwrap()),
+        vec![first]
+    );
+    assert!(blocked_by(storage.get_todo(first).await.unwrap()).is_empty());
+
+    let mut blocked = Todo::new("blocked from the start".to_string(), Uuid::new_v4());
+    blocked.blocked_by = vec![first, third];
+    blocked.blocked_by.sort();
+    storage.add_todo(blocked.clone()).await.unwrap();
+    assert_eq!(
+        storage.get_todo(blocked.id).await.unwrap(),
+        Some(blocked.clone())
+    );
+
+    assert_eq!(storage.remove_blocker(third, second).await.unwrap(), 1);
+    assert_eq!(storage.remove_blocker(third, second).await.unwrap(), 0);
+    assert!(blocked_by(storage.get_todo(third).await.unwrap()).is_empty());
+
+    storage.remove_todo(first).await.unwrap();
+    assert!(blocked_by(storage.get_todo(second).await.unwrap()).is_empty());
+    assert_eq!(
+        blocked_by(storage.get_todo(blocked.id).await.unwrap()),
+        vec![third]
+    );
+}
+
+pub async fn trash_keeps_removed_todos_until_purged(mut storage: BoxedStorage) {
+    let kept = Todo::new("kept".to_string(), Uuid::new_v4());
+    let parent = Todo::new("parent".to_string(), Uuid::new_v4());
+    let subtask = Todo::new("subtask".to_string(), Uuid::new_v4()).with_parent(parent.id);
+    for todo in [&kept, &parent, &subtask] {
+        storage.add_todo(todo.clone()).await.unwrap();
+    }
+    storage.add_blocker(kept.id, parent.id).await.unwrap();
+    let blocked_by = |todo: Option<Todo>| todo.unwrap().blocked_by;
+
+    // Trashed todos are left out everywhere else.
+    assert_eq!(storage.remove_todo(parent.id).await.unwrap(), 2);
+    assert_eq!(ids(&storage.get_todo_list().await.unwrap()), vec![kept.id]);
+    assert_eq!(storage.get_todo(subtask.id).await.unwrap(), None);
+    assert_eq!(storage.mark_todo_done(parent.id).await.unwrap(), 0);
+    assert_eq!(storage.remove_todo(parent.id).await.unwrap(), 0);
+    assert!(blocked_by(storage.get_todo(kept.id).await.unwrap()).is_empty());
+    let lists = storage.get_lists().await.unwrap();
+    assert_eq!(lists[0].open, 1);
+    let trash = storage.get_trash().await.unwrap();
+    assert_eq!(ids(&trash), vec![parent.id, subtask.id]);
+    assert!(trash.iter().all(|todo| todo.deleted_at.is_some()));
+
+    // Restoring brings the subtasks back too.
+    assert_eq!(storage.restore_todo(parent.id).await.unwrap(), 2);
+    assert_eq!(storage.restore_todo(parent.id).await.unwrap(), 0);
+    assert_eq!(storage.restore_todo(kept.id).await.unwrap(), 0);
+    assert!(storage.get_trash().await.unwrap().is_empty());
+    let restored = storage.get_todo(subtask.id).await.unwrap().unwrap();
+    assert_eq!(
+        (restored.parent, restored.deleted_at),
+        (Some(parent.id), None)
+    );
+    assert_eq!(
+        blocked_by(storage.get_todo(kept.id).await.unwrap()),
+        vec![parent.id]
+    );
+
+    // A subtask whose parent stays in the trash comes back on its own.
+    storage.remove_todo(subtask.id).await.unwrap();
+    storage.remove_todo(parent.id).await.unwrap();
+    assert_eq!(storage.restore_todo(subtask.id).await.unwrap(), 1);
+    assert_eq!(
+        storage.get_todo(subtask.id).await.unwrap().unwrap().parent,
+        None
+    );
+
+    // Purging counts the subtasks that go with their parent.
+    storage.set_parent(subtask.id, Some(kept.id)).await.unwrap();
+    storage.clear_todo_list().await.unwrap();
+    assert!(storage.get_todo_list().await.unwrap().is_empty());
+    let trash = storage.get_trash().await.unwrap();
+    assert_eq!(trash.iter().count(), 3);
+    let oldest = trash
+        .iter()
+        .filter_map(|todo| todo.deleted_at)
+        .min()
+        .unwrap();
+    let just_before = oldest - chrono::Duration::microseconds(1);
+    assert_eq!(storage.purge_trash(just_before).await.unwrap(), 0);
+    assert_eq!(storage.purge_trash(timestamp()).await.unwrap(), 3);
+    assert!(storage.get_trash().await.unwrap().is_empty());
+    assert_eq!(storage.restore_todo(kept.id).await.unwrap(), 0);
+}
+
+pub async fn restored_todos_go_back_where_they_were(mut storage: BoxedStorage) {
+    let added = add_todos(&mut storage, 4).await;
+    let all = ids(&Todos::new(added.clone()));
+
+    storage.remove_todo(added[1].id).await.unwrap();
+    storage.restore_todo(added[1].id).await.unwrap();
+    assert_eq!(ids(&storage.get_todo_list().await.unwrap()), all);
+
+    storage.mark_todo_done(added[2].id).await.unwrap();
+    storage.archive_done(timestamp()).await.unwrap();
+    storage.unarchive_todo(added[2].id).await.unwrap();
+    assert_eq!(ids(&storage.get_todo_list().await.unwrap()), all);
+}
+
+pub async fn archive_keeps_finished_todos_out_of_the_list(mut storage: BoxedStorage) {
+    let open = Todo::new("open".to_string(), Uuid::new_v4());
+    let done = Todo::new("done".to_string(), Uuid::new_v4());
+    let parent = Todo::new("parent".to_string(), Uuid::new_v4());
+    let subtask = Todo::new("subtask".to_string(), Uuid::new_v4()).with_parent(parent.id);
+    let unfinished = Todo::new("unfinished".to_string(), Uuid::new_v4());
+    let open_subtask =
+        Todo::new("open subtask".to_string(), Uuid::new_v4()).with_parent(unfinished.id);
+    for todo in [&open, &done, &parent, &subtask, &unfinished, &open_subtask] {
+        storage.add_todo(todo.clone()).await.unwrap();
+    }
+    storage.add_blocker(open.id, done.id).await.unwrap();
+    for todo in [&done, &parent, &subtask, &unfinished] {
+        storage.mark_todo_done(todo.id).await.unwrap();
+    }
+    let blocked_by = |todo: Option<Todo>| todo.unwrap().blocked_by;
+
+    // Only todos left alone since the cutoff are archived.
+    let todo_list = storage.get_todo_list().await.unwrap();
+    let first_done = todo_list
+        .iter()
+        .filter(|todo| todo.done)
+        .map(|todo| todo.updated_at)
+        .min()
+        .unwrap();
+    let just_before = first_done - chrono::Duration::microseconds(1);
+    assert_eq!(storage.archive_done(just_before).await.unwrap(), 0);
+
+    // A done todo with an open subtask stays, like the open todos.
+    assert_eq!(storage.archive_done(timestamp()).await.unwrap(), 3);
+    assert_eq!(
+        ids(&storage.get_todo_list().await.unwrap()),
+        vec![open.id, unfinished.id, open_subtask.id]
+    );
+    assert_eq!(storage.get_todo(subtask.id).await.unwrap(), None);
+    assert_eq!(storage.mark_todo_undone(done.id).await.unwrap(), 0);
+    assert_eq!(storage.remove_todo(parent.id).await.unwrap(), 0);
+    assert!(blocked_by(storage.get_todo(open.id).await.unwrap()).is_empty());
+    let lists = storage.get_lists().await.unwrap();
+    assert_eq!((lists[0].open, lists[0].done), (2, 1));
+    let archive = storage.get_archive().await.unwrap();
+    assert_eq!(ids(&archive), vec![done.id, parent.id, subtask.id]);
+    assert!(archive.iter().all(|todo| todo.archived_at.is_some()));
+
+    // Clearing the list leaves the archive alone.
+    storage.clear_todo_list().await.unwrap();
+    assert_eq!(storage.get_archive().await.unwrap().iter().count(), 3);
+    storage.purge_trash(timestamp()).await.unwrap();
+
+    // A subtask comes back on its own, a todo with the subtasks archived along with it.
+    let before_unarchiving = timestamp() - chrono::Duration::microseconds(1);
+    assert_eq!(storage.unarchive_todo(subtask.id).await.unwrap(), 1);
+    let unarchived = storage.get_todo(subtask.id).await.unwrap().unwrap();
+    assert_eq!((unarchived.parent, unarchived.archived_at), (None, None));
+    assert!(unarchived.done);
+    storage
+        .set_parent(subtask.id, Some(parent.id))
+        .await
+        .unwrap();
+    assert_eq!(storage.unarchive_todo(done.id).await.unwrap(), 1);
+    assert_eq!(storage.unarchive_todo(done.id).await.unwrap(), 0);
+    assert_eq!(storage.unarchive_todo(subtask.id).await.unwrap(), 0);
+    assert_eq!(ids(&storage.get_archive().await.unwrap()), vec![parent.id]);
+
+    // Unarchived todos count as touched, so they are not archived again right away.
+    assert_eq!(storage.archive_done(before_unarchiving).await.unwrap(), 0);
+    assert_eq!(storage.unarchive_todo(parent.id).await.unwrap(), 1);
+    assert_eq!(storage.archive_done(timestamp()).await.unwrap(), 3);
+    assert!(storage.get_todo_list().await.unwrap().is_empty());
+}
+
+// Generates one test per check. `$harness` is an async fn in the calling module that runs
+// the given check against a fresh, empty storage.
+macro_rules! storage_conformance_tests {
+    ($harness:ident) => {
+        mod conformance {
+            use crate::repository::todo::conformance;
+
+            #[tokio::test]
+            async fn insert_get_round_trip() {
+                super::$harness(conformance::insert_get_round_trip).await;
+            }
+
+            #[tokio::test]
+            async fn add_rejects_duplicate_ids_and_unknown_parents() {
+                super::$harness(conformance::add_rejects_duplicate_ids_and_unknown_parents).await;
+            }
+
+            #[tokio::test]
+            async fn remove_returns_affected_rows() {
+                super::$harness(conformance::remove_returns_affected_rows).await;
+            }
+
+            #[tokio::test]
+            async fn remove_missing_todo_returns_zero() {
+                super::$harness(conformance::remove_missing_todo_returns_zero).await;
+            }
+
+            #[tokio::test]
+            async fn mark_done_is_idempotent() {
+                super::$harness(conformance::mark_done_is_idempotent).await;
+            }
+
+            #[tokio::test]
+            async fn mark_missing_todo_done_returns_zero() {
+                super::$harness(conformance::mark_missing_todo_done_returns_zero).await;
+            }
+
+            #[tokio::test]
+            async fn mark_undone_reopens_todo() {
+                super::$harness(conformance::mark_undone_reopens_todo).await;
+            }
+
+            #[tokio::test]
+            async fn get_todo_by_id() {
+                super::$harness(conformance::get_todo_by_id).await;
+            }
+
+            #[tokio::test]
+            async fn update_changes_only_the_message() {
+                super::$harness(conformance::update_changes_only_the_message).await;
+            }
+
+            #[tokio::test]
+            async fn update_missing_todo_returns_zero() {
+                super::$harness(conformance::update_missing_todo_returns_zero).await;
+            }
+
+            #[tokio::test]
+            async fn priority_round_trips() {
+                super::$harness(conformance::priority_round_trips).await;
+            }
+
+            #[tokio::test]
+            async fn recurrence_round_trips() {
+                super::$harness(conformance::recurrence_round_trips).await;
+            }
+
+            #[tokio::test]
+            async fn todos_can_be_reordered() {
+                super::$harness(conformance::todos_can_be_reordered).await;
+            }
+
+            #[tokio::test]
+            async fn blockers_refuse_cycles_and_go_with_removed_todos() {
+                super::$harness(conformance::blockers_refuse_cycles_and_go_with_removed_todos)
+                    .await;
+            }
+
+            #[tokio::test]
+            async fn trash_keeps_removed_todos_until_purged() {
+                super::$harness(conformance::trash_keeps_removed_todos_until_purged).await;
+            }
+
+            #[tokio::test]
+            async fn restored_todos_go_back_where_they_were() {
+                super::$harness(conformance::restored_todos_go_back_where_they_were).await;
+            }
+
+            #[tokio::test]
+            async fn archive_keeps_finished_todos_out_of_the_list() {
+                super::$harness(conformance::archive_keeps_finished_todos_out_of_the_list).await;
+            }
+
+            #[tokio::test]
+            async fn due_round_trips() {
+                super::$harness(conformance::due_round_trips).await;
+            }
+
+            #[tokio::test]
+            async fn timestamps_are_maintained() {
+                super::$harness(conformance::timestamps_are_maintained).await;
+            }
+
+            #[tokio::test]
+            async fn tags_round_trip_and_filter() {
+                super::$harness(conformance::tags_round_trip_and_filter).await;
+            }
+
+            #[tokio::test]
+            async fn clear_empties_the_list() {
+                super::$harness(conformance::clear_empties_the_list).await;
+            }
+
+            #[tokio::test]
+            async fn list_order_is_stable() {
+                super::$harness(conformance::list_order_is_stable).await;
+            }
+
+            #[tokio::test]
+            async fn subtasks_move_and_cascade_with_their_parent() {
+                super::$harness(conformance::subtasks_move_and_cascade_with_their_parent).await;
+            }
+
+            #[tokio::test]
+            async fn lists_scope_todos() {
+                super::$harness(conformance::lists_scope_todos).await;
+            }
+
+            #[tokio::test]
+            async fn lists_can_be_renamed_moved_and_deleted() {
+                super::$harness(conformance::lists_can_be_renamed_moved_and_deleted).await;
+            }
+        }
+    };
+}
+
+pub(crate) use storage_conformance_tests;
@@ -1,21 +1,80 @@
 use std::fmt;
-use tokio_postgres::Error;
+use tokio_postgres::error::SqlState;
+use uuid::Uuid;
 
 #[derive(Debug)]
-pub struct StorageError {
-    pub error: Error,
+pub enum StorageError {
+    Postgres(tokio_postgres::Error),
+    Sqlite(rusqlite::Error),
+    PoolTimeout,
+    // The in-memory storage's counterparts of the key constraints the databases enforce.
+    DuplicateTodo(Uuid),
+    MissingParent(Uuid),
 }
 
 impl std::error::Error for StorageError {}
 
+impl StorageError {
+    // Errors caused by a lost or refused connection; retrying later may succeed.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            StorageError::Postgres(error) => {
+                error.is_closed()
+                    || error.code().is_some_and(|code| {
+                        code.code().starts_with("08")
+                            || [
+                                SqlState::ADMIN_SHUTDOWN,
+                                SqlState::CRASH_SHUTDOWN,
+                                SqlState::CANNOT_CONNECT_NOW,
+                            ]
+                            .contains(code)
+                    })
+                    || source_is_io(error)
+            }
+            StorageError::Sqlite(rusqlite::Error::SqliteFailure(error, _)) => matches!(
+                error.code,
+                rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked
+            ),
+            StorageError::Sqlite(_) => false,
+            StorageError::PoolTimeout => true,
+            StorageError::DuplicateTodo(_) | StorageError::MissingParent(_) => false,
+        }
+    }
+}
+
+fn source_is_io(error: &tokio_postgres::Error) -> bool {
+    std::error::Error::source(error).is_some_and(|source| source.is::<std::io::Error>())
+}
+
 impl fmt::Display for StorageError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Error in storage: {}", self.error)
+        match self {
+            StorageError::Postgres(error) => write!(f, "Error in storage: {error}"),
+            StorageError::Sqlite(error) => write!(f, "Error in storage: {error}"),
+            StorageError::PoolTimeout => {
+                write!(
+                    f,
+                    "Error in storage: timed out waiting for a database connection"
+                )
+            }
+            StorageError::DuplicateTodo(id) => {
+                write!(f, "Error in storage: there is already a todo with id {id}")
+            }
+            StorageError::MissingParent(id) => {
+                write!(f, "Error in storage: there is no parent todo with id {id}")
+            }
+        }
+    }
+}
+
+impl From<tokio_postgres::Error> for StorageError {
+    fn from(error: tokio_postgres::Error) -> Self {
+        StorageError::Postgres(error)
     }
 }
 
-impl From<Error> for StorageError {
-    fn from(error: Error) -> Self {
-        StorageError { error }
+impl From<rusqlite::Error> for StorageError {
+    fn from(error: rusqlite::Error) -> Self {
+        StorageError::Sqlite(error)
     }
 }
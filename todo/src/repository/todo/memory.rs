@@ -0,0 +1,642 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+use crate::domain::due::Due;
+use crate::domain::list::{ListName, TodoList};
+use crate::domain::priority::Priority;
+use crate::domain::recurrence::Recurrence;
+use crate::domain::tag::Tag;
+use crate::domain::todo::{timestamp, Todo};
+use crate::domain::todos::Todos;
+use crate::repository::todo::error::StorageError;
+use crate::repository::todo::Storage;
+
+pub struct InMemoryStorage {
+    lists: BTreeMap<ListName, Todos>,
+    // Removed todos, by the list they were removed from.
+    trash: BTreeMap<ListName, Todos>,
+    // Archived todos, by the list they were archived from.
+    archive: BTreeMap<ListName, Todos>,
+    // Where trashed and archived todos stood in their list, so they go back there.
+    positions: HashMap<Uuid, usize>,
+    list: ListName,
+}
+
+impl InMemoryStorage {
+    pub fn new() -> Self {
+        InMemoryStorage {
+            lists: BTreeMap::from([(ListName::default(), Todos::new(vec![]))]),
+            trash: BTreeMap::new(),
+            archive: BTreeMap::new(),
+            positions: HashMap::new(),
+            list: ListName::default(),
+        }
+    }
+
+    // The todos of the current list, which always exists.
+    fn todos(&mut self) -> &mut Todos {
+        self.lists
+            .entry(self.list.clone())
+            .or_insert_with(|| Todos::new(vec![]))
+    }
+
+    // Applies `change` to the todo with the given id and bumps its `updated_at`, returning the
+    // number of todos changed.
+    fn modify(&mut self, todo_uuid: Uuid, change: impl FnOnce(&mut Todo)) -> u64 {
+        let todos = self.todos();
+        let todo = todos
+            .position(todo_uuid)
+            .and_then(|index| todos.get_mut(index));
+        match todo {
+            Some(todo) => {
+                change(todo);
+                todo.updated_at = timestamp();
+                1
+            }
+            None => 0,
+        }
+    }
+
+    // The trashed todos of the current list.
+    fn trashed(&mut self) -> &mut Todos {
+        self.trash
+            .entry(self.list.clone())
+            .or_insert_with(|| Todos::new(vec![]))
+    }
+
+    // The archived todos of the current list.
+    fn archived(&mut self) -> &mut Todos {
+        self.archive
+            .entry(self.list.clone())
+            .or_insert_with(|| Todos::new(vec![]))
+    }
+
+    // Whether a todo with the given id is on any list, in the trash or in the archive.
+    fn exists(&self, todo_uuid: Uuid) -> bool {
+        let shelves = self.trash.values().chain(self.archive.values());
+        self.lists
+            .values()
+            .chain(shelves)
+            .any(|todos| todos.position(todo_uuid).is_some())
+    }
+
+    // Takes the todos with the given ids out of the current list, in list order, and remembers
+    // where each of them stood.
+    fn take_out(&mut self, ids: &[Uuid]) -> Vec<Todo> {
+        let todos = self.todos();
+        let indices: Vec<usize> = todos
+            .iter()
+            .enumerate()
+            .filter(|(_, todo)| ids.contains(&todo.id))
+            .map(|(index, _)| index)
+            .collect();
+        let taken: Vec<(usize, Todo)> = indices
+            .into_iter()
+            .rev()
+            .map(|index| (index, todos.remove(index)))
+            .collect();
+        taken
+            .into_iter()
+            .rev()
+            .map(|(index, todo)| {
+                self.positions.insert(todo.id, index);
+                todo
+            })
+            .collect()
+    }
+
+    // Puts todos taken out of the trash or the archive back where they stood in the current
+    // list. The todo they were taken for loses a parent that is no longer in the list.
+    fn bring_back(&mut self, todo_uuid: Uuid, mut todos: Vec<Todo>) {
+        // In list order, so that each index is counted among the todos put back before it.
+        todos.sort_by_key(|todo| self.positions.get(&todo.id).copied());
+        for mut todo in todos {
+            let index = self.positions.remove(&todo.id);
+            let live = self.todos();
+            let parent_left = todo
+                .parent
+                .is_some_and(|parent| live.position(parent).is_none());
+            if todo.id == todo_uuid && parent_left {
+                todo.parent = None;
+            }
+            let index = index.map_or(live.len(), |index| index.min(live.len()));
+            live.insert(index, todo);
+        }
+    }
+
+    // Moves the todos with the given ids from the current list to its trash.
+    fn trash_todos(&mut self, ids: &[Uuid]) {
+        let deleted_at = timestamp();
+        for mut todo in self.take_out(ids) {
+            todo.deleted_at = Some(deleted_at);
+            self.trashed().push(todo);
+        }
+    }
+
+    // Copies of `todos` without the blockers sitting in the trash or the archive, which no
+    // longer count.
+    fn without_hidden_blockers(&self, todos: &Todos) -> Todos {
+        let hidden: Vec<Uuid> = self
+            .trash
+            .values()
+            .chain(self.archive.values())
+            .flat_map(|todos| todos.iter().map(|todo| todo.id))
+            .collect();
+        Todos::new(
+            todos
+                .iter()
+                .cloned()
+                .map(|mut todo| {
+                    todo.blocked_by.retain(|blocker| !hidden.contains(blocker));
+                    todo
+                })
+                .collect(),
+        )
+    }
+
+    // Drops removed todos from the blockers of every todo left, like ON DELETE CASCADE.
+    fn forget_blockers(&mut self, removed: &[Uuid]) {
+        let shelves = self.trash.values_mut().chain(self.archive.values_mut());
+        for todos in self.lists.values_mut().chain(shelves) {
+            for todo in todos.iter_mut() {
+                todo.blocked_by.retain(|blocker| !removed.contains(blocker));
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Storage for InMemoryStorage {
+    fn current_list(&self) -> ListName {
+        self.list.clone()
+    }
+    async fn switch_list(&mut self, name: ListName) -> Result<bool, StorageError> {
+        let exists = self.lists.contains_key(&name);
+        if exists {
+            self.list = name;
+        }
+        Ok(exists)
+    }
+    async fn get_lists(&mut self) -> Result<Vec<TodoList>, StorageError> {
+        Ok(self
+            .lists
+            .iter()
+            .map(|(name, todos)| {
+                let done = todos.iter().filter(|todo| todo.done).count() as u64;
+                TodoList {
+                    name: name.clone(),
+                    open: todos.iter().count() as u64 - done,
+                    done,
+                }
+            })
+            .collect())
+    }
+    async fn create_list(&mut self, name: ListName) -> Result<bool, StorageError> {
+        if self.lists.contains_key(&name) {
+            return Ok(false);
+        }
+        self.lists.insert(name, Todos::new(vec![]));
+        Ok(true)
+    }
+    async fn rename_list(&mut self, from: ListName, to: ListName) -> Result<u64, StorageError> {
+        if self.lists.contains_key(&to) {
+            return Ok(0);
+        }
+        match self.lists.remove(&from) {
+            Some(todos) => {
+                self.lists.insert(to.clone(), todos);
+                if let Some(trashed) = self.trash.remove(&from) {
+                    self.trash.insert(to.clone(), trashed);
+                }
+                if let Some(archived) = self.archive.remove(&from) {
+                    self.archive.insert(to.clone(), archived);
+                }
+                if self.list == from {
+                    self.list = to;
+                }
+                Ok(1)
+            }
+            None => Ok(0),
+        }
+    }
+    async fn delete_list(&mut self, name: ListName) -> Result<u64, StorageError> {
+        match self.lists.remove(&name) {
+            Some(todos) => {
+                let trashed = self
+                    .trash
+                    .remove(&name)
+                    .unwrap_or_else(|| Todos::new(vec![]));
+                let archived = self
+                    .archive
+                    .remove(&name)
+                    .unwrap_or_else(|| Todos::new(vec![]));
+                let removed: Vec<Uuid> = todos
+                    .iter()
+                    .chain(trashed.iter())
+                    .chain(archived.iter())
+                    .map(|todo| todo.id)
+                    .collect();
+                self.forget_blockers(&removed);
+                if self.list == name {
+                    self.list = ListName::default();
+                }
+                Ok(1)
+            }
+            None => Ok(0),
+        }
+    }
+    async fn move_todo(&mut self, todo_uuid: Uuid, list: ListName) -> Result<u64, StorageError> {
+        if !self.lists.contains_key(&list) {
+            return Ok(0);
+        }
+        let todos = self.todos();
+        if todos.position(todo_uuid).is_none() {
+            return Ok(0);
+        }
+        let mut moving: Vec<Uuid> = todos
+            .descendants(todo_uuid)
+            .iter()
+            .map(|todo| todo.id)
+            .collect();
+        moving.insert(0, todo_uuid);
+        for id in &moving {
+            let todos = self.todos();
+            let Some(index) = todos.position(*id) else {
+                continue;
+            };
+            let mut todo = todos.remove(index);
+            todo.updated_at = timestamp();
+            if todo.id == todo_uuid {
+                todo.parent = None;
+            }
+            self.lists
+                .entry(list.clone())
+                .or_insert_with(|| Todos::new(vec![]))
+                .push(todo);
+        }
+        Ok(moving.len() as u64)
+    }
+    async fn add_todo(&mut self, todo: Todo) -> Result<(), StorageError> {
+        if self.exists(todo.id) {
+            return Err(StorageError::DuplicateTodo(todo.id));
+        }
+        if let Some(parent) = todo.parent.filter(|parent| !self.exists(*parent)) {
+            return Err(StorageError::MissingParent(parent));
+        }
+        self.todos().push(todo);
+        Ok(())
+    }
+    async fn get_todo_list(&mut self) -> Result<Todos, StorageError> {
+        let todos = self.todos().clone();
+        Ok(self.without_hidden_blockers(&todos))
+    }
+    async fn clear_todo_list(&mut self) -> Result<(), StorageError> {
+        let removed: Vec<Uuid> = self.todos().iter().map(|todo| todo.id).collect();
+        self.trash_todos(&removed);
+        Ok(())
+    }
+    async fn remove_todo(&mut self, todo_uuid: Uuid) -> Result<u64, StorageError> {
+        let todos = self.todos();
+        if todos.position(todo_uuid).is_none() {
+            return Ok(0);
+        }
+        let mut removing: Vec<Uuid> = todos
+            .descendants(todo_uuid)
+            .iter()
+            .map(|todo| todo.id)
+            .collect();
+        removing.push(todo_uuid);
+        // Kept in list order, which the trash falls back on.
+        let removing: Vec<Uuid> = todos
+            .iter()
+            .map(|todo| todo.id)
+            .filter(|id| removing.contains(id))
+            .collect();
+        self.trash_todos(&removing);
+        Ok(removing.len() as u64)
+    }
+    async fn mark_todo_done(&mut self, todo_uuid: Uuid) -> Result<u64, StorageError> {
+        Ok(self.modify(todo_uuid, |todo| {
+            todo.done = true;
+            todo.completed_at.get_or_insert_with(timestamp);
+        }))
+    }
+    async fn mark_todo_undone(&mut self, todo_uuid: Uuid) -> Result<u64, StorageError> {
+        Ok(self.modify(todo_uuid, |todo| {
+            todo.done = false;
+            todo.completed_at = None;
+        }))
+    }
+    async fn get_todo(&mut self, todo_uuid: Uuid) -> Result<Option<Todo>, StorageError> {
+        let todo = self
+            .todos()
+            .iter()
+            .find(|todo| todo.id == todo_uuid)
+            .cloned();
+        Ok(todo.and_then(|todo| {
+            self.without_hidden_blockers(&Todos::new(vec![todo]))
+                .get(0)
+                .cloned()
+        }))
+    }
+    async fn update_todo(&mut self, todo_uuid: Uuid, message: String) -> Result<u64, StorageError> {
+        Ok(self.modify(todo_uuid, |todo| todo.message = message))
+    }
+    async fn set_priority(
+        &mut self,
+        todo_uuid: Uuid,
+        priority: Priority,
+    ) -> Result<u64, StorageError> {
+        Ok(self.modify(todo_uuid, |todo| todo.priority = priority))
+    }
+    async fn set_due(&mut self, todo_uuid: Uuid, due: Option<Due>) -> Result<u64, StorageError> {
+        Ok(self.modify(todo_uuid, |todo| todo.due = due))
+    }
+    async fn update_tags(
+        &mut self,
+        todo_uuid: Uuid,
+        add: Vec<Tag>,
+        remove: Vec<Tag>,
+    ) -> Result<u64, StorageError> {
+        Ok(self.modify(todo_uuid, |todo| todo.update_tags(&add, &remove)))
+    }
+    async fn get_todos_tagged(&mut self, tag: Tag) -> Result<Todos, StorageError> {
+        let tagged = Todos::new(
+            self.todos()
+                .iter()
+                .filter(|todo| todo.tags.contains(&tag))
+                .cloned()
+                .collect(),
+        );
+        Ok(self.without_hidden_blockers(&tagged))
+    }
+    async fn set_parent(
+        &mut self,
+        todo_uuid: Uuid,
+        parent: Option<Uuid>,
+    ) -> Result<u64, StorageError> {
+        Ok(self.modify(todo_uuid, |todo| todo.parent = parent))
+    }
+    async fn set_recurrence(
+        &mut self,
+        todo_uuid: Uuid,
+        recurrence: Option<Recurrence>,
+    ) -> Result<u64, StorageError> {
+        Ok(self.modify(todo_uuid, |todo| todo.recurrence = recurrence))
+    }
+    async fn add_blocker(&mut self, todo_uuid: Uuid, blocker: Uuid) -> Result<bool, StorageError> {
+        let todos = self.todos();
+        if todos.position(todo_uuid).is_none() || todos.position(blocker).is_none() {
+            return Ok(false);
+        }
+        let mut chain = vec![blocker];
+        let mut index = 0;
+        while let Some(&id) = chain.get(index) {
+            if id == todo_uuid {
+                return Ok(false);
+            }
+            let waits_for = todos
+                .iter()
+                .find(|todo| todo.id == id)
+                .map(|todo| todo.blocked_by.clone())
+                .unwrap_or_default();
+            for id in waits_for {
+                if !chain.contains(&id) {
+                    chain.push(id);
+                }
+            }
+            index += 1;
+        }
+        self.modify(todo_uuid, |todo| {
+            todo.blocked_by.push(blocker);
+            todo.blocked_by.sort();
+            todo.blocked_by.dedup();
+        });
+        Ok(true)
+    }
+    async fn remove_blocker(
+        &mut self,
+        todo_uuid: Uuid,
+        blocker: Uuid,
+    ) -> Result<u64, StorageError> {
+        let todos = self.todos();
+        let blocked = todos
+            .position(todo_uuid)
+            .and_then(|index| todos.get(index))
+            .is_some_and(|todo| todo.blocked_by.contains(&blocker));
+        if !blocked {
+            return Ok(0);
+        }
+        Ok(self.modify(todo_uuid, |todo| {
+            todo.blocked_by.retain(|id| *id != blocker)
+        }))
+    }
+    async fn reorder_todo(
+        &mut self,
+        todo_uuid: Uuid,
+        before: Option<Uuid>,
+    ) -> Result<u64, StorageError> {
+        let todos = self.todos();
+        let Some(index) = todos.position(todo_uuid) else {
+            return Ok(0);
+        };
+        let todo = todos.remove(index);
+        let target = match before {
+            Some(before) => todos.position(before),
+            None => Some(todos.iter().count()),
+        };
+        match target {
+            Some(target) => {
+                todos.insert(target, todo);
+                Ok(1)
+            }
+            None => {
+                todos.insert(index, todo);
+                Ok(0)
+            }
+        }
+    }
+    async fn get_trash(&mut self) -> Result<Todos, StorageError> {
+        let mut trashed: Vec<Todo> = self.trashed().iter().cloned().collect();
+        // Stable, so todos removed together keep their list order.
+        trashed.sort_by_key(|todo| std::cmp::Reverse(todo.deleted_at));
+        Ok(self.without_hidden_blockers(&Todos::new(trashed)))
+    }
+    async fn restore_todo(&mut self, todo_uuid: Uuid) -> Result<u64, StorageError> {
+        let trashed = self.trashed();
+        let Some(deleted_at) = trashed
+            .iter()
+            .find(|todo| todo.id == todo_uuid)
+            .and_then(|todo| todo.deleted_at)
+        else {
+            return Ok(0);
+        };
+        // Subtasks removed at the same moment came along with the todo.
+        let restored = take_subtree(trashed, todo_uuid, |todo| {
+            todo.deleted_at == Some(deleted_at)
+        });
+        let restored = restored
+            .into_iter()
+            .map(|todo| Todo {
+                deleted_at: None,
+                ..todo
+            })
+            .collect::<Vec<_>>();
+        let number_modified = restored.len() as u64;
+        self.bring_back(todo_uuid, restored);
+        Ok(number_modified)
+    }
+    async fn purge_trash(&mut self, deleted_before: DateTime<Utc>) -> Result<u64, StorageError> {
+        let trashed = self.trashed();
+        let purging: Vec<Uuid> = trashed
+            .iter()
+            .filter(|todo| todo.deleted_at.is_some_and(|at| at <= deleted_before))
+            .map(|todo| todo.id)
+            .collect();
+        for id in &purging {
+            if let Some(index) = trashed.position(*id) {
+                trashed.remove(index);
+            }
+        }
+        self.positions.retain(|id, _| !purging.contains(id));
+        self.forget_blockers(&purging);
+        Ok(purging.len() as u64)
+    }
+    async fn get_archive(&mut self) -> Result<Todos, StorageError> {
+        let mut archived: Vec<Todo> = self.archived().iter().cloned().collect();
+        // Stable, so todos archived together keep their list order.
+        archived.sort_by_key(|todo| std::cmp::Reverse(todo.archived_at));
+        Ok(self.without_hidden_blockers(&Todos::new(archived)))
+    }
+    async fn archive_done(&mut self, untouched_since: DateTime<Utc>) -> Result<u64, StorageError> {
+        let todos = self.todos();
+        let finished = |todo: &Todo| todo.done && todo.updated_at <= untouched_since;
+        let mut archiving = Vec::new();
+        for root in todos.iter().filter(|todo| todo.parent.is_none()) {
+            let subtasks = todos.descendants(root.id);
+            if finished(root) && subtasks.iter().all(|todo| finished(todo)) {
+                archiving.push(root.id);
+                archiving.extend(subtasks.iter().map(|todo| todo.id));
+            }
+        }
+        // Kept in list order, which the archive falls back on.
+        let archived_at = timestamp();
+        let archived = self.take_out(&archiving);
+        let count = archived.len() as u64;
+        for todo in archived {
+            self.archived().push(Todo {
+                archived_at: Some(archived_at),
+                ..todo
+            });
+        }
+        Ok(count)
+    }
+    async fn unarchive_todo(&mut self, todo_uuid: Uuid) -> Result<u64, StorageError> {
+        let archived = self.archived();
+        let Some(archived_at) = archived
+            .iter()
+            .find(|todo| todo.id == todo_uuid)
+            .and_then(|todo| todo.archived_at)
+        else {
+            return Ok(0);
+        };
+        // Subtasks archived at the same moment came along with the todo.
+        let updated_at = timestamp();
+        let unarchived = take_subtree(archived, todo_uuid, |todo| {
+            todo.archived_at == Some(archived_at)
+        });
+        let unarchived = unarchived
+            .into_iter()
+            .map(|todo| Todo {
+                archived_at: None,
+                updated_at,
+                ..todo
+            })
+            .collect::<Vec<_>>();
+        let number_modified = unarchived.len() as u64;
+        self.bring_back(todo_uuid, unarchived);
+        Ok(number_modified)
+    }
+}
+
+// Takes the todo with the given id out of `todos`, along with the subtasks below it that
+// `along` accepts, parents first.
+fn take_subtree(todos: &mut Todos, todo_uuid: Uuid, along: impl Fn(&Todo) -> bool) -> Vec<Todo> {
+    let mut taking = vec![todo_uuid];
+    let mut index = 0;
+    while let Some(&id) = taking.get(index) {
+        taking.extend(
+            todos
+                .iter()
+                .filter(|todo| todo.parent == Some(id) && along(todo))
+                .map(|todo| todo.id),
+        );
+        index += 1;
+    }
+    let mut taken = Vec::with_capacity(taking.len());
+    for id in &taking {
+        if let Some(index) = todos.position(*id) {
+            taken.push(todos.remove(index));
+        }
+    }
+    taken
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::todo::mocks::*;
+    use crate::repository::todo::conformance::{storage_conformance_tests, BoxedStorage};
+    use factori::create;
+    use std::future::Future;
+
+    async fn with_storage<F, Fut>(check: F)
+    where
+        F: FnOnce(BoxedStorage) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        check(Box::new(InMemoryStorage::new())).await;
+    }
+
+    storage_conformance_tests!(with_storage);
+
+    #[tokio::test]
+    async fn test_remove_todo() {
+        let mut todo_storage = InMemoryStorage::new();
+        let todo_list = create!(Todos, number_todos: 3);
+        let todo = todo_list.get(2).unwrap();
+
+        for todo in todo_list.iter() {
+            todo_storage.add_todo(todo.clone()).await.unwrap();
+        }
+        let number_modified = todo_storage.remove_todo(todo.id).await.unwrap();
+        let mut todo_list = todo_storage.get_todo_list().await.unwrap();
+
+        assert_eq!(number_modified, 1);
+        assert_eq!(todo_list.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_remove_missing_todo() {
+        let mut todo_storage = InMemoryStorage::new();
+
+        let number_modified = todo_storage.remove_todo(Uuid::new_v4()).await.unwrap();
+
+        assert_eq!(number_modified, 0);
+    }
+
+    #[tokio::test]
+    async fn test_mark_todo_done() {
+        let mut todo_storage = InMemoryStorage::new();
+        let todo = Todo::new("Test mark done".to_string(), Uuid::new_v4());
+
+        todo_storage.add_todo(todo.clone()).await.unwrap();
+        let number_modified = todo_storage.mark_todo_done(todo.id).await.unwrap();
+        let todo_list = todo_storage.get_todo_list().await.unwrap();
+
+        assert_eq!(number_modified, 1);
+        assert!(todo_list.get(0).unwrap().done);
+    }
+}
@@ -0,0 +1,746 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rusqlite::types::Type;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::path::Path;
+use std::sync::{Mutex, PoisonError};
+use uuid::Uuid;
+
+use crate::db::migrations;
+use crate::domain::due::Due;
+use crate::domain::list::{InvalidListName, ListName, TodoList};
+use crate::domain::priority::{InvalidPriority, Priority};
+use crate::domain::recurrence::{InvalidRecurrence, Recurrence};
+use crate::domain::tag::Tag;
+use crate::domain::todo::{timestamp, Todo};
+use crate::domain::todos::Todos;
+use crate::repository::todo::error::StorageError;
+use crate::repository::todo::{reposition, Storage, POSITION_GAP};
+
+// Every todo column plus its tag names and the ids of blockers not in the trash, sorted and
+// separated by spaces.
+const SELECT_TODOS: &str = "SELECT todos.*, \
+     (SELECT group_concat(name, ' ') FROM ( \
+         SELECT tags.name FROM tags JOIN todo_tags ON todo_tags.tag_id = tags.id \
+         WHERE todo_tags.todo_id = todos.id ORDER BY tags.name)) AS tags, \
+     (SELECT group_concat(blocker_id, ' ') FROM ( \
+         SELECT blocker_id FROM todo_dependencies \
+         JOIN todos AS blocker ON blocker.id = todo_dependencies.blocker_id \
+         WHERE todo_dependencies.todo_id = todos.id AND blocker.deleted_at IS NULL \
+         AND blocker.archived_at IS NULL \
+         ORDER BY blocker_id)) AS blocked_by \
+     FROM todos";
+
+const ORDER_TODOS: &str = "ORDER BY todos.position, todos.created_at, todos.id";
+
+// Whether `?2` already waits for `?1`, following the chain of blockers.
+const WAITS_FOR: &str = "WITH RECURSIVE chain(id) AS ( \
+         SELECT ?2 \
+         UNION SELECT todo_dependencies.blocker_id FROM todo_dependencies \
+         JOIN chain ON todo_dependencies.todo_id = chain.id \
+     ) \
+     SELECT EXISTS(SELECT 1 FROM chain WHERE id = ?1)";
+
+pub struct SqliteTodoRepository {
+    connection: Mutex<Connection>,
+    list: ListName,
+}
+
+impl SqliteTodoRepository {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StorageError> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self, StorageError> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(mut connection: Connection) -> Result<Self, StorageError> {
+        // Tag links and lists rely on ON DELETE and ON UPDATE CASCADE.
+        connection.pragma_update(None, "foreign_keys", true)?;
+        migrations::sqlite::migrate_up(&mut connection)?;
+        Ok(SqliteTodoRepository {
+            connection: Mutex::new(connection),
+            list: ListName::default(),
+        })
+    }
+
+    fn connection(&mut self) -> &mut Connection {
+        self.connection
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[async_trait]
+impl Storage for SqliteTodoRepository {
+    fn current_list(&self) -> ListName {
+        self.list.clone()
+    }
+    async fn switch_list(&mut self, name: ListName) -> Result<bool, StorageError> {
+        let exists = self
+            .connection()
+            .query_row(
+                "SELECT 1 FROM lists WHERE name=?1",
+                params![name.as_str()],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some();
+        if exists {
+            self.list = name;
+        }
+        Ok(exists)
+    }
+    async fn get_lists(&mut self) -> Result<Vec<TodoList>, StorageError> {
+        let connection = self.connection();
+        let mut statement = connection.prepare(
+            "SELECT lists.name, \
+                 count(todos.id) FILTER (WHERE NOT todos.done) AS open, \
+                 count(todos.id) FILTER (WHERE todos.done) AS done \
+             FROM lists LEFT JOIN todos \
+                 ON todos.list = lists.name AND todos.deleted_at IS NULL \
+                 AND todos.archived_at IS NULL \
+             GROUP BY lists.name ORDER BY lists.name",
+        )?;
+        let lists = statement
+            .query_map([], |row| {
+                let name: String = row.get("name")?;
+                Ok(TodoList {
+                    name: name.parse().map_err(|error: InvalidListName| {
+                        rusqlite::Error::FromSqlConversionFailure(0, Type::Text, Box::new(error))
+                    })?,
+                    open: row.get("open")?,
+                    done: row.get("done")?,
+                })
+            })?
+            .collect::<Result<Vec<TodoList>, _>>()?;
+        Ok(lists)
+    }
+    async fn create_list(&mut self, name: ListName) -> Result<bool, StorageError> {
+        let created = self.connection().execute(
+            "INSERT OR IGNORE INTO lists(name) VALUES(?1)",
+            params![name.as_str()],
+        )?;
+        Ok(created == 1)
+    }
+    async fn rename_list(&mut self, from: ListName, to: ListName) -> Result<u64, StorageError> {
+        let number_modified = self.connection().execute(
+            "UPDATE lists SET name=?1 WHERE name=?2",
+            params![to.as_str(), from.as_str()],
+        )?;
+        if number_modified > 0 && self.list == from {
+            self.list = to;
+        }
+        Ok(number_modified as u64)
+    }
+    async fn delete_list(&mut self, name: ListName) -> Result<u64, StorageError> {
+        let number_modified = self
+            .connection()
+            .execute("DELETE FROM lists WHERE name=?1", params![name.as_str()])?;
+        if number_modified > 0 && self.list == name {
+            self.list = ListName::default();
+        }
+        Ok(number_modified as u64)
+    }
+    async fn move_todo(&mut self, todo_uuid: Uuid, list: ListName) -> Result<u64, StorageError> {
+        let current = self.list.to_string();
+        let number_modified = self.connection().execute(
+            "WITH RECURSIVE subtree(id) AS ( \
+                 SELECT id FROM todos WHERE id=?3 AND list=?4 AND deleted_at IS NULL \
+                 AND archived_at IS NULL \
+                 UNION SELECT todos.id FROM todos JOIN subtree ON todos.parent_id = subtree.id \
+                 WHERE todos.deleted_at IS NULL AND todos.archived_at IS NULL \
+             ) \
+             UPDATE todos SET list=?1, updated_at=?2, \
+                 parent_id = CASE WHEN id=?3 THEN NULL ELSE parent_id END, \
+                 position = position + ?5 \
+                     + (SELECT COALESCE(max(position), 0) FROM todos WHERE list=?1) \
+                     - (SELECT min(position) FROM todos WHERE id IN (SELECT id FROM subtree)) \
+             WHERE id IN (SELECT id FROM subtree) AND EXISTS (SELECT 1 FROM lists WHERE name=?1)",
+            params![
+                list.as_str(),
+                timestamp(),
+                todo_uuid.to_string(),
+                current,
+                POSITION_GAP
+            ],
+        )?;
+        Ok(number_modified as u64)
+    }
+    async fn add_todo(&mut self, todo: Todo) -> Result<(), StorageError> {
+        let list = self.list.to_string();
+        let transaction = self.connection().transaction()?;
+        transaction.execute(
+            "INSERT INTO todos(message, id, priority, due_date, due_time, created_at, updated_at, completed_at, list, parent_id, recurrence, position, origin_id) \
+             VALUES(?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, \
+                 (SELECT COALESCE(max(position), 0) + ?12 FROM todos WHERE list=?9), ?13)",
+            params![
+                todo.message,
+                todo.id.to_string(),
+                todo.priority.to_string(),
+                todo.due.map(|due| due.date),
+                todo.due.and_then(|due| due.time),
+                todo.created_at,
+                todo.updated_at,
+                todo.completed_at,
+                list,
+                todo.parent.map(|parent| parent.to_string()),
+                todo.recurrence.map(|recurrence| recurrence.to_string()),
+                POSITION_GAP,
+                todo.origin.map(|origin| origin.to_string()),
+            ],
+        )?;
+        insert_tags(&transaction, todo.id, &todo.tags)?;
+        for blocker in &todo.blocked_by {
+            transaction.execute(
+                "INSERT INTO todo_dependencies(todo_id, blocker_id) VALUES(?1, ?2)",
+                params![todo.id.to_string(), blocker.to_string()],
+            )?;
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+    async fn get_todo_list(&mut self) -> Result<Todos, StorageError> {
+        let list = self.list.to_string();
+        let connection = self.connection();
+        let mut statement = connection.prepare(&format!(
+            "{SELECT_TODOS} WHERE todos.list=?1 AND todos.deleted_at IS NULL \
+             AND todos.archived_at IS NULL {ORDER_TODOS}"
+        ))?;
+        let todos = statement
+            .query_map(params![list], get_todo_from_sqlite)?
+            .collect::<Result<Vec<Todo>, _>>()?;
+
+        Ok(Todos::new(todos))
+    }
+    async fn clear_todo_list(&mut self) -> Result<(), StorageError> {
+        let list = self.list.to_string();
+        self.connection().execute(
+            "UPDATE todos SET deleted_at=?1 WHERE list=?2 AND deleted_at IS NULL \
+             AND archived_at IS NULL",
+            params![timestamp(), list],
+        )?;
+        Ok(())
+    }
+    async fn remove_todo(&mut self, todo_uuid: Uuid) -> Result<u64, StorageError> {
+        let list = self.list.to_string();
+        let number_modified = self.connection().execute(
+            "WITH RECURSIVE subtree(id) AS ( \
+                 SELECT id FROM todos WHERE id=?2 AND list=?3 AND deleted_at IS NULL \
+                 AND archived_at IS NULL \
+                 UNION SELECT todos.id FROM todos JOIN subtree ON todos.parent_id = subtree.id \
+                 WHERE todos.deleted_at IS NULL AND todos.archived_at IS NULL \
+             ) \
+             UPDATE todos SET deleted_at=?1 WHERE id IN (SELECT id FROM subtree)",
+            params![timestamp(), todo_uuid.to_string(), list],
+        )?;
+        Ok(number_modified as u64)
+    }
+    async fn mark_todo_done(&mut self, todo_uuid: Uuid) -> Result<u64, StorageError> {
+        let list = self.list.to_string();
+        let number_modified = self.connection().execute(
+            "UPDATE todos SET done=TRUE, completed_at=COALESCE(completed_at, ?1), updated_at=?1 \
+             WHERE id=?2 AND list=?3 AND deleted_at IS NULL AND archived_at IS NULL",
+            params![timestamp(), todo_uuid.to_string(), list],
+        )?;
+        Ok(number_modified as u64)
+    }
+    async fn mark_todo_undone(&mut self, todo_uuid: Uuid) -> Result<u64, StorageError> {
+        let list = self.list.to_string();
+        let number_modified = self.connection().execute(
+            "UPDATE todos SET done=FALSE, completed_at=NULL, updated_at=?1 \
+             WHERE id=?2 AND list=?3 AND deleted_at IS NULL AND archived_at IS NULL",
+            params![timestamp(), todo_uuid.to_string(), list],
+        )?;
+        Ok(number_modified as u64)
+    }
+    async fn get_todo(&mut self, todo_uuid: Uuid) -> Result<Option<Todo>, StorageError> {
+        let list = self.list.to_string();
+        let todo = self
+            .connection()
+            .query_row(
+                &format!(
+                    "{SELECT_TODOS} \
+                     WHERE todos.id=?1 AND todos.list=?2 AND todos.deleted_at IS NULL \
+                     AND todos.archived_at IS NULL"
+                ),
+                params![todo_uuid.to_string(), list],
+                get_todo_from_sqlite,
+            )
+            .optional()?;
+        Ok(todo)
+    }
+    async fn update_todo(&mut self, todo_uuid: Uuid, message: String) -> Result<u64, StorageError> {
+        let list = self.list.to_string();
+        let number_modified = self.connection().execute(
+            "UPDATE todos SET message=?1, updated_at=?2 \
+             WHERE id=?3 AND list=?4 AND deleted_at IS NULL AND archived_at IS NULL",
+            params![message, timestamp(), todo_uuid.to_string(), list],
+        )?;
+        Ok(number_modified as u64)
+    }
+    async fn set_priority(
+        &mut self,
+        todo_uuid: Uuid,
+        priority: Priority,
+    ) -> Result<u64, StorageError> {
+        let list = self.list.to_string();
+        let number_modified = self.connection().execute(
+            "UPDATE todos SET priority=?1, updated_at=?2 \
+             WHERE id=?3 AND list=?4 AND deleted_at IS NULL AND archived_at IS NULL",
+            params![
+                priority.to_string(),
+                timestamp(),
+                todo_uuid.to_string(),
+                list
+            ],
+        )?;
+        Ok(number_modified as u64)
+    }
+    async fn set_due(&mut self, todo_uuid: Uuid, due: Option<Due>) -> Result<u64, StorageError> {
+        let list = self.list.to_string();
+        let number_modified = self.connection().execute(
+            "UPDATE todos SET due_date=?1, due_time=?2, updated_at=?3 \
+             WHERE id=?4 AND list=?5 AND deleted_at IS NULL AND archived_at IS NULL",
+            params![
+                due.map(|due| due.date),
+                due.and_then(|due| due.time),
+                timestamp(),
+                todo_uuid.to_string(),
+                list
+            ],
+        )?;
+        Ok(number_modified as u64)
+    }
+    async fn update_tags(
+        &mut self,
+        todo_uuid: Uuid,
+        add: Vec<Tag>,
+        remove: Vec<Tag>,
+    ) -> Result<u64, StorageError> {
+        let list = self.list.to_string();
+        let transaction = self.connection().transaction()?;
+        let number_modified = transaction.execute(
+            "UPDATE todos SET updated_at=?1 WHERE id=?2 AND list=?3 AND deleted_at IS NULL \
+             AND archived_at IS NULL",
+            params![timestamp(), todo_uuid.to_string(), list],
+        )?;
+        if number_modified == 0 {
+            return Ok(0);
+        }
+        insert_tags(&transaction, todo_uuid, &add)?;
+        for tag in &remove {
+            transaction.execute(
+                "DELETE FROM todo_tags WHERE todo_id=?1 AND tag_id IN (SELECT id FROM tags WHERE name=?2)",
+                params![todo_uuid.to_string(), tag.name()],
+            )?;
+        }
+        transaction.commit()?;
+        Ok(number_modified as u64)
+    }
+    async fn get_todos_tagged(&mut self, tag: Tag) -> Result<Todos, StorageError> {
+        let list = self.list.to_string();
+        let connection = self.connection();
+        let mut statement = connection.prepare(&format!(
+            "{SELECT_TODOS} WHERE todos.list = ?2 AND todos.deleted_at IS NULL \
+             AND todos.archived_at IS NULL AND EXISTS ( \
+                 SELECT 1 FROM todo_tags JOIN tags ON tags.id = todo_tags.tag_id \
+                 WHERE todo_tags.todo_id = todos.id AND tags.name = ?1) {ORDER_TODOS}"
+        ))?;
+        let todos = statement
+            .query_map(params![tag.name(), list], get_todo_from_sqlite)?
+            .collect::<Result<Vec<Todo>, _>>()?;
+
+        Ok(Todos::new(todos))
+    }
+    async fn set_parent(
+        &mut self,
+        todo_uuid: Uuid,
+        parent: Option<Uuid>,
+    ) -> Result<u64, StorageError> {
+        let list = self.list.to_string();
+        let number_modified = self.connection().execute(
+            "UPDATE todos SET parent_id=?1, updated_at=?2 \
+             WHERE id=?3 AND list=?4 AND deleted_at IS NULL AND archived_at IS NULL",
+            params![
+                parent.map(|parent| parent.to_string()),
+                timestamp(),
+                todo_uuid.to_string(),
+                list
+            ],
+        )?;
+        Ok(number_modified as u64)
+    }
+    async fn set_recurrence(
+        &mut self,
+        todo_uuid: Uuid,
+        recurrence: Option<Recurrence>,
+    ) -> Result<u64, StorageError> {
+        let list = self.list.to_string();
+        let number_modified = self.connection().execute(
+            "UPDATE todos SET recurrence=?1, updated_at=?2 \
+             WHERE id=?3 AND list=?4 AND deleted_at IS NULL AND archived_at IS NULL",
+            params![
+                recurrence.map(|recurrence| recurrence.to_string()),
+                timestamp(),
+                todo_uuid.to_string(),
+                list
+            ],
+        )?;
+        Ok(number_modified as u64)
+    }
+    async fn add_blocker(&mut self, todo_uuid: Uuid, blocker: Uuid) -> Result<bool, StorageError> {
+        let list = self.list.to_string();
+        let (todo_uuid, blocker) = (todo_uuid.to_string(), blocker.to_string());
+        let transaction = self.connection().transaction()?;
+        let on_list: i64 = transaction.query_row(
+            "SELECT count(*) FROM todos WHERE id IN (?1, ?2) AND list=?3 \
+             AND deleted_at IS NULL AND archived_at IS NULL",
+            params![todo_uuid, blocker, list],
+            |row| row.get(0),
+        )?;
+        let cycle: bool =
+            transaction.query_row(WAITS_FOR, params![todo_uuid, blocker], |row| row.get(0))?;
+        if on_list < 2 || cycle {
+            return Ok(false);
+        }
+        transaction.execute(
+            "INSERT OR IGNORE INTO todo_dependencies(todo_id, blocker_id) VALUES(?1, ?2)",
+            params![todo_uuid, blocker],
+        )?;
+        transaction.execute(
+            "UPDATE todos SET updated_at=?1 WHERE id=?2 AND list=?3 AND deleted_at IS NULL \
+             AND archived_at IS NULL",
+            params![timestamp(), todo_uuid, list],
+        )?;
+        transaction.commit()?;
+        Ok(true)
+    }
+    async fn remove_blocker(
+        &mut self,
+        todo_uuid: Uuid,
+        blocker: Uuid,
+    ) -> Result<u64, StorageError> {
+        let list = self.list.to_string();
+        let todo_uuid = todo_uuid.to_string();
+        let transaction = self.connection().transaction()?;
+        let number_modified = transaction.execute(
+            "DELETE FROM todo_dependencies WHERE todo_id=?1 AND blocker_id=?2 \
+             AND todo_id IN (SELECT id FROM todos WHERE list=?3 AND deleted_at IS NULL \
+             AND archived_at IS NULL)",
+            params![todo_uuid, blocker.to_string(), list],
+        )?;
+        if number_modified > 0 {
+            transaction.execute(
+                "UPDATE todos SET updated_at=?1 WHERE id=?2",
+                params![timestamp(), todo_uuid],
+            )?;
+        }
+        transaction.commit()?;
+        Ok(number_modified as u64)
+    }
+    async fn reorder_todo(
+        &mut self,
+        todo_uuid: Uuid,
+        before: Option<Uuid>,
+    ) -> Result<u64, StorageError> {
+        let list = self.list.to_string();
+        let transaction = self.connection().transaction()?;
+        let order = transaction
+            .prepare(&format!(
+                "SELECT id, position FROM todos WHERE list=?1 AND deleted_at IS NULL \
+                 AND archived_at IS NULL {ORDER_TODOS}"
+            ))?
+            .query_map(params![list], |row| {
+                Ok((
+                    parse_uuid(&row.get::<_, String>("id")?)?,
+                    row.get("position")?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        let Some(positions) = reposition(order, todo_uuid, before) else {
+            return Ok(0);
+        };
+        let mut number_modified = 0;
+        for (id, position) in positions {
+            number_modified += transaction.execute(
+                "UPDATE todos SET position=?1 WHERE id=?2 AND list=?3",
+                params![position, id.to_string(), list],
+            )?;
+        }
+        transaction.commit()?;
+        Ok(number_modified as u64)
+    }
+    async fn get_trash(&mut self) -> Result<Todos, StorageError> {
+        let list = self.list.to_string();
+        let connection = self.connection();
+        let mut statement = connection.prepare(&format!(
+            "{SELECT_TODOS} WHERE todos.list=?1 AND todos.deleted_at IS NOT NULL \
+             ORDER BY todos.deleted_at DESC, todos.position, todos.id"
+        ))?;
+        let todos = statement
+            .query_map(params![list], get_todo_from_sqlite)?
+            .collect::<Result<Vec<Todo>, _>>()?;
+
+        Ok(Todos::new(todos))
+    }
+    async fn restore_todo(&mut self, todo_uuid: Uuid) -> Result<u64, StorageError> {
+        let list = self.list.to_string();
+        // Subtasks removed at the same moment came along with the todo.
+        let number_modified = self.connection().execute(
+            "WITH RECURSIVE trashed AS ( \
+                 SELECT id, deleted_at FROM todos \
+                 WHERE id=?1 AND list=?2 AND deleted_at IS NOT NULL \
+             ), subtree(id) AS ( \
+                 SELECT id FROM trashed \
+                 UNION SELECT todos.id FROM todos JOIN subtree ON todos.parent_id = subtree.id \
+                 WHERE todos.deleted_at = (SELECT deleted_at FROM trashed) \
+             ) \
+             UPDATE todos SET deleted_at=NULL, \
+                 parent_id = CASE WHEN id=?1 AND parent_id NOT IN ( \
+                     SELECT id FROM todos WHERE list=?2 AND deleted_at IS NULL \
+                     AND archived_at IS NULL \
+                 ) THEN NULL ELSE parent_id END \
+             WHERE id IN (SELECT id FROM subtree)",
+            params![todo_uuid.to_string(), list],
+        )?;
+        Ok(number_modified as u64)
+    }
+    async fn purge_trash(&mut self, deleted_before: DateTime<Utc>) -> Result<u64, StorageError> {
+        let list = self.list.to_string();
+        let transaction = self.connection().transaction()?;
+        // Counted up front, since subtasks deleted along with their parent are not.
+        let purged: u64 = transaction.query_row(
+            "SELECT count(*) FROM todos WHERE list=?1 AND deleted_at <= ?2",
+            params![list, deleted_before],
+            |row| row.get(0),
+        )?;
+        transaction.execute(
+            "DELETE FROM todos WHERE list=?1 AND deleted_at <= ?2",
+            params![list, deleted_before],
+        )?;
+        transaction.commit()?;
+        Ok(purged)
+    }
+    async fn get_archive(&mut self) -> Result<Todos, StorageError> {
+        let list = self.list.to_string();
+        let connection = self.connection();
+        let mut statement = connection.prepare(&format!(
+            "{SELECT_TODOS} WHERE todos.list=?1 AND todos.archived_at IS NOT NULL \
+             ORDER BY todos.archived_at DESC, todos.position, todos.id"
+        ))?;
+        let todos = statement
+            .query_map(params![list], get_todo_from_sqlite)?
+            .collect::<Result<Vec<Todo>, _>>()?;
+
+        Ok(Todos::new(todos))
+    }
+    async fn archive_done(&mut self, untouched_since: DateTime<Utc>) -> Result<u64, StorageError> {
+        let list = self.list.to_string();
+        let number_modified = self.connection().execute(
+            "WITH RECURSIVE tree(root, id) AS ( \
+                 SELECT id, id FROM todos WHERE list=?1 AND parent_id IS NULL \
+                 AND deleted_at IS NULL AND archived_at IS NULL \
+                 UNION SELECT tree.root, todos.id FROM todos JOIN tree ON todos.parent_id = tree.id \
+                 WHERE todos.deleted_at IS NULL AND todos.archived_at IS NULL \
+             ), finished(root) AS ( \
+                 SELECT tree.root FROM tree JOIN todos ON todos.id = tree.id GROUP BY tree.root \
+                 HAVING min(todos.done AND todos.updated_at <= ?2) \
+             ) \
+             UPDATE todos SET archived_at=?3 \
+             WHERE id IN (SELECT id FROM tree WHERE root IN (SELECT root FROM finished))",
+            params![list, untouched_since, timestamp()],
+        )?;
+        Ok(number_modified as u64)
+    }
+    async fn unarchive_todo(&mut self, todo_uuid: Uuid) -> Result<u64, StorageError> {
+        let list = self.list.to_string();
+        // Subtasks archived at the same moment came along with the todo.
+        let number_modified = self.connection().execute(
+            "WITH RECURSIVE archived AS ( \
+                 SELECT id, archived_at FROM todos \
+                 WHERE id=?1 AND list=?2 AND archived_at IS NOT NULL \
+             ), subtree(id) AS ( \
+                 SELECT id FROM archived \
+                 UNION SELECT todos.id FROM todos JOIN subtree ON todos.parent_id = subtree.id \
+                 WHERE todos.archived_at = (SELECT archived_at FROM archived) \
+             ) \
+             UPDATE todos SET archived_at=NULL, updated_at=?3, \
+                 parent_id = CASE WHEN id=?1 AND parent_id NOT IN ( \
+                     SELECT id FROM todos WHERE list=?2 AND deleted_at IS NULL \
+                     AND archived_at IS NULL \
+                 ) THEN NULL ELSE parent_id END \
+             WHERE id IN (SELECT id FROM subtree)",
+            params![todo_uuid.to_string(), list, timestamp()],
+        )?;
+        Ok(number_modified as u64)
+    }
+}
+
+fn insert_tags(connection: &Connection, todo_uuid: Uuid, tags: &[Tag]) -> rusqlite::Result<()> {
+    for tag in tags {
+        connection.execute(
+            "INSERT OR IGNORE INTO tags(name) VALUES(?1)",
+            params![tag.name()],
+        )?;
+        connection.execute(
+            "INSERT OR IGNORE INTO todo_tags(todo_id, tag_id) SELECT ?1, id FROM tags WHERE name=?2",
+            params![todo_uuid.to_string(), tag.name()],
+        )?;
+    }
+    Ok(())
+}
+
+fn parse_uuid(id: &str) -> rusqlite::Result<Uuid> {
+    Uuid::parse_str(id)
+        .map_err(|error| rusqlite::Error::FromSqlConversionFailure(0, Type::Text, Box::new(error)))
+}
+
+pub fn get_todo_from_sqlite(row: &Row) -> rusqlite::Result<Todo> {
+    let id = parse_uuid(&row.get::<_, String>("id")?)?;
+    let parent = match row.get::<_, Option<String>>("parent_id")? {
+        Some(parent) => Some(parse_uuid(&parent)?),
+        None => None,
+    };
+    let origin = match row.get::<_, Option<String>>("origin_id")? {
+        Some(origin) => Some(parse_uuid(&origin)?),
+        None => None,
+    };
+    let priority: String = row.get("priority")?;
+    let priority = priority.parse().map_err(|error: InvalidPriority| {
+        rusqlite::Error::FromSqlConversionFailure(3, Type::Text, Box::new(error))
+    })?;
+
+    let recurrence = match row.get::<_, Option<String>>("recurrence")? {
+        Some(recurrence) => Some(recurrence.parse().map_err(|error: InvalidRecurrence| {
+            rusqlite::Error::FromSqlConversionFailure(0, Type::Text, Box::new(error))
+        })?),
+        None => None,
+    };
+
+    let due_date: Option<_> = row.get("due_date")?;
+    let due = match due_date {
+        Some(date) => Some(Due {
+            date,
+            time: row.get("due_time")?,
+        }),
+        None => None,
+    };
+
+    Ok(Todo {
+        done: row.get("done")?,
+        message: row.get("message")?,
+        id,
+        priority,
+        due,
+        created_at: row.get("created_at")?,
+        updated_at: row.get("updated_at")?,
+        completed_at: row.get("completed_at")?,
+        deleted_at: row.get("deleted_at")?,
+        archived_at: row.get("archived_at")?,
+        tags: row
+            .get::<_, Option<String>>("tags")?
+            .unwrap_or_default()
+            .split_whitespace()
+            .filter_map(|name| name.parse().ok())
+            .collect(),
+        parent,
+        origin,
+        blocked_by: row
+            .get::<_, Option<String>>("blocked_by")?
+            .unwrap_or_default()
+            .split_whitespace()
+            .map(parse_uuid)
+            .collect::<rusqlite::Result<_>>()?,
+        recurrence,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::todo::mocks::*;
+    use crate::repository::todo::conformance::{storage_conformance_tests, BoxedStorage};
+    use factori::create;
+    use std::future::Future;
+
+    async fn with_storage<F, Fut>(check: F)
+    where
+        F: FnOnce(BoxedStorage) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        check(Box::new(SqliteTodoRepository::open_in_memory().unwrap())).await;
+    }
+
+    storage_conformance_tests!(with_storage);
+
+    #[tokio::test]
+    async fn test_list_todos() {
+        let mut todo_storage = SqliteTodoRepository::open_in_memory().unwrap();
+        let todo = Todo::new("Test todo".to_string(), Uuid::new_v4());
+        todo_storage.add_todo(todo).await.unwrap();
+
+        let mut todo_list = todo_storage.get_todo_list().await.unwrap();
+
+        assert_eq!(todo_list.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_add_todos() {
+        let mut todo_storage = SqliteTodoRepository::open_in_memory().unwrap();
+        let id = Uuid::new_v4();
+        let todo = Todo::new("Test add todo".to_string(), id);
+
+        todo_storage.add_todo(todo.clone()).await.unwrap();
+        let todo_list = todo_storage.get_todo_list().await.unwrap();
+
+        match todo_list.iter().find(|todo| todo.id == id) {
+            Some(todo_returned) => assert_eq!(*todo_returned, todo),
+            None => panic!("Could not find todo created"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remove_todo() {
+        let mut todo_storage = SqliteTodoRepository::open_in_memory().unwrap();
+        let todo_list = create!(Todos, number_todos: 3);
+        let todo = todo_list.get(2).unwrap();
+
+        for todo in todo_list.iter() {
+            todo_storage.add_todo(todo.clone()).await.unwrap();
+        }
+        todo_storage.remove_todo(todo.id).await.unwrap();
+        let mut todo_list = todo_storage.get_todo_list().await.unwrap();
+
+        assert_eq!(todo_list.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_clear_list() {
+        let mut todo_storage = SqliteTodoRepository::open_in_memory().unwrap();
+        let todo_list = create!(Todos, number_todos: 3);
+
+        for todo in todo_list.iter() {
+            todo_storage.add_todo(todo.clone()).await.unwrap();
+        }
+        todo_storage.clear_todo_list().await.unwrap();
+        let mut todo_list = todo_storage.get_todo_list().await.unwrap();
+
+        assert_eq!(todo_list.len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_mark_todo_done() {
+        let mut todo_storage = SqliteTodoRepository::open_in_memory().unwrap();
+        let todo = Todo::new("Test mark done".to_string(), Uuid::new_v4());
+
+        todo_storage.add_todo(todo.clone()).await.unwrap();
+        let number_modified = todo_storage.mark_todo_done(todo.id).await.unwrap();
+        let todo_list = todo_storage.get_todo_list().await.unwrap();
+
+        assert_eq!(number_modified, 1);
+        assert!(todo_list.get(0).unwrap().done);
+    }
+}
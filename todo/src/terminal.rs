@@ -1,81 +1,440 @@
-use crate::domain::{todo::Todo, todos::Todos};
+use crate::domain::{
+    age::Age,
+    due::{Due, DueFilter, DueStatus},
+    list::{ListName, TodoList},
+    priority::Priority,
+    recurrence::Recurrence,
+    subtask::Progress,
+    tag::{tags_in, InvalidTag, Tag},
+    todo::Todo,
+    todo_ref::TodoRef,
+    todos::Todos,
+};
+use chrono::{DateTime, Local, Utc};
 use console::style;
 use error::TerminalError;
+use input::Input;
 use std::{
     fmt::Display,
-    io::{Stdin, Stdout, Write},
+    io::{Stdout, Write},
 };
 use uuid::Uuid;
 pub(crate) mod error;
+pub(crate) mod input;
+pub(crate) mod json;
 
 pub struct Terminal {
-    stdin: Stdin,
+    input: Input,
     stdout: Stdout,
 }
 
 pub enum UserOptions {
     NewTodo(Todo),
-    RemoveTodo(Uuid),
+    NewSubtask(TodoRef, Todo),
+    RemoveTodo(TodoRef),
     ClearList,
     Quit,
     Help,
     ShowList,
     Unrecognized,
-    DoTodo(Uuid),
+    DoTodo(TodoRef),
+    UndoneTodo(TodoRef),
+    EditTodo(TodoRef),
+    SetPriority(TodoRef, Priority),
+    SetDue(TodoRef, Option<Due>),
+    SetRecurrence(TodoRef, Option<Recurrence>),
+    ShowDue(DueFilter),
+    ShowNext,
+    Block(TodoRef, TodoRef),
+    Unblock(TodoRef, TodoRef),
+    TagTodo(TodoRef, Vec<Tag>, Vec<Tag>),
+    ShowTagged(Tag),
+    ShowLists,
+    CreateList(ListName),
+    RenameList(ListName, ListName),
+    DeleteList(ListName),
+    SwitchList(ListName),
+    MoveTodo(TodoRef, ListName),
+    // Positions start at 1, like the ones shown.
+    MoveToPosition(TodoRef, usize),
+    MoveToTop(TodoRef),
+    MoveToBottom(TodoRef),
+    ShowTrash,
+    RestoreTodo(TodoRef),
+    ShowArchive,
+    ArchiveDone,
+    UnarchiveTodo(TodoRef),
+    Undo,
+    Redo,
+    // Without an age the whole trash goes.
+    PurgeTrash(Option<Age>),
 }
 
 #[cfg_attr(test, mockall::automock)]
 pub trait UserInterface {
     fn prompt_new_todo(&mut self) -> Result<Todo, TerminalError>;
-    fn show_todo(&mut self, todo: &Todo) -> Result<(), TerminalError>;
+    fn prompt_edit_todo(&mut self, todo: &Todo) -> Result<String, TerminalError>;
+    fn show_todo(
+        &mut self,
+        position: usize,
+        todo: &Todo,
+        depth: usize,
+        progress: Option<Progress>,
+        blocked: bool,
+    ) -> Result<(), TerminalError>;
     fn alert_unrecognized(&mut self) -> Result<(), TerminalError>;
     fn clear_todo_message(&mut self) -> Result<(), TerminalError>;
-    fn remove_todo_message(&mut self) -> Result<(), TerminalError>;
+    fn add_todo_message(&mut self, todo: &Todo) -> Result<(), TerminalError>;
+    fn remove_todo_message(&mut self, uuid: Uuid, todos_modified: u64)
+        -> Result<(), TerminalError>;
+    fn show_trash(&mut self, trash: Todos) -> Result<(), TerminalError>;
+    fn restore_todo_message(
+        &mut self,
+        uuid: Uuid,
+        todos_modified: u64,
+    ) -> Result<(), TerminalError>;
+    fn purge_message(&mut self, purged: u64) -> Result<(), TerminalError>;
+    fn show_archive(&mut self, archive: Todos) -> Result<(), TerminalError>;
+    fn archive_message(&mut self, archived: u64) -> Result<(), TerminalError>;
+    fn unarchive_todo_message(
+        &mut self,
+        uuid: Uuid,
+        todos_modified: u64,
+    ) -> Result<(), TerminalError>;
+    // `command` made the change that was taken back or made again.
+    fn undo_message(&mut self, command: &str) -> Result<(), TerminalError>;
+    fn redo_message(&mut self, command: &str) -> Result<(), TerminalError>;
+    // There was nothing for `command`, "undo" or "redo", to do.
+    fn history_empty_message(&mut self, command: &str) -> Result<(), TerminalError>;
     fn user_intention(&mut self) -> Result<UserOptions, TerminalError>;
     fn input(&mut self) -> Result<String, TerminalError>;
     fn write_interface(&mut self, string: &dyn Display) -> Result<(), TerminalError>;
     fn show_help(&mut self) -> Result<(), TerminalError>;
     fn show_todo_list(&mut self, todo_list: Todos) -> Result<(), TerminalError>;
-    fn mark_done_message(&mut self) -> Result<(), TerminalError>;
+    fn mark_done_message(&mut self, uuid: Uuid, todos_modified: u64) -> Result<(), TerminalError>;
+    fn mark_undone_message(&mut self, uuid: Uuid, todos_modified: u64)
+        -> Result<(), TerminalError>;
+    fn edit_todo_message(&mut self, uuid: Uuid, todos_modified: u64) -> Result<(), TerminalError>;
+    fn priority_message(
+        &mut self,
+        uuid: Uuid,
+        priority: Priority,
+        todos_modified: u64,
+    ) -> Result<(), TerminalError>;
+    fn due_message(
+        &mut self,
+        uuid: Uuid,
+        due: Option<Due>,
+        todos_modified: u64,
+    ) -> Result<(), TerminalError>;
+    fn recurrence_message(
+        &mut self,
+        uuid: Uuid,
+        recurrence: Option<Recurrence>,
+        todos_modified: u64,
+    ) -> Result<(), TerminalError>;
+    // A recurring todo was done and `next` replaces it.
+    fn next_occurrence_message(&mut self, next: &Todo) -> Result<(), TerminalError>;
+    fn tags_message(
+        &mut self,
+        uuid: Uuid,
+        add: &[Tag],
+        remove: &[Tag],
+        todos_modified: u64,
+    ) -> Result<(), TerminalError>;
+    fn block_message(
+        &mut self,
+        uuid: Uuid,
+        blocker: Uuid,
+        todos_modified: u64,
+    ) -> Result<(), TerminalError>;
+    fn unblock_message(
+        &mut self,
+        uuid: Uuid,
+        blocker: Uuid,
+        todos_modified: u64,
+    ) -> Result<(), TerminalError>;
+    // The todo was done anyway, before the todos it waits for.
+    fn open_blockers_warning(&mut self, uuid: Uuid, blockers: &[Uuid])
+        -> Result<(), TerminalError>;
+    fn show_lists(&mut self, lists: Vec<TodoList>, current: &ListName)
+        -> Result<(), TerminalError>;
+    fn list_created_message(&mut self, name: &ListName) -> Result<(), TerminalError>;
+    fn list_renamed_message(&mut self, from: &ListName, to: &ListName)
+        -> Result<(), TerminalError>;
+    fn list_deleted_message(&mut self, name: &ListName) -> Result<(), TerminalError>;
+    fn switch_list_message(&mut self, name: &ListName) -> Result<(), TerminalError>;
+    fn move_todo_message(
+        &mut self,
+        uuid: Uuid,
+        list: &ListName,
+        todos_modified: u64,
+    ) -> Result<(), TerminalError>;
+    fn reorder_todo_message(
+        &mut self,
+        uuid: Uuid,
+        todos_modified: u64,
+    ) -> Result<(), TerminalError>;
     fn print_error(&mut self, error: &TerminalError);
     fn report_not_found(&mut self) -> Result<(), TerminalError>;
+    fn report_list_not_found(&mut self, name: &ListName) -> Result<(), TerminalError>;
 }
 
 impl UserInterface for Terminal {
     fn print_error(&mut self, error: &TerminalError) {
-        self.write_interface(&style(error).red()).unwrap();
+        let message = self.at_line(error);
+        self.write_interface(&style(message).red()).unwrap();
     }
 
     fn prompt_new_todo(&mut self) -> Result<Todo, TerminalError> {
-        self.write_interface(&style("Write your new todo:").blue())?;
-        let user_input = self.input()?;
-
-        if user_input.is_empty() {
-            self.write_interface(&style("Please input a valid todo.").red())?;
-            self.prompt_new_todo()
-        } else {
-            Ok(Todo::new(user_input, Uuid::new_v4()))
+        let message = self.prompt_message("Write your new todo:")?;
+        let todo = Todo::new(message, Uuid::new_v4());
+        if self.input.is_script() {
+            return Ok(todo);
         }
+        Ok(todo.with_priority(self.prompt_priority()?))
+    }
+
+    fn prompt_edit_todo(&mut self, todo: &Todo) -> Result<String, TerminalError> {
+        self.write_interface(&style(format!("Current todo: {}", todo.message)).yellow())?;
+        self.prompt_message("Write the new message for this todo:")
     }
 
     fn report_not_found(&mut self) -> Result<(), TerminalError> {
-        self.write_interface(&style("Could not find a todo with the specified id.").red())
+        let message = self.at_line(&"Could not find a todo with the specified id.");
+        self.write_interface(&style(message).red())
+    }
+
+    fn report_list_not_found(&mut self, name: &ListName) -> Result<(), TerminalError> {
+        let message = self.at_line(&format!("There is no list named \"{name}\"."));
+        self.write_interface(&style(message).red())
     }
 
-    fn mark_done_message(&mut self) -> Result<(), TerminalError> {
+    fn mark_done_message(
+        &mut self,
+        _uuid: Uuid,
+        _todos_modified: u64,
+    ) -> Result<(), TerminalError> {
         self.write_interface(&style("Todo marked as done.").green())
     }
 
-    fn show_todo(&mut self, todo: &Todo) -> Result<(), TerminalError> {
-        self.write_interface(&style(todo).green())
+    fn mark_undone_message(
+        &mut self,
+        _uuid: Uuid,
+        _todos_modified: u64,
+    ) -> Result<(), TerminalError> {
+        self.write_interface(&style("Todo marked as not done.").yellow())
+    }
+
+    fn edit_todo_message(
+        &mut self,
+        _uuid: Uuid,
+        _todos_modified: u64,
+    ) -> Result<(), TerminalError> {
+        self.write_interface(&style("Successfully edited todo.").green())
+    }
+
+    fn priority_message(
+        &mut self,
+        _uuid: Uuid,
+        priority: Priority,
+        _todos_modified: u64,
+    ) -> Result<(), TerminalError> {
+        self.write_interface(&style(format!("Todo priority set to {priority}.")).green())
+    }
+
+    fn tags_message(
+        &mut self,
+        _uuid: Uuid,
+        _add: &[Tag],
+        _remove: &[Tag],
+        _todos_modified: u64,
+    ) -> Result<(), TerminalError> {
+        self.write_interface(&style("Todo tags updated.").green())
+    }
+
+    fn show_lists(
+        &mut self,
+        lists: Vec<TodoList>,
+        current: &ListName,
+    ) -> Result<(), TerminalError> {
+        self.write_interface(&style("Your lists are:").green())?;
+        for todo_list in lists {
+            let line = format!(
+                "{} ({} open, {} done)",
+                todo_list.name, todo_list.open, todo_list.done
+            );
+            if todo_list.name == *current {
+                self.write_interface(&style(format!("* {line}")).green().bold())?;
+            } else {
+                self.write_interface(&style(format!("  {line}")).green())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn list_created_message(&mut self, name: &ListName) -> Result<(), TerminalError> {
+        self.write_interface(&style(format!("Created the list {name}.")).green())
+    }
+
+    fn list_renamed_message(
+        &mut self,
+        from: &ListName,
+        to: &ListName,
+    ) -> Result<(), TerminalError> {
+        self.write_interface(&style(format!("Renamed the list {from} to {to}.")).green())
+    }
+
+    fn list_deleted_message(&mut self, name: &ListName) -> Result<(), TerminalError> {
+        self.write_interface(&style(format!("Deleted the list {name} and its todos.")).yellow())
+    }
+
+    fn switch_list_message(&mut self, name: &ListName) -> Result<(), TerminalError> {
+        self.write_interface(&style(format!("Now on the list {name}.")).green())
+    }
+
+    fn move_todo_message(
+        &mut self,
+        _uuid: Uuid,
+        list: &ListName,
+        _todos_modified: u64,
+    ) -> Result<(), TerminalError> {
+        self.write_interface(&style(format!("Todo moved to {list}.")).green())
+    }
+
+    fn reorder_todo_message(
+        &mut self,
+        _uuid: Uuid,
+        _todos_modified: u64,
+    ) -> Result<(), TerminalError> {
+        self.write_interface(&style("Todo moved.").green())
+    }
+
+    fn due_message(
+        &mut self,
+        _uuid: Uuid,
+        due: Option<Due>,
+        _todos_modified: u64,
+    ) -> Result<(), TerminalError> {
+        match due {
+            Some(due) => self.write_interface(&style(format!("Todo is due {due}.")).green()),
+            None => self.write_interface(&style("Todo no longer has a due date.").yellow()),
+        }
+    }
+
+    fn recurrence_message(
+        &mut self,
+        _uuid: Uuid,
+        recurrence: Option<Recurrence>,
+        _todos_modified: u64,
+    ) -> Result<(), TerminalError> {
+        match recurrence {
+            Some(recurrence) => {
+                self.write_interface(&style(format!("Todo repeats {recurrence}.")).green())
+            }
+            None => self.write_interface(&style("Todo no longer repeats.").yellow()),
+        }
+    }
+
+    fn next_occurrence_message(&mut self, next: &Todo) -> Result<(), TerminalError> {
+        match next.due {
+            Some(due) => self.write_interface(&style(format!("Next one is due {due}.")).green()),
+            None => Ok(()),
+        }
+    }
+
+    fn block_message(
+        &mut self,
+        _uuid: Uuid,
+        _blocker: Uuid,
+        _todos_modified: u64,
+    ) -> Result<(), TerminalError> {
+        self.write_interface(&style("Todo now waits for the other one.").green())
+    }
+
+    fn unblock_message(
+        &mut self,
+        _uuid: Uuid,
+        _blocker: Uuid,
+        _todos_modified: u64,
+    ) -> Result<(), TerminalError> {
+        self.write_interface(&style("Todo no longer waits for the other one.").yellow())
+    }
+
+    fn open_blockers_warning(
+        &mut self,
+        _uuid: Uuid,
+        blockers: &[Uuid],
+    ) -> Result<(), TerminalError> {
+        let message = format!(
+            "Heads up: this todo was still waiting for {} open todo(s).",
+            blockers.len()
+        );
+        self.write_interface(&style(message).yellow())
+    }
+
+    fn show_todo(
+        &mut self,
+        position: usize,
+        todo: &Todo,
+        depth: usize,
+        progress: Option<Progress>,
+        blocked: bool,
+    ) -> Result<(), TerminalError> {
+        let mut line = format!("{}{position}. {todo}", "    ".repeat(depth));
+        if let Some(progress) = progress {
+            line.push_str(&format!(" [{progress}]"));
+        }
+        if todo.priority != Priority::Normal {
+            line.push_str(&format!(" ({})", todo.priority));
+        }
+        // Tags written in the message already show up in it.
+        let written = tags_in(&todo.message);
+        for tag in todo.tags.iter().filter(|tag| !written.contains(tag)) {
+            line.push_str(&format!(" {tag}"));
+        }
+        let now = Utc::now();
+        match todo.completed_at {
+            Some(completed_at) if todo.done => {
+                line.push_str(&format!(" - done {}", relative_age(completed_at, now)));
+            }
+            _ => line.push_str(&format!(" - added {}", relative_age(todo.created_at, now))),
+        }
+        let status = match todo.due {
+            Some(due) => {
+                line.push_str(&format!(" - due {due}"));
+                Some(due.status(Local::now().naive_local()))
+            }
+            None => None,
+        };
+        if let Some(recurrence) = todo.recurrence {
+            line.push_str(&format!(" - repeats {recurrence}"));
+        }
+        if blocked {
+            line.push_str(" (blocked)");
+        }
+
+        let line = match status {
+            Some(DueStatus::Overdue) if !todo.done => style(format!("{line} (overdue)")).red(),
+            Some(DueStatus::Today) if !todo.done => style(format!("{line} (due today)")).magenta(),
+            _ if blocked => style(line).dim(),
+            _ => match todo.priority {
+                Priority::Urgent => style(line).red().bold(),
+                Priority::High => style(line).yellow(),
+                Priority::Normal => style(line).green(),
+                Priority::Low => style(line).dim(),
+            },
+        };
+        self.write_interface(&line)
     }
 
     fn alert_unrecognized(&mut self) -> Result<(), TerminalError> {
-        self.write_interface(&style("Invalid option. Please type again").red())
+        let message = self.at_line(&"Invalid option. Please type again");
+        self.write_interface(&style(message).red())
     }
 
     fn clear_todo_message(&mut self) -> Result<(), TerminalError> {
-        self.write_interface(&style("Successfully cleared all todos.").yellow())?;
+        self.write_interface(&style("Moved all todos to the trash.").yellow())?;
         Ok(())
     }
 
@@ -84,47 +443,114 @@ impl UserInterface for Terminal {
             self.write_interface(&style("Your current todo list is empty!").green())?;
         } else {
             self.write_interface(&style("Your current todo list is:").green())?;
-            for todo in &todo_list {
-                self.show_todo(todo)?;
+            for (index, todo) in todo_list.iter().enumerate() {
+                let depth = todo_list.depth(todo);
+                let progress = todo_list.progress(todo.id);
+                let blocked = !todo.done && todo_list.is_blocked(todo);
+                self.show_todo(index + 1, todo, depth, progress, blocked)?;
             }
         }
         Ok(())
     }
 
-    fn remove_todo_message(&mut self) -> Result<(), TerminalError> {
-        self.write_interface(&style("Successfully removed todo.").yellow())
+    // The updated list shown right after is confirmation enough.
+    fn add_todo_message(&mut self, _todo: &Todo) -> Result<(), TerminalError> {
+        Ok(())
     }
 
-    fn user_intention(&mut self) -> Result<UserOptions, TerminalError> {
-        self.write_interface(&style("Do you want to input a new todo? Type \"y\" to add a new todo or \"help\" to see all commands.").blue())?;
-        let user_input = self.input()?;
+    fn remove_todo_message(
+        &mut self,
+        _uuid: Uuid,
+        _todos_modified: u64,
+    ) -> Result<(), TerminalError> {
+        self.write_interface(&style("Moved the todo to the trash.").yellow())
+    }
 
-        if let Some(uuid) = user_input.strip_prefix("rm ") {
-            let uuid = Uuid::parse_str(uuid).map_err(TerminalError::UuidParse)?;
-            return Ok(UserOptions::RemoveTodo(uuid));
+    fn show_trash(&mut self, trash: Todos) -> Result<(), TerminalError> {
+        if trash.is_empty() {
+            return self.write_interface(&style("Your trash is empty!").green());
         }
+        self.write_interface(&style("Your trash has:").yellow())?;
+        let now = Utc::now();
+        for (index, todo) in trash.iter().enumerate() {
+            let mut line = format!("{}. {todo}", index + 1);
+            if let Some(deleted_at) = todo.deleted_at {
+                line.push_str(&format!(" - removed {}", relative_age(deleted_at, now)));
+            }
+            self.write_interface(&style(line).dim())?;
+        }
+        Ok(())
+    }
 
-        if let Some(uuid) = user_input.strip_prefix("done ") {
-            let uuid = Uuid::parse_str(uuid).map_err(TerminalError::UuidParse)?;
-            return Ok(UserOptions::DoTodo(uuid));
+    fn restore_todo_message(
+        &mut self,
+        _uuid: Uuid,
+        _todos_modified: u64,
+    ) -> Result<(), TerminalError> {
+        self.write_interface(&style("Todo restored from the trash.").green())
+    }
+
+    fn undo_message(&mut self, command: &str) -> Result<(), TerminalError> {
+        self.write_interface(&style(format!("Undid the last \"{command}\".")).yellow())
+    }
+
+    fn redo_message(&mut self, command: &str) -> Result<(), TerminalError> {
+        self.write_interface(&style(format!("Redid the last \"{command}\".")).green())
+    }
+
+    fn history_empty_message(&mut self, command: &str) -> Result<(), TerminalError> {
+        let message = self.at_line(&format!("Nothing to {command}."));
+        self.write_interface(&style(message).red())
+    }
+
+    fn purge_message(&mut self, purged: u64) -> Result<(), TerminalError> {
+        self.write_interface(
+            &style(format!("Deleted {purged} todo(s) from the trash for good.")).yellow(),
+        )
+    }
+
+    fn show_archive(&mut self, archive: Todos) -> Result<(), TerminalError> {
+        if archive.is_empty() {
+            return self.write_interface(&style("Your archive is empty!").green());
         }
+        self.write_interface(&style("Your archive has:").yellow())?;
+        let now = Utc::now();
+        for (index, todo) in archive.iter().enumerate() {
+            let mut line = format!("{}. {todo}", index + 1);
+            if let Some(archived_at) = todo.archived_at {
+                line.push_str(&format!(" - archived {}", relative_age(archived_at, now)));
+            }
+            self.write_interface(&style(line).dim())?;
+        }
+        Ok(())
+    }
 
-        match user_input.as_str() {
-            "y" => Ok(UserOptions::NewTodo(self.prompt_new_todo()?)),
-            "help" => Ok(UserOptions::Help),
-            "clear" => Ok(UserOptions::ClearList),
-            "quit" => Ok(UserOptions::Quit),
-            "list" => Ok(UserOptions::ShowList),
-            _ => Ok(UserOptions::Unrecognized),
+    fn archive_message(&mut self, archived: u64) -> Result<(), TerminalError> {
+        self.write_interface(&style(format!("Archived {archived} done todo(s).")).green())
+    }
+
+    fn unarchive_todo_message(
+        &mut self,
+        _uuid: Uuid,
+        _todos_modified: u64,
+    ) -> Result<(), TerminalError> {
+        self.write_interface(&style("Todo brought back from the archive.").green())
+    }
+
+    fn user_intention(&mut self) -> Result<UserOptions, TerminalError> {
+        if !self.input.is_script() {
+            self.write_interface(&style("Do you want to input a new todo? Type \"y\" to add a new todo or \"help\" to see all commands.").blue())?;
         }
+        let user_input = match self.input() {
+            Err(TerminalError::EndOfInput) => return Ok(UserOptions::Quit),
+            result => result?,
+        };
+
+        parse_user_option(&user_input, || self.prompt_new_todo())
     }
 
     fn input(&mut self) -> Result<String, TerminalError> {
-        let mut buf = String::new();
-        self.stdin
-            .read_line(&mut buf)
-            .map_err(TerminalError::Stdin)
-            .map(|_| buf.trim().to_string())
+        self.input.read_line()
     }
 
     fn write_interface(&mut self, string: &dyn Display) -> Result<(), TerminalError> {
@@ -138,19 +564,558 @@ impl UserInterface for Terminal {
             &style("⭐️ To add a new todo, type y when asked, type your todo and press enter. ⭐️")
                 .yellow(),
         )?;
-        self.write_interface(&style("⭐️ To remove a todo, type \"rm n\", being \"n\" the index of the todo in the list. ⭐️").yellow())?;
+        self.write_interface(
+            &style("⭐️ You can also type \"add\" followed by the todo on a single line, optionally starting with \"-p high\" to set its priority or \"--parent n\" to make it a subtask of another todo. ⭐️")
+                .yellow(),
+        )?;
+        self.write_interface(&style("⭐️ To remove a todo, type \"rm n\", being \"n\" the position of the todo in the list or the start of its id. ⭐️").yellow())?;
         self.write_interface(&style("⭐️ To clear the list of todos, type \"clear\" ⭐️").yellow())?;
+        self.write_interface(&style("⭐️ Removed and cleared todos go to the trash. To see it, type \"trash\"; \"restore n\" brings back the nth todo there and \"purge\" or \"purge --older-than 30d\" empties it for good. ⭐️").yellow())?;
         self.write_interface(&style("⭐️ To see the list of todos, type \"list\" ⭐️").yellow())?;
-        self.write_interface(&style("⭐️ To mark a todo as done, type \"done n\", being \"n\" the index of the todo in the list.  ⭐️").yellow())?;
+        self.write_interface(&style("⭐️ To put away the done todos, type \"archive\"; a todo goes once it and all its subtasks are done. To see them, type \"list --archived\"; \"unarchive n\" brings back the nth todo there. ⭐️").yellow())?;
+        self.write_interface(&style("⭐️ To take back the last add, rm, done, clear or edit, type \"undo\"; \"redo\" makes it again. ⭐️").yellow())?;
+        self.write_interface(&style("⭐️ To mark a todo as done, type \"done n\", being \"n\" the position of the todo in the list or the start of its id.  ⭐️").yellow())?;
+        self.write_interface(&style("⭐️ To mark a done todo as not done again, type \"undone n\", being \"n\" the position of the todo in the list or the start of its id. ⭐️").yellow())?;
+        self.write_interface(&style("⭐️ To edit a todo, type \"edit n\", being \"n\" the position of the todo in the list or the start of its id. ⭐️").yellow())?;
+        self.write_interface(&style("⭐️ To change the priority of a todo, type \"priority n level\", being \"level\" one of low, normal, high or urgent. ⭐️").yellow())?;
+        self.write_interface(&style("⭐️ To set a due date, type \"due n when\", like \"due 2 tomorrow\", \"due 2 2026-11-01\" or \"due 2 fri 17:00\"; \"due n none\" removes it. ⭐️").yellow())?;
+        self.write_interface(&style("⭐️ To make a todo come back once it is done, type \"repeat n rule\", like \"repeat 2 daily\", \"repeat 2 weekdays\", \"repeat 2 every 3 days\" or \"repeat 2 monthly on 15\"; \"repeat n none\" stops it. ⭐️").yellow())?;
+        self.write_interface(
+            &style("⭐️ To see what is due today or overdue, type \"today\" or \"overdue\" ⭐️")
+                .yellow(),
+        )?;
+        self.write_interface(&style("⭐️ To make a todo wait for another, type \"block n m\", being \"m\" the todo to do first; \"unblock n m\" undoes it. To see what can be done now, type \"next\". ⭐️").yellow())?;
+        self.write_interface(&style("⭐️ Words like #backend or @home in a todo become its tags. To change them, type \"tag n +foo -bar\"; to see the todos with a tag, type \"list #foo\". ⭐️").yellow())?;
+        self.write_interface(&style("⭐️ To see your lists, type \"lists\"; manage them with \"lists new name\", \"lists rename old new\" and \"lists rm name\". ⭐️").yellow())?;
+        self.write_interface(&style("⭐️ To work on another list, type \"switch name\"; to send a todo there, type \"move n name\". ⭐️").yellow())?;
+        self.write_interface(&style("⭐️ To reorder todos, type \"mv n position\", \"top n\" or \"bottom n\"; todos stay grouped by priority. ⭐️").yellow())?;
+        self.write_interface(
+            &style("⭐️ Positions start at 1; an id prefix must match a single todo. ⭐️").yellow(),
+        )?;
         Ok(())
     }
 }
 
+// Shared by every `UserInterface`; `new_todo` asks for the message after a bare "y".
+pub fn parse_user_option(
+    user_input: &str,
+    new_todo: impl FnOnce() -> Result<Todo, TerminalError>,
+) -> Result<UserOptions, TerminalError> {
+    if let Some(arguments) = user_input.strip_prefix("add ") {
+        let (priority, parent, message) = parse_add_flags(arguments.trim())?;
+        if !message.is_empty() {
+            let todo = Todo::new(message.to_string(), Uuid::new_v4()).with_priority(priority);
+            return Ok(match parent {
+                Some(parent) => UserOptions::NewSubtask(parent, todo),
+                None => UserOptions::NewTodo(todo),
+            });
+        }
+    }
+
+    if let Some(arguments) = user_input.strip_prefix("priority ") {
+        let (todo_ref, priority) = arguments.trim().split_once(' ').unwrap_or((arguments, ""));
+        return Ok(UserOptions::SetPriority(
+            parse_todo_ref(todo_ref)?,
+            parse_priority(priority)?,
+        ));
+    }
+
+    if let Some(arguments) = user_input.strip_prefix("due ") {
+        let (todo_ref, when) = arguments.trim().split_once(' ').unwrap_or((arguments, ""));
+        return Ok(UserOptions::SetDue(
+            parse_todo_ref(todo_ref)?,
+            parse_due(when)?,
+        ));
+    }
+
+    if let Some(arguments) = user_input.strip_prefix("repeat ") {
+        let (todo_ref, rule) = arguments.trim().split_once(' ').unwrap_or((arguments, ""));
+        return Ok(UserOptions::SetRecurrence(
+            parse_todo_ref(todo_ref)?,
+            parse_recurrence(rule)?,
+        ));
+    }
+
+    for (command, block) in [("block ", true), ("unblock ", false)] {
+        if let Some(arguments) = user_input.strip_prefix(command) {
+            let (todo_ref, blocker) = arguments.trim().split_once(' ').unwrap_or((arguments, ""));
+            let (todo_ref, blocker) = (parse_todo_ref(todo_ref)?, parse_todo_ref(blocker.trim())?);
+            return Ok(match block {
+                true => UserOptions::Block(todo_ref, blocker),
+                false => UserOptions::Unblock(todo_ref, blocker),
+            });
+        }
+    }
+
+    if let Some(arguments) = user_input.strip_prefix("tag ") {
+        let mut words = arguments.split_whitespace();
+        let todo_ref = parse_todo_ref(words.next().unwrap_or_default())?;
+        let (add, remove) = parse_tag_changes(words)?;
+        return Ok(UserOptions::TagTodo(todo_ref, add, remove));
+    }
+
+    if user_input == "list --archived" {
+        return Ok(UserOptions::ShowArchive);
+    }
+
+    if let Some(tag) = user_input.strip_prefix("list ") {
+        let tag = tag.parse().map_err(TerminalError::InvalidTag)?;
+        return Ok(UserOptions::ShowTagged(tag));
+    }
+
+    if let Some(arguments) = user_input.strip_prefix("lists ") {
+        let words: Vec<&str> = arguments.split_whitespace().collect();
+        return match words.as_slice() {
+            ["new", name] => Ok(UserOptions::CreateList(parse_list_name(name)?)),
+            ["rename", from, to] => Ok(UserOptions::RenameList(
+                parse_list_name(from)?,
+                parse_list_name(to)?,
+            )),
+            ["rm", name] => Ok(UserOptions::DeleteList(parse_list_name(name)?)),
+            _ => Ok(UserOptions::Unrecognized),
+        };
+    }
+
+    if let Some(name) = user_input.strip_prefix("switch ") {
+        return Ok(UserOptions::SwitchList(parse_list_name(name)?));
+    }
+
+    if let Some(arguments) = user_input.strip_prefix("move ") {
+        let (todo_ref, list) = arguments.trim().split_once(' ').unwrap_or((arguments, ""));
+        return Ok(UserOptions::MoveTodo(
+            parse_todo_ref(todo_ref)?,
+            parse_list_name(list)?,
+        ));
+    }
+
+    if let Some(arguments) = user_input.strip_prefix("mv ") {
+        let (todo_ref, position) = arguments.trim().split_once(' ').unwrap_or((arguments, ""));
+        return Ok(UserOptions::MoveToPosition(
+            parse_todo_ref(todo_ref)?,
+            parse_position(position)?,
+        ));
+    }
+
+    if let Some(todo_ref) = user_input.strip_prefix("top ") {
+        return Ok(UserOptions::MoveToTop(parse_todo_ref(todo_ref)?));
+    }
+
+    if let Some(todo_ref) = user_input.strip_prefix("bottom ") {
+        return Ok(UserOptions::MoveToBottom(parse_todo_ref(todo_ref)?));
+    }
+
+    if let Some(todo_ref) = user_input.strip_prefix("unarchive ") {
+        return Ok(UserOptions::UnarchiveTodo(parse_todo_ref(todo_ref)?));
+    }
+
+    if let Some(todo_ref) = user_input.strip_prefix("restore ") {
+        return Ok(UserOptions::RestoreTodo(parse_todo_ref(todo_ref)?));
+    }
+
+    if let Some(arguments) = user_input.strip_prefix("purge ") {
+        let words: Vec<&str> = arguments.split_whitespace().collect();
+        return match words.as_slice() {
+            ["--older-than", age] => Ok(UserOptions::PurgeTrash(Some(parse_age(age)?))),
+            _ => Ok(UserOptions::Unrecognized),
+        };
+    }
+
+    if let Some(todo_ref) = user_input.strip_prefix("rm ") {
+        return Ok(UserOptions::RemoveTodo(parse_todo_ref(todo_ref)?));
+    }
+
+    if let Some(todo_ref) = user_input.strip_prefix("done ") {
+        return Ok(UserOptions::DoTodo(parse_todo_ref(todo_ref)?));
+    }
+
+    if let Some(todo_ref) = user_input.strip_prefix("undone ") {
+        return Ok(UserOptions::UndoneTodo(parse_todo_ref(todo_ref)?));
+    }
+
+    if let Some(todo_ref) = user_input.strip_prefix("edit ") {
+        return Ok(UserOptions::EditTodo(parse_todo_ref(todo_ref)?));
+    }
+
+    match user_input {
+        "y" => Ok(UserOptions::NewTodo(new_todo()?)),
+        "help" => Ok(UserOptions::Help),
+        "clear" => Ok(UserOptions::ClearList),
+        "quit" => Ok(UserOptions::Quit),
+        "list" => Ok(UserOptions::ShowList),
+        "lists" => Ok(UserOptions::ShowLists),
+        "today" => Ok(UserOptions::ShowDue(DueFilter::Today)),
+        "overdue" => Ok(UserOptions::ShowDue(DueFilter::Overdue)),
+        "next" => Ok(UserOptions::ShowNext),
+        "trash" => Ok(UserOptions::ShowTrash),
+        "archive" => Ok(UserOptions::ArchiveDone),
+        "undo" => Ok(UserOptions::Undo),
+        "redo" => Ok(UserOptions::Redo),
+        "purge" => Ok(UserOptions::PurgeTrash(None)),
+        _ => Ok(UserOptions::Unrecognized),
+    }
+}
+
+fn parse_todo_ref(input: &str) -> Result<TodoRef, TerminalError> {
+    input.parse().map_err(TerminalError::InvalidTodoRef)
+}
+
+fn parse_position(input: &str) -> Result<usize, TerminalError> {
+    match input.trim().parse() {
+        Ok(position) if position > 0 => Ok(position),
+        _ => Err(TerminalError::InvalidPosition(input.trim().to_string())),
+    }
+}
+
+fn parse_age(input: &str) -> Result<Age, TerminalError> {
+    input.parse().map_err(TerminalError::InvalidAge)
+}
+
+fn parse_priority(input: &str) -> Result<Priority, TerminalError> {
+    input.parse().map_err(TerminalError::InvalidPriority)
+}
+
+pub fn parse_list_name(input: &str) -> Result<ListName, TerminalError> {
+    input.parse().map_err(TerminalError::InvalidListName)
+}
+
+// Splits words like "+foo" and "-bar" into the tags to add and the tags to remove.
+pub fn parse_tag_changes<'a>(
+    words: impl IntoIterator<Item = &'a str>,
+) -> Result<(Vec<Tag>, Vec<Tag>), TerminalError> {
+    let (mut add, mut remove) = (Vec::new(), Vec::new());
+    for word in words {
+        let (tags, tag) = match (word.strip_prefix('+'), word.strip_prefix('-')) {
+            (Some(tag), _) => (&mut add, tag),
+            (_, Some(tag)) => (&mut remove, tag),
+            _ => return Err(TerminalError::InvalidTag(InvalidTag(word.to_string()))),
+        };
+        tags.push(tag.parse().map_err(TerminalError::InvalidTag)?);
+    }
+    if add.is_empty() && remove.is_empty() {
+        return Err(TerminalError::InvalidTag(InvalidTag(String::new())));
+    }
+    Ok((add, remove))
+}
+
+// Like "3d ago", rounded down to the largest whole unit.
+fn relative_age(since: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let age = now.signed_duration_since(since);
+    if age.num_days() > 0 {
+        format!("{}d ago", age.num_days())
+    } else if age.num_hours() > 0 {
+        format!("{}h ago", age.num_hours())
+    } else if age.num_minutes() > 0 {
+        format!("{}m ago", age.num_minutes())
+    } else {
+        "just now".to_string()
+    }
+}
+
+// "none" removes the due date.
+pub fn parse_due(input: &str) -> Result<Option<Due>, TerminalError> {
+    match input.trim() {
+        "none" => Ok(None),
+        when => Due::parse(when, Local::now().date_naive())
+            .map(Some)
+            .map_err(TerminalError::InvalidDue),
+    }
+}
+
+// "none" stops the todo from repeating.
+pub fn parse_recurrence(input: &str) -> Result<Option<Recurrence>, TerminalError> {
+    match input.trim() {
+        "none" => Ok(None),
+        rule => rule
+            .parse()
+            .map(Some)
+            .map_err(TerminalError::InvalidRecurrence),
+    }
+}
+
+// Splits the leading "-p <priority>", "--priority <priority>" and "--parent <n>" flags off a
+// todo message.
+fn parse_add_flags(
+    mut arguments: &str,
+) -> Result<(Priority, Option<TodoRef>, &str), TerminalError> {
+    let (mut priority, mut parent) = (Priority::default(), None);
+    loop {
+        let (flag, rest) = arguments.split_once(' ').unwrap_or((arguments, ""));
+        let (value, rest) = rest.trim_start().split_once(' ').unwrap_or((rest, ""));
+        match flag {
+            "-p" | "--priority" => priority = parse_priority(value)?,
+            "--parent" => parent = Some(parse_todo_ref(value)?),
+            _ => return Ok((priority, parent, arguments)),
+        }
+        arguments = rest.trim_start();
+    }
+}
+
 impl Terminal {
-    pub fn new() -> Self {
+    pub fn new(input: Input) -> Self {
         Terminal {
-            stdin: std::io::stdin(),
+            input,
             stdout: std::io::stdout(),
         }
     }
+
+    fn prompt_message(&mut self, prompt: &str) -> Result<String, TerminalError> {
+        loop {
+            if !self.input.is_script() {
+                self.write_interface(&style(prompt).blue())?;
+            }
+            let user_input = self.input()?;
+
+            if !user_input.is_empty() {
+                return Ok(user_input);
+            }
+            self.write_interface(&style("Please input a valid todo.").red())?;
+        }
+    }
+
+    fn prompt_priority(&mut self) -> Result<Priority, TerminalError> {
+        loop {
+            self.write_interface(
+                &style("Priority (low, normal, high or urgent)? Press enter for normal.").blue(),
+            )?;
+            let user_input = self.input()?;
+            if user_input.is_empty() {
+                return Ok(Priority::default());
+            }
+            match parse_priority(&user_input) {
+                Ok(priority) => return Ok(priority),
+                Err(error) => self.print_error(&error),
+            }
+        }
+    }
+
+    fn at_line(&self, message: &dyn Display) -> String {
+        match self.input.line() {
+            Some(line) => format!("line {line}: {message}"),
+            None => message.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(user_input: &str) -> Result<UserOptions, TerminalError> {
+        parse_user_option(user_input, || panic!("should not prompt"))
+    }
+
+    #[test]
+    fn should_parse_add_with_priority() {
+        match parse("add -p high buy milk").unwrap() {
+            UserOptions::NewTodo(todo) => {
+                assert_eq!(todo.message, "buy milk");
+                assert_eq!(todo.priority, Priority::High);
+            }
+            _ => panic!("expected a new todo"),
+        }
+        match parse("add buy milk").unwrap() {
+            UserOptions::NewTodo(todo) => assert_eq!(todo.priority, Priority::Normal),
+            _ => panic!("expected a new todo"),
+        }
+        assert!(matches!(
+            parse("add --priority soon buy milk"),
+            Err(TerminalError::InvalidPriority(_))
+        ));
+        match parse("add --parent 2 -p high write notes").unwrap() {
+            UserOptions::NewSubtask(TodoRef::Position(2), todo) => {
+                assert_eq!(todo.message, "write notes");
+                assert_eq!(todo.priority, Priority::High);
+            }
+            _ => panic!("expected a new subtask"),
+        }
+    }
+
+    #[test]
+    fn should_parse_priority_changes() {
+        assert!(matches!(
+            parse("priority 2 urgent").unwrap(),
+            UserOptions::SetPriority(TodoRef::Position(2), Priority::Urgent)
+        ));
+        assert!(matches!(
+            parse("priority 2"),
+            Err(TerminalError::InvalidPriority(_))
+        ));
+    }
+
+    #[test]
+    fn should_parse_due_dates_and_filters() {
+        match parse("due 2 fri 17:00").unwrap() {
+            UserOptions::SetDue(TodoRef::Position(2), Some(due)) => {
+                assert_eq!(due.time, chrono::NaiveTime::from_hms_opt(17, 0, 0));
+            }
+            _ => panic!("expected a due date"),
+        }
+        assert!(matches!(
+            parse("due 2 none").unwrap(),
+            UserOptions::SetDue(TodoRef::Position(2), None)
+        ));
+        assert!(matches!(
+            parse("due 2 someday"),
+            Err(TerminalError::InvalidDue(_))
+        ));
+        assert!(matches!(
+            parse("overdue").unwrap(),
+            UserOptions::ShowDue(DueFilter::Overdue)
+        ));
+    }
+
+    #[test]
+    fn should_parse_recurrences() {
+        assert!(matches!(
+            parse("repeat 2 every 3 days").unwrap(),
+            UserOptions::SetRecurrence(TodoRef::Position(2), Some(Recurrence::EveryDays(3)))
+        ));
+        assert!(matches!(
+            parse("repeat 2 none").unwrap(),
+            UserOptions::SetRecurrence(TodoRef::Position(2), None)
+        ));
+        assert!(matches!(
+            parse("repeat 2 sometimes"),
+            Err(TerminalError::InvalidRecurrence(_))
+        ));
+    }
+
+    #[test]
+    fn should_parse_trash_commands() {
+        assert!(matches!(parse("trash").unwrap(), UserOptions::ShowTrash));
+        assert!(matches!(
+            parse("restore 2").unwrap(),
+            UserOptions::RestoreTodo(TodoRef::Position(2))
+        ));
+        assert!(matches!(
+            parse("purge").unwrap(),
+            UserOptions::PurgeTrash(None)
+        ));
+        match parse("purge --older-than 30d").unwrap() {
+            UserOptions::PurgeTrash(Some(age)) => assert_eq!(age.to_string(), "30d"),
+            _ => panic!("expected a purge"),
+        }
+        assert!(matches!(
+            parse("purge --older-than soon"),
+            Err(TerminalError::InvalidAge(_))
+        ));
+    }
+
+    #[test]
+    fn should_parse_archive_commands() {
+        assert!(matches!(
+            parse("archive").unwrap(),
+            UserOptions::ArchiveDone
+        ));
+        assert!(matches!(
+            parse("list --archived").unwrap(),
+            UserOptions::ShowArchive
+        ));
+        assert!(matches!(
+            parse("unarchive 3").unwrap(),
+            UserOptions::UnarchiveTodo(TodoRef::Position(3))
+        ));
+    }
+
+    #[test]
+    fn should_parse_undo_and_redo() {
+        assert!(matches!(parse("undo").unwrap(), UserOptions::Undo));
+        assert!(matches!(parse("redo").unwrap(), UserOptions::Redo));
+    }
+
+    #[test]
+    fn should_parse_reordering() {
+        assert!(matches!(
+            parse("mv 4 1").unwrap(),
+            UserOptions::MoveToPosition(TodoRef::Position(4), 1)
+        ));
+        assert!(matches!(
+            parse("top 4").unwrap(),
+            UserOptions::MoveToTop(TodoRef::Position(4))
+        ));
+        assert!(matches!(
+            parse("bottom 4").unwrap(),
+            UserOptions::MoveToBottom(TodoRef::Position(4))
+        ));
+        assert!(matches!(
+            parse("mv 4 0"),
+            Err(TerminalError::InvalidPosition(_))
+        ));
+    }
+
+    #[test]
+    fn should_parse_dependencies() {
+        assert!(matches!(
+            parse("block 3 1").unwrap(),
+            UserOptions::Block(TodoRef::Position(3), TodoRef::Position(1))
+        ));
+        assert!(matches!(
+            parse("unblock 3 1").unwrap(),
+            UserOptions::Unblock(TodoRef::Position(3), TodoRef::Position(1))
+        ));
+        assert!(matches!(
+            parse("block 3"),
+            Err(TerminalError::InvalidTodoRef(_))
+        ));
+        assert!(matches!(parse("next").unwrap(), UserOptions::ShowNext));
+    }
+
+    #[test]
+    fn should_parse_tag_changes_and_filters() {
+        match parse("tag 2 +Backend -@home").unwrap() {
+            UserOptions::TagTodo(TodoRef::Position(2), add, remove) => {
+                assert_eq!(add, vec!["backend".parse::<Tag>().unwrap()]);
+                assert_eq!(remove, vec!["@home".parse::<Tag>().unwrap()]);
+            }
+            _ => panic!("expected tag changes"),
+        }
+        assert!(matches!(
+            parse("tag 2 backend"),
+            Err(TerminalError::InvalidTag(_))
+        ));
+        assert!(matches!(parse("tag 2"), Err(TerminalError::InvalidTag(_))));
+        assert!(matches!(
+            parse("list #backend").unwrap(),
+            UserOptions::ShowTagged(tag) if tag.name() == "backend"
+        ));
+    }
+
+    #[test]
+    fn should_parse_list_commands() {
+        let name = |input: &str| input.parse::<ListName>().unwrap();
+
+        assert!(matches!(parse("lists").unwrap(), UserOptions::ShowLists));
+        assert!(matches!(
+            parse("lists new work").unwrap(),
+            UserOptions::CreateList(list) if list == name("work")
+        ));
+        assert!(matches!(
+            parse("lists rename work job").unwrap(),
+            UserOptions::RenameList(from, to) if from == name("work") && to == name("job")
+        ));
+        assert!(matches!(
+            parse("move 2 job").unwrap(),
+            UserOptions::MoveTodo(TodoRef::Position(2), list) if list == name("job")
+        ));
+        assert!(matches!(
+            parse("switch two words"),
+            Err(TerminalError::InvalidListName(_))
+        ));
+        assert!(matches!(
+            parse("lists drop work").unwrap(),
+            UserOptions::Unrecognized
+        ));
+    }
+
+    #[test]
+    fn should_show_relative_ages() {
+        let now = Utc::now();
+        let age = |seconds| relative_age(now - chrono::Duration::seconds(seconds), now);
+
+        assert_eq!(age(20), "just now");
+        assert_eq!(age(5 * 60), "5m ago");
+        assert_eq!(age(3 * 3600 + 59 * 60), "3h ago");
+        assert_eq!(age(3 * 86400 + 5), "3d ago");
+        assert_eq!(age(-30), "just now");
+    }
 }
@@ -1,13 +1,33 @@
+use crate::domain::age::InvalidAge;
+use crate::domain::due::InvalidDue;
+use crate::domain::list::{InvalidListName, ListName};
+use crate::domain::priority::InvalidPriority;
+use crate::domain::recurrence::InvalidRecurrence;
+use crate::domain::tag::InvalidTag;
+use crate::domain::todo_ref::InvalidTodoRef;
 use crate::repository::todo::error::StorageError;
 use std::{fmt, io::Error};
-use uuid::Error as UuidError;
 
 #[derive(Debug)]
 pub enum TerminalError {
     Stdout(Error),
     Stdin(Error),
     StorageError(StorageError),
-    UuidParse(UuidError),
+    InvalidTodoRef(InvalidTodoRef),
+    InvalidPriority(InvalidPriority),
+    InvalidDue(InvalidDue),
+    InvalidRecurrence(InvalidRecurrence),
+    InvalidAge(InvalidAge),
+    InvalidTag(InvalidTag),
+    InvalidListName(InvalidListName),
+    ListExists(ListName),
+    ProtectedList(ListName),
+    OpenSubtasks(usize),
+    DependencyCycle,
+    InvalidPosition(String),
+    UnreachablePosition(usize),
+    AmbiguousTodoRef(String, usize),
+    EndOfInput,
 }
 
 impl fmt::Display for TerminalError {
@@ -16,17 +36,64 @@ impl fmt::Display for TerminalError {
             TerminalError::Stdin(error) => write!(f, "Input error: {error}"),
             TerminalError::Stdout(error) => write!(f, "Output error: {error}"),
             TerminalError::StorageError(error) => write!(f, "Error in storage: {error}"),
-            TerminalError::UuidParse(error) => write!(f, "Error when parsing the uuid: {error}"),
+            TerminalError::InvalidTodoRef(error) => write!(f, "Invalid todo: {error}"),
+            TerminalError::InvalidPriority(error) => write!(f, "Invalid priority: {error}"),
+            TerminalError::InvalidDue(error) => write!(f, "Invalid due date: {error}"),
+            TerminalError::InvalidRecurrence(error) => write!(f, "Invalid recurrence: {error}"),
+            TerminalError::InvalidAge(error) => write!(f, "Invalid age: {error}"),
+            TerminalError::InvalidTag(error) => write!(f, "Invalid tag: {error}"),
+            TerminalError::InvalidListName(error) => write!(f, "Invalid list: {error}"),
+            TerminalError::ListExists(name) => {
+                write!(f, "There is already a list named \"{name}\"")
+            }
+            TerminalError::ProtectedList(name) => {
+                write!(f, "The \"{name}\" list cannot be renamed or deleted")
+            }
+            TerminalError::OpenSubtasks(open) => write!(
+                f,
+                "This todo has {open} open subtask(s); finish them first, or start with --complete-parent cascade"
+            ),
+            TerminalError::DependencyCycle => write!(
+                f,
+                "That todo already waits for this one, so it cannot block it"
+            ),
+            TerminalError::InvalidPosition(position) => write!(
+                f,
+                "\"{position}\" is not a position in the list; positions start at 1"
+            ),
+            TerminalError::UnreachablePosition(position) => write!(
+                f,
+                "The list is ordered by priority, with subtasks under their parent, so this todo cannot show at position {position}"
+            ),
+            TerminalError::AmbiguousTodoRef(prefix, matches) => write!(
+                f,
+                "\"{prefix}\" matches {matches} todos; type more characters of the id"
+            ),
+            TerminalError::EndOfInput => write!(f, "Unexpected end of input"),
         }
     }
 }
 
 impl TerminalError {
     pub fn is_fatal(&self) -> bool {
-        matches!(
-            self,
-            TerminalError::Stdin(_) | TerminalError::Stdout(_) | TerminalError::StorageError(_)
-        )
+        match self {
+            TerminalError::Stdin(_) | TerminalError::Stdout(_) | TerminalError::EndOfInput => true,
+            TerminalError::StorageError(error) => !error.is_retryable(),
+            TerminalError::InvalidTodoRef(_)
+            | TerminalError::InvalidPriority(_)
+            | TerminalError::InvalidDue(_)
+            | TerminalError::InvalidRecurrence(_)
+            | TerminalError::InvalidAge(_)
+            | TerminalError::InvalidTag(_)
+            | TerminalError::InvalidListName(_)
+            | TerminalError::ListExists(_)
+            | TerminalError::ProtectedList(_)
+            | TerminalError::OpenSubtasks(_)
+            | TerminalError::DependencyCycle
+            | TerminalError::InvalidPosition(_)
+            | TerminalError::UnreachablePosition(_)
+            | TerminalError::AmbiguousTodoRef(..) => false,
+        }
     }
 }
 
@@ -0,0 +1,86 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, IsTerminal},
+    path::Path,
+};
+
+use crate::terminal::error::TerminalError;
+
+// Where commands come from. Scripts (a `--script` file, or stdin when it is not a terminal)
+// get no prompts, skip blank lines and `#` comments, and keep track of the line number so
+// failures can point at it.
+pub struct Input {
+    reader: Box<dyn BufRead + Send + Sync>,
+    script: bool,
+    line: usize,
+}
+
+impl Input {
+    pub fn stdin() -> Self {
+        let stdin = io::stdin();
+        let script = !stdin.is_terminal();
+        Input {
+            reader: Box::new(BufReader::new(stdin)),
+            script,
+            line: 0,
+        }
+    }
+
+    pub fn open_script(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::script(BufReader::new(File::open(path)?)))
+    }
+
+    pub fn script(reader: impl BufRead + Send + Sync + 'static) -> Self {
+        Input {
+            reader: Box::new(reader),
+            script: true,
+            line: 0,
+        }
+    }
+
+    pub fn is_script(&self) -> bool {
+        self.script
+    }
+
+    // Line of the command being run, only tracked for scripts once they started reading.
+    pub fn line(&self) -> Option<usize> {
+        (self.script && self.line > 0).then_some(self.line)
+    }
+
+    pub fn read_line(&mut self) -> Result<String, TerminalError> {
+        loop {
+            let mut buf = String::new();
+            let read = self
+                .reader
+                .read_line(&mut buf)
+                .map_err(TerminalError::Stdin)?;
+            if read == 0 {
+                return Err(TerminalError::EndOfInput);
+            }
+            self.line += 1;
+
+            let line = buf.trim();
+            if self.script && (line.is_empty() || line.starts_with('#')) {
+                continue;
+            }
+            return Ok(line.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn should_skip_comments_and_blank_lines_in_scripts() {
+        let mut input = Input::script(Cursor::new("# seed\n\nadd buy milk\n  # indented\nlist\n"));
+
+        assert_eq!(input.read_line().unwrap(), "add buy milk");
+        assert_eq!(input.line(), Some(3));
+        assert_eq!(input.read_line().unwrap(), "list");
+        assert_eq!(input.line(), Some(5));
+        assert!(matches!(input.read_line(), Err(TerminalError::EndOfInput)));
+    }
+}
@@ -0,0 +1,734 @@
+use console::strip_ansi_codes;
+use serde::Serialize;
+use std::{
+    fmt::Display,
+    io::{Stdout, Write},
+};
+use uuid::Uuid;
+
+use crate::domain::{
+    due::Due,
+    list::{ListName, TodoList},
+    priority::Priority,
+    recurrence::Recurrence,
+    subtask::Progress,
+    tag::Tag,
+    todo::Todo,
+    todos::Todos,
+};
+use crate::terminal::error::TerminalError;
+use crate::terminal::input::Input;
+use crate::terminal::{parse_user_option, UserInterface, UserOptions};
+
+#[derive(Clone, Copy)]
+pub enum JsonStyle {
+    // One indented document per event.
+    Pretty,
+    // One compact document per line (NDJSON).
+    Compact,
+}
+
+// Machine-readable counterpart of `Terminal`: every message becomes a JSON event on stdout
+// and prompts are not printed.
+pub struct JsonInterface {
+    input: Input,
+    stdout: Stdout,
+    style: JsonStyle,
+}
+
+#[derive(Serialize)]
+pub struct ListedTodo<'a> {
+    pub position: usize,
+    pub depth: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<Progress>,
+    pub blocked: bool,
+    #[serde(flatten)]
+    pub todo: &'a Todo,
+}
+
+// A todo from the trash or the archive, with its position there.
+#[derive(Serialize)]
+pub struct ShownTodo<'a> {
+    pub position: usize,
+    #[serde(flatten)]
+    pub todo: &'a Todo,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+    List {
+        todos: Vec<ListedTodo<'a>>,
+    },
+    Todo(ListedTodo<'a>),
+    Added {
+        id: Uuid,
+    },
+    Removed {
+        id: Uuid,
+        rows_affected: u64,
+    },
+    Done {
+        id: Uuid,
+        rows_affected: u64,
+    },
+    Undone {
+        id: Uuid,
+        rows_affected: u64,
+    },
+    Edited {
+        id: Uuid,
+        rows_affected: u64,
+    },
+    Prioritized {
+        id: Uuid,
+        priority: Priority,
+        rows_affected: u64,
+    },
+    DueSet {
+        id: Uuid,
+        due: Option<Due>,
+        rows_affected: u64,
+    },
+    RecurrenceSet {
+        id: Uuid,
+        recurrence: Option<Recurrence>,
+        rows_affected: u64,
+    },
+    Recurred {
+        id: Uuid,
+        due: Option<Due>,
+    },
+    Blocked {
+        id: Uuid,
+        blocker: Uuid,
+        rows_affected: u64,
+    },
+    Unblocked {
+        id: Uuid,
+        blocker: Uuid,
+        rows_affected: u64,
+    },
+    StillBlocked {
+        id: Uuid,
+        blocked_by: &'a [Uuid],
+    },
+    Tagged {
+        id: Uuid,
+        added: &'a [Tag],
+        removed: &'a [Tag],
+        rows_affected: u64,
+    },
+    Lists {
+        lists: Vec<TodoList>,
+        current: &'a ListName,
+    },
+    ListCreated {
+        name: &'a ListName,
+    },
+    ListRenamed {
+        from: &'a ListName,
+        to: &'a ListName,
+    },
+    ListDeleted {
+        name: &'a ListName,
+    },
+    Switched {
+        list: &'a ListName,
+    },
+    Moved {
+        id: Uuid,
+        list: &'a ListName,
+        rows_affected: u64,
+    },
+    Reordered {
+        id: Uuid,
+        rows_affected: u64,
+    },
+    Cleared,
+    Trash {
+        todos: Vec<ShownTodo<'a>>,
+    },
+    Restored {
+        id: Uuid,
+        rows_affected: u64,
+    },
+    Purged {
+        rows_affected: u64,
+    },
+    Archive {
+        todos: Vec<ShownTodo<'a>>,
+    },
+    Archived {
+        rows_affected: u64,
+    },
+    Unarchived {
+        id: Uuid,
+        rows_affected: u64,
+    },
+    Reverted {
+        command: &'a str,
+    },
+    Reapplied {
+        command: &'a str,
+    },
+    HistoryEmpty {
+        command: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        line: Option<usize>,
+    },
+    NotFound {
+        rows_affected: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        line: Option<usize>,
+    },
+    ListNotFound {
+        list: &'a ListName,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        line: Option<usize>,
+    },
+    Unrecognized {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        line: Option<usize>,
+    },
+    Help {
+        commands: &'static [&'static str],
+    },
+    Message {
+        message: String,
+    },
+    Error {
+        message: String,
+        fatal: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        line: Option<usize>,
+    },
+}
+
+const COMMANDS: &[&str] = &[
+    "y",
+    "add [-p <priority>] [--parent <n>] <message>",
+    "list",
+    "done <n>",
+    "undone <n>",
+    "edit <n>",
+    "priority <n> <priority>",
+    "due <n> <when|none>",
+    "repeat <n> <rule|none>",
+    "today",
+    "overdue",
+    "next",
+    "block <n> <blocker>",
+    "unblock <n> <blocker>",
+    "tag <n> [+<tag>] [-<tag>]",
+    "list <tag>",
+    "lists",
+    "lists new <name>",
+    "lists rename <name> <new name>",
+    "lists rm <name>",
+    "switch <name>",
+    "move <n> <list>",
+    "mv <n> <position>",
+    "top <n>",
+    "bottom <n>",
+    "rm <n>",
+    "clear",
+    "trash",
+    "restore <n>",
+    "purge [--older-than <age>]",
+    "archive",
+    "list --archived",
+    "unarchive <n>",
+    "undo",
+    "redo",
+    "help",
+    "quit",
+];
+
+impl JsonInterface {
+    pub fn new(style: JsonStyle, input: Input) -> Self {
+        JsonInterface {
+            input,
+            stdout: std::io::stdout(),
+            style,
+        }
+    }
+
+    fn emit(&mut self, event: &Event) -> Result<(), TerminalError> {
+        let json = match self.style {
+            JsonStyle::Pretty => serde_json::to_string_pretty(event),
+            JsonStyle::Compact => serde_json::to_string(event),
+        }
+        .expect("events always serialize");
+        writeln!(self.stdout, "{json}").map_err(TerminalError::Stdout)
+    }
+
+    fn read_message(&mut self) -> Result<String, TerminalError> {
+        loop {
+            let message = self.input()?;
+            if !message.is_empty() {
+                return Ok(message);
+            }
+            self.emit(&Event::Error {
+                message: "Please input a valid todo.".to_string(),
+                fatal: false,
+                line: self.input.line(),
+            })?;
+        }
+    }
+}
+
+impl UserInterface for JsonInterface {
+    fn print_error(&mut self, error: &TerminalError) {
+        let event = Event::Error {
+            message: error.to_string(),
+            fatal: error.is_fatal(),
+            line: self.input.line(),
+        };
+        self.emit(&event).unwrap();
+    }
+
+    fn prompt_new_todo(&mut self) -> Result<Todo, TerminalError> {
+        let message = self.read_message()?;
+        Ok(Todo::new(message, Uuid::new_v4()))
+    }
+
+    fn prompt_edit_todo(&mut self, _todo: &Todo) -> Result<String, TerminalError> {
+        self.read_message()
+    }
+
+    fn show_todo(
+        &mut self,
+        position: usize,
+        todo: &Todo,
+        depth: usize,
+        progress: Option<Progress>,
+        blocked: bool,
+    ) -> Result<(), TerminalError> {
+        self.emit(&Event::Todo(ListedTodo {
+            position,
+            depth,
+            progress,
+            blocked,
+            todo,
+        }))
+    }
+
+    fn alert_unrecognized(&mut self) -> Result<(), TerminalError> {
+        self.emit(&Event::Unrecognized {
+            line: self.input.line(),
+        })
+    }
+
+    fn clear_todo_message(&mut self) -> Result<(), TerminalError> {
+        self.emit(&Event::Cleared)
+    }
+
+    fn add_todo_message(&mut self, todo: &Todo) -> Result<(), TerminalError> {
+        self.emit(&Event::Added { id: todo.id })
+    }
+
+    fn remove_todo_message(
+        &mut self,
+        uuid: Uuid,
+        todos_modified: u64,
+    ) -> Result<(), TerminalError> {
+        self.emit(&Event::Removed {
+            id: uuid,
+            rows_affected: todos_modified,
+        })
+    }
+
+    fn show_trash(&mut self, trash: Todos) -> Result<(), TerminalError> {
+        let todos = trash
+            .iter()
+            .enumerate()
+            .map(|(index, todo)| ShownTodo {
+                position: index + 1,
+                todo,
+            })
+            .collect();
+        self.emit(&Event::Trash { todos })
+    }
+
+    fn restore_todo_message(
+        &mut self,
+        uuid: Uuid,
+        todos_modified: u64,
+    ) -> Result<(), TerminalError> {
+        self.emit(&Event::Restored {
+            id: uuid,
+            rows_affected: todos_modified,
+        })
+    }
+
+    fn purge_message(&mut self, purged: u64) -> Result<(), TerminalError> {
+        self.emit(&Event::Purged {
+            rows_affected: purged,
+        })
+    }
+
+    fn show_archive(&mut self, archive: Todos) -> Result<(), TerminalError> {
+        let todos = archive
+            .iter()
+            .enumerate()
+            .map(|(index, todo)| ShownTodo {
+                position: index + 1,
+                todo,
+            })
+            .collect();
+        self.emit(&Event::Archive { todos })
+    }
+
+    fn archive_message(&mut self, archived: u64) -> Result<(), TerminalError> {
+        self.emit(&Event::Archived {
+            rows_affected: archived,
+        })
+    }
+
+    fn unarchive_todo_message(
+        &mut self,
+        uuid: Uuid,
+        todos_modified: u64,
+    ) -> Result<(), TerminalError> {
+        self.emit(&Event::Unarchived {
+            id: uuid,
+            rows_affected: todos_modified,
+        })
+    }
+
+    fn undo_message(&mut self, command: &str) -> Result<(), TerminalError> {
+        self.emit(&Event::Reverted { command })
+    }
+
+    fn redo_message(&mut self, command: &str) -> Result<(), TerminalError> {
+        self.emit(&Event::Reapplied { command })
+    }
+
+    fn history_empty_message(&mut self, command: &str) -> Result<(), TerminalError> {
+        self.emit(&Event::HistoryEmpty {
+            command,
+            line: self.input.line(),
+        })
+    }
+
+    fn user_intention(&mut self) -> Result<UserOptions, TerminalError> {
+        let user_input = match self.input() {
+            Err(TerminalError::EndOfInput) => return Ok(UserOptions::Quit),
+            result => result?,
+        };
+        parse_user_option(&user_input, || self.prompt_new_todo())
+    }
+
+    fn input(&mut self) -> Result<String, TerminalError> {
+        self.input.read_line()
+    }
+
+    fn write_interface(&mut self, string: &dyn Display) -> Result<(), TerminalError> {
+        let message = strip_ansi_codes(&string.to_string()).into_owned();
+        self.emit(&Event::Message { message })
+    }
+
+    fn show_help(&mut self) -> Result<(), TerminalError> {
+        self.emit(&Event::Help { commands: COMMANDS })
+    }
+
+    fn show_todo_list(&mut self, todo_list: Todos) -> Result<(), TerminalError> {
+        let todos = todo_list
+            .iter()
+            .enumerate()
+            .map(|(index, todo)| ListedTodo {
+                position: index + 1,
+                depth: todo_list.depth(todo),
+                progress: todo_list.progress(todo.id),
+                blocked: !todo.done && todo_list.is_blocked(todo),
+                todo,
+            })
+            .collect();
+        self.emit(&Event::List { todos })
+    }
+
+    fn mark_done_message(&mut self, uuid: Uuid, todos_modified: u64) -> Result<(), TerminalError> {
+        self.emit(&Event::Done {
+            id: uuid,
+            rows_affected: todos_modified,
+        })
+    }
+
+    fn mark_undone_message(
+        &mut self,
+        uuid: Uuid,
+        todos_modified: u64,
+    ) -> Result<(), TerminalError> {
+        self.emit(&Event::Undone {
+            id: uuid,
+            rows_affected: todos_modified,
+        })
+    }
+
+    fn edit_todo_message(&mut self, uuid: Uuid, todos_modified: u64) -> Result<(), TerminalError> {
+        self.emit(&Event::Edited {
+            id: uuid,
+            rows_affected: todos_modified,
+        })
+    }
+
+    fn priority_message(
+        &mut self,
+        uuid: Uuid,
+        priority: Priority,
+        todos_modified: u64,
+    ) -> Result<(), TerminalError> {
+        self.emit(&Event::Prioritized {
+            id: uuid,
+            priority,
+            rows_affected: todos_modified,
+        })
+    }
+
+    fn tags_message(
+        &mut self,
+        uuid: Uuid,
+        add: &[Tag],
+        remove: &[Tag],
+        todos_modified: u64,
+    ) -> Result<(), TerminalError> {
+        self.emit(&Event::Tagged {
+            id: uuid,
+            added: add,
+            removed: remove,
+            rows_affected: todos_modified,
+        })
+    }
+
+    fn show_lists(
+        &mut self,
+        lists: Vec<TodoList>,
+        current: &ListName,
+    ) -> Result<(), TerminalError> {
+        self.emit(&Event::Lists { lists, current })
+    }
+
+    fn list_created_message(&mut self, name: &ListName) -> Result<(), TerminalError> {
+        self.emit(&Event::ListCreated { name })
+    }
+
+    fn list_renamed_message(
+        &mut self,
+        from: &ListName,
+        to: &ListName,
+    ) -> Result<(), TerminalError> {
+        self.emit(&Event::ListRenamed { from, to })
+    }
+
+    fn list_deleted_message(&mut self, name: &ListName) -> Result<(), TerminalError> {
+        self.emit(&Event::ListDeleted { name })
+    }
+
+    fn switch_list_message(&mut self, name: &ListName) -> Result<(), TerminalError> {
+        self.emit(&Event::Switched { list: name })
+    }
+
+    fn move_todo_message(
+        &mut self,
+        uuid: Uuid,
+        list: &ListName,
+        todos_modified: u64,
+    ) -> Result<(), TerminalError> {
+        self.emit(&Event::Moved {
+            id: uuid,
+            list,
+            rows_affected: todos_modified,
+        })
+    }
+
+    fn reorder_todo_message(
+        &mut self,
+        uuid: Uuid,
+        todos_modified: u64,
+    ) -> Result<(), TerminalError> {
+        self.emit(&Event::Reordered {
+            id: uuid,
+            rows_affected: todos_modified,
+        })
+    }
+
+    fn due_message(
+        &mut self,
+        uuid: Uuid,
+        due: Option<Due>,
+        todos_modified: u64,
+    ) -> Result<(), TerminalError> {
+        self.emit(&Event::DueSet {
+            id: uuid,
+            due,
+            rows_affected: todos_modified,
+        })
+    }
+
+    fn recurrence_message(
+        &mut self,
+        uuid: Uuid,
+        recurrence: Option<Recurrence>,
+        todos_modified: u64,
+    ) -> Result<(), TerminalError> {
+        self.emit(&Event::RecurrenceSet {
+            id: uuid,
+            recurrence,
+            rows_affected: todos_modified,
+        })
+    }
+
+    fn next_occurrence_message(&mut self, next: &Todo) -> Result<(), TerminalError> {
+        self.emit(&Event::Recurred {
+            id: next.id,
+            due: next.due,
+        })
+    }
+
+    fn block_message(
+        &mut self,
+        uuid: Uuid,
+        blocker: Uuid,
+        todos_modified: u64,
+    ) -> Result<(), TerminalError> {
+        self.emit(&Event::Blocked {
+            id: uuid,
+            blocker,
+            rows_affected: todos_modified,
+        })
+    }
+
+    fn unblock_message(
+        &mut self,
+        uuid: Uuid,
+        blocker: Uuid,
+        todos_modified: u64,
+    ) -> Result<(), TerminalError> {
+        self.emit(&Event::Unblocked {
+            id: uuid,
+            blocker,
+            rows_affected: todos_modified,
+        })
+    }
+
+    fn open_blockers_warning(
+        &mut self,
+        uuid: Uuid,
+        blockers: &[Uuid],
+    ) -> Result<(), TerminalError> {
+        self.emit(&Event::StillBlocked {
+            id: uuid,
+            blocked_by: blockers,
+        })
+    }
+
+    fn report_not_found(&mut self) -> Result<(), TerminalError> {
+        self.emit(&Event::NotFound {
+            rows_affected: 0,
+            line: self.input.line(),
+        })
+    }
+
+    fn report_list_not_found(&mut self, name: &ListName) -> Result<(), TerminalError> {
+        self.emit(&Event::ListNotFound {
+            list: name,
+            line: self.input.line(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn should_serialize_listed_todos_with_their_position() {
+        let parent = Uuid::new_v4();
+        let todo = Todo::new("Buy milk".to_string(), Uuid::new_v4()).with_parent(parent);
+        let event = Event::List {
+            todos: vec![ListedTodo {
+                position: 1,
+                depth: 1,
+                progress: Some(Progress { done: 1, total: 2 }),
+                blocked: false,
+                todo: &todo,
+            }],
+        };
+
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            json!({
+                "event": "list",
+                "todos": [{
+                    "position": 1,
+                    "depth": 1,
+                    "progress": { "done": 1, "total": 2 },
+                    "blocked": false,
+                    "message": "Buy milk",
+                    "done": false,
+                    "id": todo.id.to_string(),
+                    "priority": "normal",
+                    "due": null,
+                    "created_at": todo.created_at,
+                    "updated_at": todo.updated_at,
+                    "completed_at": null,
+                    "tags": [],
+                    "parent": parent.to_string(),
+                    "recurrence": null,
+                    "blocked_by": [],
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn should_serialize_the_tags_of_listed_todos() {
+        let todo = Todo::new("Buy milk @store #errands".to_string(), Uuid::new_v4());
+        let event = Event::List {
+            todos: vec![ListedTodo {
+                position: 1,
+                depth: 0,
+                progress: None,
+                blocked: false,
+                todo: &todo,
+            }],
+        };
+
+        let value = serde_json::to_value(&event).unwrap();
+        assert_eq!(value["todos"][0]["tags"], json!(["@store", "#errands"]));
+    }
+
+    #[test]
+    fn should_serialize_operation_results() {
+        let id = Uuid::new_v4();
+
+        assert_eq!(
+            serde_json::to_value(Event::Removed {
+                id,
+                rows_affected: 1
+            })
+            .unwrap(),
+            json!({ "event": "removed", "id": id.to_string(), "rows_affected": 1 })
+        );
+        assert_eq!(
+            serde_json::to_value(Event::NotFound {
+                rows_affected: 0,
+                line: None
+            })
+            .unwrap(),
+            json!({ "event": "not_found", "rows_affected": 0 })
+        );
+        assert_eq!(
+            serde_json::to_value(Event::Error {
+                message: "Invalid todo".to_string(),
+                fatal: false,
+                line: Some(3)
+            })
+            .unwrap(),
+            json!({ "event": "error", "message": "Invalid todo", "fatal": false, "line": 3 })
+        );
+    }
+}